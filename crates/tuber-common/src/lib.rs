@@ -1,2 +1,3 @@
 pub mod tilemap;
+pub mod time;
 pub mod transform;
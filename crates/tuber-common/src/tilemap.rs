@@ -1,4 +1,22 @@
-use std::collections::HashSet;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+
+/// Flags stored in the high bits of Tiled global tile ids
+const TILED_FLIP_FLAGS: u32 = 0xE000_0000;
+const TILED_HORIZONTAL_FLIP_FLAG: u32 = 0x8000_0000;
+const TILED_VERTICAL_FLIP_FLAG: u32 = 0x4000_0000;
+const TILED_DIAGONAL_FLIP_FLAG: u32 = 0x2000_0000;
+
+#[derive(Debug)]
+pub enum TiledMapError {
+    FileOpenError(std::io::Error),
+    SerdeError(serde_json::error::Error),
+    UnsupportedOrientation(String),
+    UnsupportedInfiniteMap,
+    MissingTileset,
+}
 
 pub struct Tilemap {
     pub width: usize,
@@ -24,17 +42,273 @@ impl Tilemap {
             tiles: vec![Tile::with_tags(default_tags); width * height],
         }
     }
+
+    /// Loads an orthogonal map with a single tileset exported by Tiled in the JSON format
+    ///
+    /// Every tile is tagged with its id in the tileset and its Tiled class if it has one.
+    /// The tile layers are merged, a tile holding the tags of every layer and the flip of
+    /// the topmost one.
+    pub fn from_tiled_json(path: &str) -> Result<Self, TiledMapError> {
+        let file = File::open(path).map_err(TiledMapError::FileOpenError)?;
+        let tiled_map: TiledMap =
+            serde_json::from_reader(BufReader::new(file)).map_err(TiledMapError::SerdeError)?;
+        Self::from_tiled_map(tiled_map)
+    }
+
+    fn from_tiled_map(tiled_map: TiledMap) -> Result<Self, TiledMapError> {
+        if tiled_map.orientation != "orthogonal" {
+            return Err(TiledMapError::UnsupportedOrientation(tiled_map.orientation));
+        }
+        if tiled_map.infinite {
+            return Err(TiledMapError::UnsupportedInfiniteMap);
+        }
+        let tileset = tiled_map
+            .tilesets
+            .first()
+            .ok_or(TiledMapError::MissingTileset)?;
+        let tile_classes: HashMap<u32, &str> = tileset
+            .tiles
+            .iter()
+            .filter_map(|tile| {
+                let class = tile
+                    .class
+                    .as_deref()
+                    .or(tile.tile_type.as_deref())?;
+                Some((tile.id, class))
+            })
+            .collect();
+
+        let mut tilemap = Self::new(
+            tiled_map.width,
+            tiled_map.height,
+            tiled_map.tilewidth,
+            tiled_map.tileheight,
+            &[],
+        );
+        for layer in tiled_map
+            .layers
+            .iter()
+            .filter(|layer| layer.layer_type == "tilelayer")
+        {
+            for (tile, &gid) in tilemap.tiles.iter_mut().zip(&layer.data) {
+                let flip = TileFlip::from_tiled_gid(gid);
+                let gid = gid & !TILED_FLIP_FLAGS;
+                if gid < tileset.firstgid {
+                    continue;
+                }
+
+                tile.flip = flip;
+                let tile_id = gid - tileset.firstgid;
+                tile.tags.insert(tile_id.to_string());
+                if let Some(class) = tile_classes.get(&tile_id) {
+                    tile.tags.insert((*class).to_owned());
+                }
+            }
+        }
+
+        Ok(tilemap)
+    }
+
+    /// Returns the coordinates of the tile containing the given point, relative to the tilemap's origin
+    pub fn tile_at(&self, local_x: f32, local_y: f32) -> Option<(usize, usize)> {
+        if local_x < 0.0 || local_y < 0.0 {
+            return None;
+        }
+
+        let tile_x = (local_x / self.tile_width as f32) as usize;
+        let tile_y = (local_y / self.tile_height as f32) as usize;
+        if tile_x >= self.width || tile_y >= self.height {
+            return None;
+        }
+
+        Some((tile_x, tile_y))
+    }
 }
 
 #[derive(Clone)]
 pub struct Tile {
     pub tags: HashSet<String>,
+    pub flip: TileFlip,
 }
 
 impl Tile {
     pub fn with_tags(tags: &[String]) -> Self {
         Self {
             tags: tags.iter().cloned().map(|s| s.to_owned()).collect(),
+            flip: TileFlip::default(),
+        }
+    }
+}
+
+/// How the texture of a tile is flipped, the way Tiled encodes it
+///
+/// The diagonal flip swaps the x and y axes and is applied before the other two,
+/// combining them rotates the tile by quarter turns.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct TileFlip {
+    pub horizontal: bool,
+    pub vertical: bool,
+    pub diagonal: bool,
+}
+
+impl TileFlip {
+    /// Returns the flip rotating a tile clockwise by the given number of quarter turns
+    pub fn rotation(quarter_turns: u32) -> Self {
+        match quarter_turns % 4 {
+            0 => Self::default(),
+            1 => Self {
+                horizontal: true,
+                vertical: false,
+                diagonal: true,
+            },
+            2 => Self {
+                horizontal: true,
+                vertical: true,
+                diagonal: false,
+            },
+            _ => Self {
+                horizontal: false,
+                vertical: true,
+                diagonal: true,
+            },
+        }
+    }
+
+    fn from_tiled_gid(gid: u32) -> Self {
+        Self {
+            horizontal: gid & TILED_HORIZONTAL_FLIP_FLAG != 0,
+            vertical: gid & TILED_VERTICAL_FLIP_FLAG != 0,
+            diagonal: gid & TILED_DIAGONAL_FLIP_FLAG != 0,
         }
     }
 }
+
+#[derive(Deserialize)]
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tilewidth: usize,
+    tileheight: usize,
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<TiledLayer>,
+    tilesets: Vec<TiledTileset>,
+}
+
+#[derive(Deserialize)]
+struct TiledLayer {
+    #[serde(rename = "type")]
+    layer_type: String,
+    #[serde(default)]
+    data: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct TiledTileset {
+    firstgid: u32,
+    #[serde(default)]
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize)]
+struct TiledTile {
+    id: u32,
+    class: Option<String>,
+    #[serde(rename = "type")]
+    tile_type: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tilemap() -> Tilemap {
+        Tilemap::new(4, 3, 16, 8, &[])
+    }
+
+    #[test]
+    fn tile_at_in_bounds() {
+        let tilemap = tilemap();
+        assert_eq!(tilemap.tile_at(0.0, 0.0), Some((0, 0)));
+        assert_eq!(tilemap.tile_at(20.0, 9.0), Some((1, 1)));
+        assert_eq!(tilemap.tile_at(63.9, 23.9), Some((3, 2)));
+    }
+
+    #[test]
+    fn tile_at_edges() {
+        let tilemap = tilemap();
+        assert_eq!(tilemap.tile_at(16.0, 8.0), Some((1, 1)));
+        assert_eq!(tilemap.tile_at(64.0, 0.0), None);
+        assert_eq!(tilemap.tile_at(0.0, 24.0), None);
+    }
+
+    #[test]
+    fn tile_at_out_of_bounds() {
+        let tilemap = tilemap();
+        assert_eq!(tilemap.tile_at(-0.1, 0.0), None);
+        assert_eq!(tilemap.tile_at(0.0, -5.0), None);
+        assert_eq!(tilemap.tile_at(1000.0, 1000.0), None);
+    }
+
+    #[test]
+    fn from_tiled_json() {
+        let tilemap = Tilemap::from_tiled_json(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/tiled_map.json"
+        ))
+        .unwrap();
+
+        assert_eq!(tilemap.width, 3);
+        assert_eq!(tilemap.height, 2);
+        assert_eq!(tilemap.tile_width, 16);
+        assert_eq!(tilemap.tile_height, 8);
+        assert_eq!(tilemap.tiles.len(), 6);
+
+        assert!(tilemap.tiles[0].tags.contains("0"));
+        assert!(tilemap.tiles[0].tags.contains("water"));
+        assert!(tilemap.tiles[1].tags.contains("1"));
+        assert!(tilemap.tiles[1].tags.contains("sand"));
+        assert!(tilemap.tiles[2].tags.contains("2"));
+        assert_eq!(tilemap.tiles[2].tags.len(), 1);
+        assert!(tilemap.tiles[3].tags.is_empty());
+        assert!(tilemap.tiles[4].tags.contains("1"));
+        assert!(tilemap.tiles[4].tags.contains("2"));
+        assert!(tilemap.tiles[5].tags.contains("0"));
+
+        assert_eq!(
+            tilemap.tiles[5].flip,
+            TileFlip {
+                horizontal: true,
+                vertical: false,
+                diagonal: false,
+            }
+        );
+        assert!(tilemap.tiles[..5]
+            .iter()
+            .all(|tile| tile.flip == TileFlip::default()));
+    }
+
+    #[test]
+    fn tiled_flip_flags() {
+        assert_eq!(TileFlip::from_tiled_gid(1), TileFlip::default());
+        assert_eq!(TileFlip::from_tiled_gid(0xA000_0001), TileFlip::rotation(1));
+        assert_eq!(TileFlip::from_tiled_gid(0xC000_0001), TileFlip::rotation(2));
+        assert_eq!(TileFlip::from_tiled_gid(0x6000_0001), TileFlip::rotation(3));
+        assert_eq!(TileFlip::rotation(4), TileFlip::rotation(0));
+    }
+
+    #[test]
+    fn from_tiled_json_rejects_isometric_maps() {
+        let tiled_map: TiledMap = serde_json::from_str(
+            r#"{"width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+                "orientation": "isometric", "layers": [], "tilesets": [{"firstgid": 1}]}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            Tilemap::from_tiled_map(tiled_map),
+            Err(TiledMapError::UnsupportedOrientation(_))
+        ));
+    }
+}
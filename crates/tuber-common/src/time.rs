@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+
+/// The time elapsed since the last engine step, in seconds
+pub struct DeltaTime(pub f64);
+
+/// A source of monotonic time
+pub trait Clock {
+    /// Returns the time elapsed since the origin of the clock
+    fn now(&self) -> Duration;
+}
+
+/// The clock of the system, whose origin is its creation
+pub struct SystemClock(Instant);
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
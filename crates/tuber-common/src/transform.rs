@@ -1,4 +1,4 @@
-use nalgebra::{Matrix4, Vector3};
+use nalgebra::{Matrix4, Point3, Vector3};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Transform2D {
@@ -6,6 +6,8 @@ pub struct Transform2D {
     pub angle: f32,
     pub rotation_center: (f32, f32),
     pub scale: (f32, f32),
+    /// The depth of the transform, elements with a greater z are drawn over the others
+    pub z: f32,
 }
 
 impl Default for Transform2D {
@@ -15,10 +17,46 @@ impl Default for Transform2D {
             angle: 0.0,
             rotation_center: (0.0, 0.0),
             scale: (1.0, 1.0),
+            z: 0.0,
         }
     }
 }
 
+impl Transform2D {
+    /// Returns the transform moved to the given translation
+    pub fn with_translation(self, translation: (f32, f32)) -> Self {
+        Self {
+            translation,
+            ..self
+        }
+    }
+
+    /// Moves the transform by the given offset
+    pub fn translate(&mut self, offset: (f32, f32)) {
+        self.translation.0 += offset.0;
+        self.translation.1 += offset.1;
+    }
+
+    /// Rotates the transform around its rotation center, in degrees
+    pub fn rotate(&mut self, angle: f32) {
+        self.angle += angle;
+    }
+
+    /// Returns the matrix applying the given child transform then this one,
+    /// such as the world matrix of an entity relative to its parent
+    pub fn combine(&self, child: &Transform2D) -> Matrix4<f32> {
+        self.into_matrix4() * child.into_matrix4()
+    }
+
+    /// Applies the transform to a point
+    pub fn transform_point(&self, point: (f32, f32)) -> (f32, f32) {
+        let transformed_point = self
+            .into_matrix4()
+            .transform_point(&Point3::new(point.0, point.1, 0.0));
+        (transformed_point.x, transformed_point.y)
+    }
+}
+
 pub trait IntoMatrix4 {
     fn into_matrix4(self) -> Matrix4<f32>;
 }
@@ -29,9 +67,102 @@ impl IntoMatrix4 for Transform2D {
             Vector3::new(self.rotation_center.0, self.rotation_center.1, 0.0);
 
         Matrix4::new_nonuniform_scaling(&Vector3::new(self.scale.0, self.scale.1, 1.0))
-            * Matrix4::new_translation(&Vector3::new(self.translation.0, self.translation.1, 0.0))
+            * Matrix4::new_translation(&Vector3::new(
+                self.translation.0,
+                self.translation.1,
+                self.z,
+            ))
             * Matrix4::new_translation(&translate_to_rotation_center.clone())
             * Matrix4::new_rotation(Vector3::new(0.0, 0.0, self.angle.to_radians()))
             * Matrix4::new_translation(&-&translate_to_rotation_center)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn translate_and_rotate_accumulate() {
+        let mut transform = Transform2D::default().with_translation((10.0, 20.0));
+        transform.translate((5.0, -5.0));
+        transform.rotate(30.0);
+        transform.rotate(15.0);
+
+        assert_eq!(transform.translation, (15.0, 15.0));
+        assert_eq!(transform.angle, 45.0);
+    }
+
+    #[test]
+    fn transform_point_matches_hand_computed_matrices() {
+        let transform = Transform2D {
+            translation: (10.0, 20.0),
+            angle: 90.0,
+            rotation_center: (1.0, 0.0),
+            ..Default::default()
+        };
+        // Rotating (2, 0) by 90 degrees around (1, 0) gives (1, 1), then it is translated
+        assert_close(transform.transform_point((2.0, 0.0)), (11.0, 21.0));
+
+        let scaled_transform = Transform2D {
+            translation: (10.0, 20.0),
+            scale: (2.0, 3.0),
+            ..Default::default()
+        };
+        assert_close(scaled_transform.transform_point((1.0, 1.0)), (22.0, 63.0));
+    }
+
+    #[test]
+    fn into_matrix4_encodes_the_z_translation() {
+        let transform = Transform2D {
+            translation: (10.0, 20.0),
+            angle: 90.0,
+            z: 5.0,
+            ..Default::default()
+        };
+
+        let point = transform
+            .into_matrix4()
+            .transform_point(&Point3::new(1.0, 2.0, 0.0));
+        assert_eq!(point.z, 5.0);
+        assert_close(transform.transform_point((1.0, 2.0)), (8.0, 21.0));
+    }
+
+    #[test]
+    fn combine_is_associative() {
+        let parent = Transform2D {
+            translation: (100.0, 50.0),
+            angle: 30.0,
+            ..Default::default()
+        };
+        let child = Transform2D {
+            translation: (10.0, 0.0),
+            angle: 45.0,
+            rotation_center: (5.0, 5.0),
+            ..Default::default()
+        };
+        let grandchild = Transform2D {
+            translation: (-3.0, 7.0),
+            scale: (2.0, 2.0),
+            ..Default::default()
+        };
+
+        let left = parent.combine(&child) * grandchild.into_matrix4();
+        let right = parent.into_matrix4() * child.combine(&grandchild);
+        assert!((left - right).abs().max() < 1e-3);
+
+        let point = left.transform_point(&Point3::new(1.0, 2.0, 0.0));
+        let expected =
+            parent.transform_point(child.transform_point(grandchild.transform_point((1.0, 2.0))));
+        assert_close((point.x, point.y), expected);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 pub mod keyboard {
     #[derive(Debug, Copy, Clone)]
     pub enum Key {
@@ -48,14 +50,17 @@ pub mod keyboard {
 }
 
 pub mod mouse {
-    #[derive(Debug, Copy, Clone)]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     pub enum Button {
         Left,
         Right,
         Middle,
+        /// An extra button, identified by the platform
+        Other(u16),
     }
 }
 
+#[derive(Debug, Copy, Clone)]
 pub enum Input {
     KeyDown(keyboard::Key),
     KeyUp(keyboard::Key),
@@ -67,20 +72,38 @@ pub enum Input {
 pub struct InputState {
     key_state: [bool; 43],
     previous_key_state: [bool; 43],
-    mouse_button_state: [bool; 3],
-    previous_mouse_button_state: [bool; 3],
+    /// The mouse buttons currently pressed
+    mouse_button_state: HashSet<mouse::Button>,
+    previous_mouse_button_state: HashSet<mouse::Button>,
+    /// The mouse position at the last press of each button
+    mouse_down_positions: HashMap<mouse::Button, (f32, f32)>,
     last_mouse_position: (f32, f32),
     mouse_moved: bool,
+    /// Whether a mouse motion has been received yet, so the first one has no delta
+    mouse_position_known: bool,
+    /// The mouse motion accumulated since the start of the frame
+    mouse_delta: (f32, f32),
+    /// The key and button states at the end of the last fixed step and the mouse motion
+    /// accumulated since, swapped with the per-frame ones while the fixed steps run
+    fixed_step_key_state: [bool; 43],
+    fixed_step_mouse_button_state: HashSet<mouse::Button>,
+    fixed_step_mouse_delta: (f32, f32),
 }
 impl InputState {
     pub fn new() -> Self {
         Self {
             key_state: [false; 43],
             previous_key_state: [false; 43],
-            mouse_button_state: [false; 3],
-            previous_mouse_button_state: [false; 3],
+            mouse_button_state: HashSet::new(),
+            previous_mouse_button_state: HashSet::new(),
+            mouse_down_positions: HashMap::new(),
             last_mouse_position: (0.0, 0.0),
             mouse_moved: false,
+            mouse_position_known: false,
+            mouse_delta: (0.0, 0.0),
+            fixed_step_key_state: [false; 43],
+            fixed_step_mouse_button_state: HashSet::new(),
+            fixed_step_mouse_delta: (0.0, 0.0),
         }
     }
 
@@ -88,36 +111,48 @@ impl InputState {
         match input {
             Input::KeyDown(key) => self.key_state[key as usize],
             Input::KeyUp(key) => !self.key_state[key as usize],
-            Input::MouseButtonDown(button) => self.mouse_button_state[button as usize],
-            Input::MouseButtonUp(button) => !self.mouse_button_state[button as usize],
+            Input::MouseButtonDown(button) => self.mouse_button_state.contains(&button),
+            Input::MouseButtonUp(button) => !self.mouse_button_state.contains(&button),
             Input::MouseMotion(..) => self.mouse_moved,
         }
     }
 
+    /// Returns whether an input was active at the start of the frame
     pub fn was(&self, input: Input) -> bool {
         match input {
             Input::KeyDown(key) => self.previous_key_state[key as usize],
             Input::KeyUp(key) => !self.previous_key_state[key as usize],
-            Input::MouseButtonDown(button) => self.previous_mouse_button_state[button as usize],
-            Input::MouseButtonUp(button) => !self.previous_mouse_button_state[button as usize],
+            Input::MouseButtonDown(button) => self.previous_mouse_button_state.contains(&button),
+            Input::MouseButtonUp(button) => !self.previous_mouse_button_state.contains(&button),
             Input::MouseMotion(..) => unimplemented!(),
         }
     }
 
     pub fn handle_input(&mut self, input: Input) {
         self.mouse_moved = false;
-        self.previous_key_state = self.key_state.clone();
-        self.previous_mouse_button_state = self.mouse_button_state.clone();
         match input {
             Input::KeyDown(key) => self.key_state[key as usize] = true,
             Input::KeyUp(key) => self.key_state[key as usize] = false,
             Input::MouseButtonDown(button) => {
-                self.mouse_button_state[button as usize] = true;
+                self.mouse_button_state.insert(button);
+                self.mouse_down_positions
+                    .insert(button, self.last_mouse_position);
             }
             Input::MouseButtonUp(button) => {
-                self.mouse_button_state[button as usize] = false;
+                self.mouse_button_state.remove(&button);
             }
             Input::MouseMotion(new_position) => {
+                if self.mouse_position_known {
+                    let delta = (
+                        new_position.0 - self.last_mouse_position.0,
+                        new_position.1 - self.last_mouse_position.1,
+                    );
+                    self.mouse_delta.0 += delta.0;
+                    self.mouse_delta.1 += delta.1;
+                    self.fixed_step_mouse_delta.0 += delta.0;
+                    self.fixed_step_mouse_delta.1 += delta.1;
+                }
+                self.mouse_position_known = true;
                 self.last_mouse_position = new_position;
                 self.mouse_moved = true;
             }
@@ -127,4 +162,203 @@ impl InputState {
     pub fn mouse_position(&self) -> (f32, f32) {
         self.last_mouse_position
     }
+
+    /// Returns how much the mouse moved since the start of the frame
+    pub fn mouse_delta(&self) -> (f32, f32) {
+        self.mouse_delta
+    }
+
+    /// Resets the per-frame state, called by the engine after each step
+    ///
+    /// The key and button states are kept as the previous ones of the next frame.
+    pub fn new_frame(&mut self) {
+        self.previous_key_state = self.key_state;
+        self.previous_mouse_button_state = self.mouse_button_state.clone();
+        self.mouse_delta = (0.0, 0.0);
+    }
+
+    /// Makes the previous states and the mouse delta relative to the last fixed step instead of
+    /// the start of the frame, called by the engine before the fixed steps of a frame
+    ///
+    /// The engine calls `new_frame` after each fixed step, so an input pressed during a frame
+    /// without fixed steps is still just pressed in the next one, and only in that one.
+    pub(crate) fn start_fixed_steps(&mut self) {
+        self.swap_fixed_step_state();
+    }
+
+    /// Restores the per-frame previous states and mouse delta after the fixed steps of a frame
+    pub(crate) fn end_fixed_steps(&mut self) {
+        self.swap_fixed_step_state();
+    }
+
+    fn swap_fixed_step_state(&mut self) {
+        std::mem::swap(&mut self.previous_key_state, &mut self.fixed_step_key_state);
+        std::mem::swap(
+            &mut self.previous_mouse_button_state,
+            &mut self.fixed_step_mouse_button_state,
+        );
+        std::mem::swap(&mut self.mouse_delta, &mut self.fixed_step_mouse_delta);
+    }
+
+    /// Returns the mouse position at the last press of a button, `None` if it was never pressed
+    pub fn mouse_down_position(&self, button: mouse::Button) -> Option<(f32, f32)> {
+        self.mouse_down_positions.get(&button).copied()
+    }
+
+    /// Returns whether any of the inputs bound to an action is active
+    pub fn action_active(&self, input_map: &InputMap, action: &str) -> bool {
+        input_map
+            .bindings(action)
+            .iter()
+            .any(|&input| self.is(input))
+    }
+
+    /// Returns whether an action became active during the frame
+    pub fn action_just_pressed(&self, input_map: &InputMap, action: &str) -> bool {
+        let was_active = input_map.bindings(action).iter().any(|&input| match input {
+            Input::MouseMotion(..) => false,
+            input => self.was(input),
+        });
+
+        self.action_active(input_map, action) && !was_active
+    }
+}
+
+/// Maps named actions to the inputs triggering them, so that they can be rebound
+#[derive(Default)]
+pub struct InputMap {
+    bindings: HashMap<String, Vec<Input>>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an input triggering an action, an action can be bound to several inputs
+    pub fn bind(&mut self, action: &str, input: Input) {
+        self.bindings
+            .entry(action.to_owned())
+            .or_default()
+            .push(input);
+    }
+
+    /// Removes every input bound to an action
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Input] {
+        self.bindings
+            .get(action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::keyboard::Key;
+    use super::mouse::Button;
+    use super::*;
+
+    #[test]
+    fn mouse_down_position_is_recorded_on_click() {
+        let mut input_state = InputState::new();
+        assert_eq!(input_state.mouse_down_position(Button::Left), None);
+
+        input_state.handle_input(Input::MouseMotion((10.0, 20.0)));
+        input_state.handle_input(Input::MouseButtonDown(Button::Left));
+        input_state.handle_input(Input::MouseMotion((30.0, 40.0)));
+        input_state.handle_input(Input::MouseButtonUp(Button::Left));
+
+        assert_eq!(
+            input_state.mouse_down_position(Button::Left),
+            Some((10.0, 20.0))
+        );
+        assert_eq!(input_state.mouse_position(), (30.0, 40.0));
+        assert_eq!(input_state.mouse_down_position(Button::Right), None);
+    }
+
+    #[test]
+    fn action_bound_to_two_keys_is_activated_by_either() {
+        let mut input_map = InputMap::new();
+        input_map.bind("jump", Input::KeyDown(Key::Z));
+        input_map.bind("jump", Input::KeyDown(Key::Spacebar));
+        let mut input_state = InputState::new();
+        assert!(!input_state.action_active(&input_map, "jump"));
+
+        input_state.handle_input(Input::KeyDown(Key::Spacebar));
+        assert!(input_state.action_active(&input_map, "jump"));
+        assert!(input_state.action_just_pressed(&input_map, "jump"));
+
+        input_state.new_frame();
+        input_state.handle_input(Input::KeyUp(Key::Spacebar));
+        input_state.new_frame();
+        input_state.handle_input(Input::KeyDown(Key::Z));
+        assert!(input_state.action_active(&input_map, "jump"));
+        assert!(input_state.action_just_pressed(&input_map, "jump"));
+
+        input_state.new_frame();
+        input_state.handle_input(Input::KeyDown(Key::Z));
+        assert!(input_state.action_active(&input_map, "jump"));
+        assert!(!input_state.action_just_pressed(&input_map, "jump"));
+        assert!(!input_state.action_active(&input_map, "fire"));
+    }
+
+    #[test]
+    fn action_stays_just_pressed_after_a_mouse_motion_in_the_same_frame() {
+        let mut input_map = InputMap::new();
+        input_map.bind("fire", Input::KeyDown(Key::Spacebar));
+        input_map.bind("select", Input::MouseButtonDown(Button::Left));
+        let mut input_state = InputState::new();
+
+        input_state.handle_input(Input::KeyDown(Key::Spacebar));
+        input_state.handle_input(Input::MouseButtonDown(Button::Left));
+        input_state.handle_input(Input::MouseMotion((10.0, 20.0)));
+
+        assert!(input_state.action_just_pressed(&input_map, "fire"));
+        assert!(input_state.action_just_pressed(&input_map, "select"));
+    }
+
+    #[test]
+    fn action_is_no_longer_just_pressed_in_the_next_frame() {
+        let mut input_map = InputMap::new();
+        input_map.bind("fire", Input::KeyDown(Key::Spacebar));
+        input_map.bind("select", Input::MouseButtonDown(Button::Left));
+        let mut input_state = InputState::new();
+        input_state.handle_input(Input::KeyDown(Key::Spacebar));
+        input_state.handle_input(Input::MouseButtonDown(Button::Left));
+
+        input_state.new_frame();
+
+        assert!(input_state.action_active(&input_map, "fire"));
+        assert!(!input_state.action_just_pressed(&input_map, "fire"));
+        assert!(input_state.action_active(&input_map, "select"));
+        assert!(!input_state.action_just_pressed(&input_map, "select"));
+    }
+
+    #[test]
+    fn mouse_delta_is_the_difference_between_motions() {
+        let mut input_state = InputState::new();
+        input_state.handle_input(Input::MouseMotion((10.0, 20.0)));
+        assert_eq!(input_state.mouse_delta(), (0.0, 0.0));
+
+        input_state.handle_input(Input::MouseMotion((15.0, 12.0)));
+        assert_eq!(input_state.mouse_delta(), (5.0, -8.0));
+
+        input_state.new_frame();
+        assert_eq!(input_state.mouse_delta(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn extra_mouse_buttons_are_tracked() {
+        let mut input_state = InputState::new();
+
+        input_state.handle_input(Input::MouseButtonDown(Button::Other(4)));
+
+        assert!(input_state.is(Input::MouseButtonDown(Button::Other(4))));
+        assert!(input_state.is(Input::MouseButtonUp(Button::Other(5))));
+        assert!(input_state.was(Input::MouseButtonUp(Button::Other(4))));
+    }
 }
@@ -1,26 +1,51 @@
 use ecs::ecs::Ecs;
 use ecs::system::SystemBundle;
+use std::time::Duration;
 pub use tuber_ecs as ecs;
-use tuber_graphics::Graphics;
+use tuber_graphics::{Graphics, GraphicsError};
 
-use crate::input::InputState;
+use crate::input::{InputMap, InputState};
+use crate::window::WindowSettings;
+pub use tuber_common::time::{Clock, DeltaTime, SystemClock};
 
 pub mod input;
+pub mod window;
 
-pub struct DeltaTime(pub f64);
+/// The time step of the fixed system bundles, in seconds
+pub const FIXED_DELTA_TIME: f64 = 1.0 / 100.0;
+
+/// The greatest time step of a call to `step`, in seconds, so that a long frame isn't followed
+/// by a spiral of fixed steps catching up with it
+pub const MAX_DELTA_TIME: f64 = 0.25;
+
+/// Whether the engine is paused, only the bundles running while paused are stepped if it is
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Paused(pub bool);
+
+struct RegisteredSystemBundle {
+    system_bundle: SystemBundle,
+    runs_while_paused: bool,
+}
 
 pub struct Engine {
     ecs: Ecs,
-    system_bundles: Vec<SystemBundle>,
+    system_bundles: Vec<RegisteredSystemBundle>,
+    fixed_system_bundles: Vec<SystemBundle>,
+    fixed_step_accumulator: Duration,
 }
 
 impl Engine {
     pub fn new() -> Engine {
         let mut ecs = Ecs::new();
         ecs.insert_shared_resource(InputState::new());
+        ecs.insert_shared_resource(InputMap::new());
+        ecs.insert_shared_resource(WindowSettings::new());
+        ecs.insert_shared_resource(Paused::default());
         Self {
             ecs,
             system_bundles: vec![],
+            fixed_system_bundles: vec![],
+            fixed_step_accumulator: Duration::ZERO,
         }
     }
 
@@ -33,23 +58,129 @@ impl Engine {
         &mut self.ecs
     }
 
+    /// Adds a bundle of gameplay systems stepped once per frame, which aren't stepped while the
+    /// engine is paused
     pub fn add_system_bundle(&mut self, system_bundle: SystemBundle) {
-        self.system_bundles.push(system_bundle);
+        self.system_bundles.push(RegisteredSystemBundle {
+            system_bundle,
+            runs_while_paused: false,
+        });
+    }
+
+    /// Adds a bundle of systems stepped even while the engine is paused, such as the UI ones
+    pub fn add_system_bundle_running_while_paused(&mut self, system_bundle: SystemBundle) {
+        self.system_bundles.push(RegisteredSystemBundle {
+            system_bundle,
+            runs_while_paused: true,
+        });
+    }
+
+    /// Adds a bundle of systems stepped at a constant [`FIXED_DELTA_TIME`], such as the physics
+    /// ones, which aren't stepped while the engine is paused
+    ///
+    /// The fixed steps of a frame see the inputs pressed or released since the last fixed step,
+    /// so an input just pressed is seen by exactly one fixed step whatever the frame rate.
+    pub fn add_fixed_system_bundle(&mut self, system_bundle: SystemBundle) {
+        self.fixed_system_bundles.push(system_bundle);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.ecs.insert_shared_resource(Paused(paused));
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.ecs
+            .shared_resource::<Paused>()
+            .is_some_and(|paused| paused.0)
     }
 
+    /// Steps the engine by the time elapsed since the last frame, running as many fixed steps as
+    /// fit in the accumulated time then the per-frame bundles once
+    ///
+    /// The delta time is clamped to [`MAX_DELTA_TIME`].
     pub fn step(&mut self, delta_time: f64) {
+        let delta_time = delta_time.min(MAX_DELTA_TIME);
+        let paused = self.is_paused();
+        self.fixed_step(Duration::from_secs_f64(delta_time), paused);
+
         self.ecs.insert_shared_resource(DeltaTime(delta_time));
-        for bundle in &mut self.system_bundles {
-            bundle.step(&mut self.ecs);
+        for registered_bundle in &mut self.system_bundles {
+            if paused && !registered_bundle.runs_while_paused {
+                continue;
+            }
+
+            registered_bundle.system_bundle.step(&mut self.ecs);
+        }
+
+        let mut input_state = self.ecs.shared_resource_mut::<InputState>().unwrap();
+        input_state.new_frame();
+    }
+
+    fn fixed_step(&mut self, delta_time: Duration, paused: bool) {
+        if paused {
+            self.fixed_step_accumulator = Duration::ZERO;
+            return;
+        }
+
+        let fixed_delta_time = Duration::from_secs_f64(FIXED_DELTA_TIME);
+        self.fixed_step_accumulator += delta_time;
+        self.ecs
+            .shared_resource_mut::<InputState>()
+            .unwrap()
+            .start_fixed_steps();
+        while self.fixed_step_accumulator >= fixed_delta_time {
+            self.ecs
+                .insert_shared_resource(DeltaTime(fixed_delta_time.as_secs_f64()));
+            for bundle in &mut self.fixed_system_bundles {
+                bundle.step(&mut self.ecs);
+            }
+            self.ecs
+                .shared_resource_mut::<InputState>()
+                .unwrap()
+                .new_frame();
+            self.fixed_step_accumulator -= fixed_delta_time;
         }
+        self.ecs
+            .shared_resource_mut::<InputState>()
+            .unwrap()
+            .end_fixed_steps();
     }
 
-    pub fn ignite(mut self) -> Result<()> {
+    /// Steps the engine forever by the real time elapsed between the steps
+    pub fn ignite(self) -> Result<()> {
+        self.ignite_with_clock(SystemClock::new())
+    }
+
+    /// Steps the engine forever by the time elapsed between the steps as measured by a clock
+    pub fn ignite_with_clock(mut self, clock: impl Clock) -> Result<()> {
+        let mut last_step_time = clock.now();
         loop {
-            self.step(1.0);
+            last_step_time = self.step_since(&clock, last_step_time);
         }
     }
 
+    /// Steps the engine by the time elapsed since the last step and returns the time of this step
+    fn step_since(&mut self, clock: &impl Clock, last_step_time: Duration) -> Duration {
+        let step_time = clock.now();
+        self.step(step_time.saturating_sub(last_step_time).as_secs_f64());
+        step_time
+    }
+
+    pub fn set_window_title(&mut self, title: &str) {
+        let mut window_settings = self.ecs.shared_resource_mut::<WindowSettings>().unwrap();
+        window_settings.set_title(title);
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        let mut window_settings = self.ecs.shared_resource_mut::<WindowSettings>().unwrap();
+        window_settings.set_cursor_visible(visible);
+    }
+
+    pub fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        let mut window_settings = self.ecs.shared_resource_mut::<WindowSettings>().unwrap();
+        window_settings.set_cursor_grabbed(grabbed);
+    }
+
     pub fn on_window_resized(&mut self, width: u32, height: u32) {
         if let Some(mut graphics) = self.ecs.shared_resource_mut::<Graphics>() {
             graphics.on_window_resized(width, height);
@@ -64,4 +195,280 @@ pub trait TuberRunner {
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
-pub enum Error {}
+pub enum Error {
+    Graphics(GraphicsError),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Graphics(error) => write!(f, "graphics error: {}", error),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Graphics(error) => Some(error),
+            Error::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<GraphicsError> for Error {
+    fn from(error: GraphicsError) -> Self {
+        Error::Graphics(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn cursor_settings_are_stored_in_the_window_settings() {
+        let mut engine = Engine::new();
+        engine.set_window_title("Score: 10");
+        engine.set_cursor_visible(false);
+        engine.set_cursor_grabbed(true);
+
+        let window_settings = engine.ecs().shared_resource::<WindowSettings>().unwrap();
+        assert_eq!(window_settings.title(), "Score: 10");
+        assert!(!window_settings.cursor_visible());
+        assert!(window_settings.cursor_grabbed());
+    }
+
+    #[derive(Default)]
+    struct Counters {
+        gameplay: u32,
+        ui: u32,
+    }
+
+    #[test]
+    fn gameplay_bundles_are_not_stepped_while_paused() {
+        let mut engine = Engine::new();
+        engine.ecs().insert_shared_resource(Counters::default());
+        let mut gameplay_bundle = SystemBundle::new();
+        gameplay_bundle.add_system(|ecs: &mut Ecs| {
+            ecs.shared_resource_mut::<Counters>().unwrap().gameplay += 1;
+        });
+        engine.add_system_bundle(gameplay_bundle);
+        let mut ui_bundle = SystemBundle::new();
+        ui_bundle.add_system(|ecs: &mut Ecs| {
+            ecs.shared_resource_mut::<Counters>().unwrap().ui += 1;
+        });
+        engine.add_system_bundle_running_while_paused(ui_bundle);
+        let counters = |engine: &mut Engine| {
+            let counters = engine.ecs().shared_resource::<Counters>().unwrap();
+            (counters.gameplay, counters.ui)
+        };
+
+        engine.step(0.1);
+        assert_eq!(counters(&mut engine), (1, 1));
+
+        engine.set_paused(true);
+        assert!(engine.is_paused());
+        engine.step(0.1);
+        engine.step(0.1);
+        assert_eq!(counters(&mut engine), (1, 3));
+
+        engine.set_paused(false);
+        engine.step(0.1);
+        assert_eq!(counters(&mut engine), (2, 4));
+    }
+
+    #[derive(Default)]
+    struct StepCounters {
+        fixed: u32,
+        per_frame: u32,
+    }
+
+    #[test]
+    fn fixed_bundles_are_stepped_at_a_constant_rate() {
+        let mut engine = Engine::new();
+        engine.ecs().insert_shared_resource(StepCounters::default());
+        let mut fixed_bundle = SystemBundle::new();
+        fixed_bundle.add_system(|ecs: &mut Ecs| {
+            assert_eq!(
+                ecs.shared_resource::<DeltaTime>().unwrap().0,
+                FIXED_DELTA_TIME
+            );
+            ecs.shared_resource_mut::<StepCounters>().unwrap().fixed += 1;
+        });
+        engine.add_fixed_system_bundle(fixed_bundle);
+        let mut per_frame_bundle = SystemBundle::new();
+        per_frame_bundle.add_system(|ecs: &mut Ecs| {
+            ecs.shared_resource_mut::<StepCounters>().unwrap().per_frame += 1;
+        });
+        engine.add_system_bundle(per_frame_bundle);
+        let counters = |engine: &mut Engine| {
+            let counters = engine.ecs().shared_resource::<StepCounters>().unwrap();
+            (counters.fixed, counters.per_frame)
+        };
+
+        for _ in 0..60 {
+            engine.step(1.0 / 60.0);
+        }
+        assert_eq!(counters(&mut engine), (100, 60));
+
+        for _ in 0..50 {
+            engine.step(1.0 / 50.0);
+        }
+        assert_eq!(counters(&mut engine), (200, 110));
+
+        engine.set_paused(true);
+        engine.step(1.0);
+        assert_eq!(counters(&mut engine), (200, 110));
+    }
+
+    #[derive(Default)]
+    struct SeenInputs {
+        fixed: Vec<(bool, (f32, f32))>,
+        per_frame: Vec<(bool, (f32, f32))>,
+    }
+
+    fn seen_input(ecs: &Ecs) -> (bool, (f32, f32)) {
+        let input_state = ecs.shared_resource::<InputState>().unwrap();
+        let spacebar = input::Input::KeyDown(input::keyboard::Key::Spacebar);
+        (
+            input_state.is(spacebar) && !input_state.was(spacebar),
+            input_state.mouse_delta(),
+        )
+    }
+
+    #[test]
+    fn fixed_bundles_see_each_input_edge_once() {
+        let mut engine = Engine::new();
+        engine.ecs().insert_shared_resource(SeenInputs::default());
+        let mut fixed_bundle = SystemBundle::new();
+        fixed_bundle.add_system(|ecs: &mut Ecs| {
+            let seen_input = seen_input(ecs);
+            ecs.shared_resource_mut::<SeenInputs>()
+                .unwrap()
+                .fixed
+                .push(seen_input);
+        });
+        engine.add_fixed_system_bundle(fixed_bundle);
+        let mut per_frame_bundle = SystemBundle::new();
+        per_frame_bundle.add_system(|ecs: &mut Ecs| {
+            let seen_input = seen_input(ecs);
+            ecs.shared_resource_mut::<SeenInputs>()
+                .unwrap()
+                .per_frame
+                .push(seen_input);
+        });
+        engine.add_system_bundle(per_frame_bundle);
+
+        engine.handle_input(input::Input::MouseMotion((10.0, 20.0)));
+        engine.handle_input(input::Input::MouseMotion((15.0, 20.0)));
+        engine.handle_input(input::Input::KeyDown(input::keyboard::Key::Spacebar));
+        engine.step(FIXED_DELTA_TIME / 2.0);
+        engine.step(FIXED_DELTA_TIME * 2.5);
+
+        let seen_inputs = engine.ecs().shared_resource::<SeenInputs>().unwrap();
+        assert_eq!(
+            seen_inputs.fixed,
+            vec![(true, (5.0, 0.0)), (false, (0.0, 0.0)), (false, (0.0, 0.0))]
+        );
+        assert_eq!(
+            seen_inputs.per_frame,
+            vec![(true, (5.0, 0.0)), (false, (0.0, 0.0))]
+        );
+    }
+
+    #[test]
+    fn long_steps_are_clamped_to_the_max_delta_time() {
+        let mut engine = Engine::new();
+        engine.ecs().insert_shared_resource(StepCounters::default());
+        let mut fixed_bundle = SystemBundle::new();
+        fixed_bundle.add_system(|ecs: &mut Ecs| {
+            ecs.shared_resource_mut::<StepCounters>().unwrap().fixed += 1;
+        });
+        engine.add_fixed_system_bundle(fixed_bundle);
+        let mut per_frame_bundle = SystemBundle::new();
+        per_frame_bundle.add_system(|ecs: &mut Ecs| {
+            assert_eq!(
+                ecs.shared_resource::<DeltaTime>().unwrap().0,
+                MAX_DELTA_TIME
+            );
+            ecs.shared_resource_mut::<StepCounters>().unwrap().per_frame += 1;
+        });
+        engine.add_system_bundle(per_frame_bundle);
+
+        engine.step(60.0);
+
+        let counters = engine.ecs().shared_resource::<StepCounters>().unwrap();
+        assert_eq!(
+            (counters.fixed, counters.per_frame),
+            ((MAX_DELTA_TIME / FIXED_DELTA_TIME).round() as u32, 1)
+        );
+    }
+
+    struct FakeClock {
+        times: RefCell<VecDeque<Duration>>,
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.times.borrow_mut().pop_front().unwrap()
+        }
+    }
+
+    #[test]
+    fn ignited_steps_are_as_long_as_the_elapsed_time() {
+        let mut engine = Engine::new();
+        engine.ecs().insert_shared_resource(Vec::<f64>::new());
+        let mut per_frame_bundle = SystemBundle::new();
+        per_frame_bundle.add_system(|ecs: &mut Ecs| {
+            let delta_time = ecs.shared_resource::<DeltaTime>().unwrap().0;
+            ecs.shared_resource_mut::<Vec<f64>>()
+                .unwrap()
+                .push(delta_time);
+        });
+        engine.add_system_bundle(per_frame_bundle);
+        let clock = FakeClock {
+            times: RefCell::new(
+                [16, 50, 2050]
+                    .iter()
+                    .map(|&milliseconds| Duration::from_millis(milliseconds))
+                    .collect(),
+            ),
+        };
+
+        let mut last_step_time = Duration::from_millis(0);
+        for _ in 0..3 {
+            last_step_time = engine.step_since(&clock, last_step_time);
+        }
+
+        assert_eq!(last_step_time, Duration::from_millis(2050));
+        assert_eq!(
+            *engine.ecs().shared_resource::<Vec<f64>>().unwrap(),
+            vec![0.016, 0.034, MAX_DELTA_TIME]
+        );
+    }
+
+    #[test]
+    fn errors_have_a_message() {
+        let io_error = || std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
+        let errors: Vec<Error> = vec![
+            GraphicsError::TextureFileOpenError(io_error()).into(),
+            io_error().into(),
+        ];
+
+        for error in errors {
+            assert!(!error.to_string().is_empty());
+            assert!(std::error::Error::source(&error).is_some());
+        }
+    }
+}
@@ -0,0 +1,48 @@
+/// Window state requested by the game, applied by the runner every loop iteration
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSettings {
+    title: String,
+    cursor_visible: bool,
+    cursor_grabbed: bool,
+}
+
+impl WindowSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_owned();
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+    }
+
+    pub fn cursor_grabbed(&self) -> bool {
+        self.cursor_grabbed
+    }
+
+    /// Confines the cursor to the window
+    pub fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.cursor_grabbed = grabbed;
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            title: "tuber".into(),
+            cursor_visible: true,
+            cursor_grabbed: false,
+        }
+    }
+}
@@ -10,6 +10,9 @@ pub trait BitSet {
 
     /// Returns the total number of bits of a bitset
     fn bit_count(&self) -> usize;
+
+    /// Returns the number of set bits
+    fn set_bit_count(&self) -> usize;
 }
 
 impl BitSet for [u64] {
@@ -33,6 +36,10 @@ impl BitSet for [u64] {
     fn bit_count(&self) -> usize {
         self.len() * 64
     }
+
+    fn set_bit_count(&self) -> usize {
+        self.iter().map(|cell| cell.count_ones() as usize).sum()
+    }
 }
 
 impl BitSet for u64 {
@@ -51,6 +58,10 @@ impl BitSet for u64 {
     fn bit_count(&self) -> usize {
         64
     }
+
+    fn set_bit_count(&self) -> usize {
+        self.count_ones() as usize
+    }
 }
 
 #[cfg(test)]
@@ -106,4 +117,14 @@ mod tests {
         assert_eq!(bitset.bit(66), true);
         assert_eq!(bitset.bit(2), true);
     }
+
+    #[test]
+    fn set_bit_count() {
+        let mut bitset = [0u64; 1024];
+        bitset.set_bit(66);
+        bitset.set_bit(2);
+        bitset.set_bit(3);
+        assert_eq!(bitset.set_bit_count(), 3);
+        assert_eq!(5u64.set_bit_count(), 2);
+    }
 }
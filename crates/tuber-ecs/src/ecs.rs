@@ -1,8 +1,12 @@
 //! The ecs module defines the Ecs struct which is the main entry point of tuber-ecs
 
 use crate::bitset::BitSet;
-use crate::query::{Query, QueryIterator, QueryIteratorByIds};
+use crate::prefab::{ComponentRegistry, Prefab, PrefabError};
+use crate::query::{
+    matching_entities, required_bitsets, ComponentBundle, Query, QueryIterator, QueryIteratorByIds,
+};
 use crate::EntityIndex;
+use serde::de::DeserializeOwned;
 use std::any::{Any, TypeId};
 use std::cell::{Ref, RefCell, RefMut};
 use std::collections::{HashMap, HashSet};
@@ -10,7 +14,7 @@ use std::collections::{HashMap, HashSet};
 pub type Components = HashMap<TypeId, ComponentStore>;
 pub type Resources = HashMap<TypeId, RefCell<Box<dyn Any>>>;
 
-type EntitiesBitsetType = [u64; 1024];
+pub(crate) type EntitiesBitsetType = [u64; 1024];
 
 pub struct ComponentStore {
     pub(crate) component_data: Vec<Option<RefCell<Box<dyn Any>>>>,
@@ -52,6 +56,7 @@ impl ComponentStore {
 pub struct Ecs {
     components: Components,
     shared_resources: Resources,
+    component_registry: ComponentRegistry,
     next_index: EntityIndex,
 }
 
@@ -61,15 +66,18 @@ impl Ecs {
         Self {
             components: HashMap::new(),
             shared_resources: HashMap::new(),
+            component_registry: ComponentRegistry::default(),
             next_index: 0,
         }
     }
 
+    /// Inserts a resource shared by the systems, replacing the one of the same type if any
     pub fn insert_shared_resource<T: 'static>(&mut self, resource: T) {
         self.shared_resources
             .insert(TypeId::of::<T>(), RefCell::new(Box::new(resource)));
     }
 
+    /// Returns the shared resource of the given type, `None` if there is none
     pub fn shared_resource<T: 'static>(&self) -> Option<Ref<T>> {
         Some(Ref::map(
             self.shared_resources.get(&TypeId::of::<T>())?.borrow(),
@@ -77,6 +85,7 @@ impl Ecs {
         ))
     }
 
+    /// Returns the shared resource of the given type mutably, `None` if there is none
     pub fn shared_resource_mut<T: 'static>(&self) -> Option<RefMut<T>> {
         Some(RefMut::map(
             self.shared_resources
@@ -99,6 +108,46 @@ impl Ecs {
         index
     }
 
+    /// Inserts several entities into the Ecs.
+    ///
+    /// It returns the [`EntityIndex`] of each inserted entity, in insertion order.
+    pub fn insert_batch<ED: EntityDefinition>(
+        &mut self,
+        entity_definitions: impl IntoIterator<Item = ED>,
+    ) -> Vec<EntityIndex> {
+        entity_definitions
+            .into_iter()
+            .map(|entity_definition| self.insert(entity_definition))
+            .collect()
+    }
+
+    /// Registers a component type under the name prefabs refer to it with
+    pub fn register_component<C: DeserializeOwned + 'static>(&mut self, name: &str) {
+        self.component_registry.register::<C>(name);
+    }
+
+    /// Inserts an entity made of the components of a prefab, which must all be registered
+    ///
+    /// It returns the [`EntityIndex`] of the inserted entity, nothing is inserted on error.
+    pub fn spawn_prefab(&mut self, prefab: &Prefab) -> Result<EntityIndex, PrefabError> {
+        let prefab_components = self.component_registry.deserialize(prefab)?;
+        let index = self.next_index;
+        for component_storage in self.components.values_mut() {
+            component_storage.component_data.push(None);
+        }
+        for (type_id, component) in prefab_components {
+            let component_storage = self
+                .components
+                .entry(type_id)
+                .or_insert_with(|| ComponentStore::with_size(index));
+            *component_storage.component_data.last_mut().unwrap() = Some(RefCell::new(component));
+            component_storage.entities_bitset.set_bit(index);
+        }
+
+        self.next_index += 1;
+        Ok(index)
+    }
+
     pub fn delete_by_query<Q: for<'a> Query<'a>>(&mut self) {
         let to_delete = Q::matching_ids(self.entity_count(), &mut self.components);
         self.delete_by_ids(to_delete.iter().cloned().collect::<Vec<_>>().as_slice());
@@ -113,6 +162,17 @@ impl Ecs {
         }
     }
 
+    /// Deletes every entity and resets the entity indices, keeping the shared resources
+    pub fn clear(&mut self) {
+        self.components.clear();
+        self.next_index = 0;
+    }
+
+    /// Removes every shared resource
+    pub fn clear_resources(&mut self) {
+        self.shared_resources.clear();
+    }
+
     pub fn remove_component<C: 'static>(&mut self, entity_index: EntityIndex) {
         if let Some(components) = self.components.get_mut(&TypeId::of::<C>()) {
             components.remove_from_entity(entity_index);
@@ -129,42 +189,60 @@ impl Ecs {
         QueryIterator::new(self.entity_count(), &self.components)
     }
 
+    /// Returns the ids of the entities matching a query in ascending order, without borrowing their components
+    pub fn matching_entities<'a, Q: Query<'a>>(&self) -> Vec<EntityIndex> {
+        matching_entities::<Q>(self.entity_count(), &self.components)
+    }
+
     pub fn query_by_ids<'a, Q: Query<'a>>(&self, ids: &HashSet<usize>) -> QueryIteratorByIds<Q> {
         QueryIteratorByIds::new(self.entity_count(), &self.components, ids)
     }
 
     pub fn query_one<'a, Q: Query<'a>>(&'a self) -> Option<Q::ResultType> {
-        let index = {
-            let type_ids = Q::type_ids();
-            let bitsets: Vec<&EntitiesBitsetType> = type_ids
-                .iter()
-                .filter_map(|type_id| Some(&self.components.get(&type_id)?.entities_bitset))
-                .collect();
-            if bitsets.len() != type_ids.len() {
-                return None;
-            }
-
-            let mut index = None;
-            for i in 0..self.entity_count() {
-                if bitsets.iter().all(|bitset| bitset.bit(i)) {
-                    index = Some(i);
-                    break;
-                }
-            }
-            index?
-        };
-
-        Some(Q::fetch(index, &self.components)?)
+        let bitsets = required_bitsets::<Q>(&self.components)?;
+        let index = (0..self.entity_count()).find(|&index| {
+            bitsets.iter().all(|bitset| bitset.bit(index))
+                && Q::matches_filters(index, &self.components)
+        })?;
+        Q::fetch(index, &self.components)
     }
 
     pub fn query_one_by_id<'a, Q: Query<'a>>(&'a self, id: EntityIndex) -> Option<Q::ResultType> {
         Q::fetch(id, &self.components)
     }
 
+    /// Returns the components of an entity, or `None` if it doesn't have all of them
+    pub fn get<'a, CB: ComponentBundle<'a>>(&'a self, entity: EntityIndex) -> Option<CB::RefType> {
+        CB::fetch(entity, &self.components)
+    }
+
     /// Returns the entity count of the Ecs.
     pub fn entity_count(&self) -> usize {
         self.next_index
     }
+
+    /// Returns the number of entities having a component of type `C`
+    pub fn component_count<C: 'static>(&self) -> usize {
+        self.components
+            .get(&TypeId::of::<C>())
+            .map_or(0, |component_store| {
+                component_store.entities_bitset.set_bit_count()
+            })
+    }
+
+    /// Returns the indices of the entities having at least one component, in ascending order
+    pub fn iter_entities(&self) -> impl Iterator<Item = EntityIndex> + '_ {
+        (0..self.entity_count()).filter(move |&index| is_alive(index, &self.components))
+    }
+}
+
+/// Returns whether an entity has at least one component, which deleted and never inserted
+/// entities don't
+pub(crate) fn is_alive(index: EntityIndex, components: &Components) -> bool {
+    components.values().any(|component_store| {
+        index < component_store.entities_bitset.bit_count()
+            && component_store.entities_bitset.bit(index)
+    })
 }
 
 /// A type that can be used to define an entity
@@ -200,19 +278,24 @@ impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4,);
 impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5,);
 impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6,);
 impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7,);
+impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8,);
+impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9,);
+impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,);
+impl_entity_definition_tuples!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10, L => 11,);
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::query::accessors::*;
+    use serde::Deserialize;
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, PartialEq, Deserialize)]
     struct Position {
         x: f32,
         y: f32,
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, Deserialize)]
     struct Velocity {
         x: f32,
         y: f32,
@@ -233,6 +316,127 @@ mod tests {
         assert_eq!(ecs.entity_count(), 2usize);
     }
 
+    #[test]
+    pub fn ecs_insert_batch() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Velocity { x: 0.0, y: 0.0 },));
+        let ids = ecs.insert_batch(vec![
+            (Position { x: 1.0, y: 1.0 },),
+            (Position { x: 2.0, y: 2.0 },),
+            (Position { x: 3.0, y: 3.0 },),
+        ]);
+
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(ecs.entity_count(), 4);
+        let queried_ids: Vec<EntityIndex> =
+            ecs.query::<(R<Position>,)>().map(|(id, _)| id).collect();
+        assert_eq!(queried_ids, ids);
+        for (id, expected_x) in ids.into_iter().zip(&[1.0, 2.0, 3.0]) {
+            let (_, (position,)) = ecs.query_one_by_id::<(R<Position>,)>(id).unwrap();
+            assert_eq!(position.x, *expected_x);
+        }
+    }
+
+    #[test]
+    pub fn ecs_shared_resources() {
+        struct Score(u32);
+        struct Missing;
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Score(10));
+
+        ecs.shared_resource_mut::<Score>().unwrap().0 += 5;
+        assert_eq!(ecs.shared_resource::<Score>().unwrap().0, 15);
+        assert!(ecs.shared_resource::<Missing>().is_none());
+        assert!(ecs.shared_resource_mut::<Missing>().is_none());
+
+        ecs.insert_shared_resource(Score(0));
+        assert_eq!(ecs.shared_resource::<Score>().unwrap().0, 0);
+    }
+
+    #[test]
+    pub fn ecs_spawn_prefab() {
+        let mut ecs = Ecs::new();
+        ecs.register_component::<Position>("Position");
+        ecs.register_component::<Velocity>("Velocity");
+        ecs.insert((Velocity { x: 0.0, y: 0.0 },));
+        let prefab = Prefab::from_json(
+            r#"{
+                "Position": { "x": 1.0, "y": 2.0 },
+                "Velocity": { "x": 3.0, "y": 4.0 }
+            }"#,
+        )
+        .unwrap();
+
+        let first = ecs.spawn_prefab(&prefab).unwrap();
+        let second = ecs.spawn_prefab(&prefab).unwrap();
+        {
+            let (_, (mut position,)) = ecs.query_one_by_id::<(W<Position>,)>(first).unwrap();
+            position.x = 10.0;
+        }
+
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(ecs.entity_count(), 3);
+        let (_, (position, velocity)) = ecs
+            .query_one_by_id::<(R<Position>, R<Velocity>)>(first)
+            .unwrap();
+        assert_eq!(*position, Position { x: 10.0, y: 2.0 });
+        assert_eq!((velocity.x, velocity.y), (3.0, 4.0));
+        let (_, (position,)) = ecs.query_one_by_id::<(R<Position>,)>(second).unwrap();
+        assert_eq!(*position, Position { x: 1.0, y: 2.0 });
+    }
+
+    #[test]
+    pub fn ecs_spawn_prefab_with_unregistered_component() {
+        let mut ecs = Ecs::new();
+        ecs.register_component::<Position>("Position");
+        let prefab =
+            Prefab::from_json(r#"{ "Position": { "x": 1.0, "y": 2.0 }, "Health": 10 }"#).unwrap();
+
+        assert!(matches!(
+            ecs.spawn_prefab(&prefab),
+            Err(PrefabError::UnregisteredComponent(name)) if name == "Health"
+        ));
+        assert_eq!(ecs.entity_count(), 0);
+        assert_eq!(ecs.query::<(R<Position>,)>().count(), 0);
+    }
+
+    #[test]
+    pub fn ecs_clear() {
+        struct Score(u32);
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Score(10));
+        ecs.insert((Position { x: 1.0, y: 1.0 }, Velocity { x: 2.0, y: 3.0 }));
+        ecs.insert((Position { x: 4.0, y: 5.0 },));
+
+        ecs.clear();
+
+        assert_eq!(ecs.entity_count(), 0);
+        assert_eq!(ecs.query::<(R<Position>,)>().count(), 0);
+        assert_eq!(ecs.query::<(R<Velocity>,)>().count(), 0);
+        assert_eq!(ecs.shared_resource::<Score>().unwrap().0, 10);
+        assert_eq!(ecs.insert((Velocity { x: 0.0, y: 0.0 },)), 0);
+        assert_eq!(ecs.query::<(R<Position>,)>().count(), 0);
+
+        ecs.clear_resources();
+        assert!(ecs.shared_resource::<Score>().is_none());
+    }
+
+    #[test]
+    pub fn ecs_component_count_and_iter_entities() {
+        struct Unused;
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 1.0, y: 1.0 }, Velocity { x: 2.0, y: 3.0 }));
+        ecs.insert((Position { x: 4.0, y: 5.0 },));
+        ecs.insert((Velocity { x: 6.0, y: 7.0 },));
+        ecs.insert((Position { x: 8.0, y: 9.0 },));
+        ecs.delete_by_ids(&[1]);
+
+        assert_eq!(ecs.component_count::<Position>(), 2);
+        assert_eq!(ecs.component_count::<Velocity>(), 2);
+        assert_eq!(ecs.component_count::<Unused>(), 0);
+        assert_eq!(ecs.iter_entities().collect::<Vec<_>>(), vec![0, 2, 3]);
+    }
+
     #[test]
     pub fn ecs_query() {
         let mut ecs = Ecs::new();
@@ -255,6 +459,24 @@ mod tests {
         assert_eq!(ecs.query::<(R<Velocity>,)>().count(), 2);
     }
 
+    #[test]
+    pub fn ecs_query_yields_entities_in_ascending_index_order() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 0.0, y: 0.0 },));
+        ecs.insert((Velocity { x: 1.0, y: 1.0 },));
+        ecs.insert((Position { x: 2.0, y: 2.0 },));
+        ecs.insert((Position { x: 3.0, y: 3.0 },));
+
+        let ids: Vec<EntityIndex> = ecs.query::<(R<Position>,)>().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![0, 2, 3]);
+
+        let ids: Vec<EntityIndex> = ecs
+            .query_by_ids::<(R<Position>,)>(&[3, 0].iter().cloned().collect())
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, vec![0, 3]);
+    }
+
     #[test]
     pub fn ecs_query_one() {
         let mut ecs = Ecs::new();
@@ -266,4 +488,229 @@ mod tests {
             Position { x: 12.0, y: 1.0 }
         );
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Component tuber_ecs::ecs::tests::Velocity of entity 0 is already borrowed"
+    )]
+    pub fn ecs_overlapping_queries_panic_with_component_name() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 12.0, y: 1.0 }, Velocity { x: 2.0, y: 3.0 }));
+
+        for (_, (_velocity,)) in ecs.query::<(R<Velocity>,)>() {
+            for (_, (mut velocity,)) in ecs.query::<(W<Velocity>,)>() {
+                velocity.x = 0.0;
+            }
+        }
+    }
+
+    #[test]
+    pub fn ecs_ten_components_entity() {
+        struct C0(u32);
+        struct C1(u32);
+        struct C2(u32);
+        struct C3(u32);
+        struct C4(u32);
+        struct C5(u32);
+        struct C6(u32);
+        struct C7(u32);
+        struct C8(u32);
+        struct C9(u32);
+
+        let mut ecs = Ecs::new();
+        ecs.insert((
+            C0(0),
+            C1(1),
+            C2(2),
+            C3(3),
+            C4(4),
+            C5(5),
+            C6(6),
+            C7(7),
+            C8(8),
+            C9(9),
+        ));
+
+        for (_, (c0, c1, c2, c3, c4, c5, c6, c7, c8, mut c9)) in ecs.query::<(
+            R<C0>,
+            R<C1>,
+            R<C2>,
+            R<C3>,
+            R<C4>,
+            R<C5>,
+            R<C6>,
+            R<C7>,
+            R<C8>,
+            W<C9>,
+        )>() {
+            assert_eq!(
+                [c0.0, c1.0, c2.0, c3.0, c4.0, c5.0, c6.0, c7.0, c8.0, c9.0],
+                [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+            );
+            c9.0 = 90;
+        }
+
+        let (_, (c9,)) = ecs.query_one::<(R<C9>,)>().unwrap();
+        assert_eq!(c9.0, 90);
+    }
+
+    #[test]
+    pub fn ecs_matching_entities() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 12.0, y: 1.0 }, Velocity { x: 2.0, y: 3.0 }));
+        ecs.insert((Position { x: 4.0, y: 5.0 },));
+        ecs.insert((Velocity { x: 6.0, y: 7.0 },));
+        ecs.insert((Position { x: 4.0, y: 5.0 }, Velocity { x: 6.0, y: 7.0 }));
+
+        let position_bitset = &ecs.components[&TypeId::of::<Position>()].entities_bitset;
+        let velocity_bitset = &ecs.components[&TypeId::of::<Velocity>()].entities_bitset;
+        let expected: Vec<EntityIndex> = (0..ecs.entity_count())
+            .filter(|&i| position_bitset.bit(i) && velocity_bitset.bit(i))
+            .collect();
+
+        let matching_entities = ecs.matching_entities::<(R<Position>, W<Velocity>)>();
+
+        assert_eq!(matching_entities, expected);
+        assert_eq!(matching_entities, vec![0, 3]);
+        for (_, (mut velocity,)) in
+            ecs.query_by_ids::<(W<Velocity>,)>(&matching_entities.into_iter().collect())
+        {
+            velocity.x = 0.0;
+        }
+    }
+
+    #[test]
+    pub fn ecs_matching_entities_with_missing_component_store() {
+        struct Unused;
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 12.0, y: 1.0 },));
+
+        assert!(ecs
+            .matching_entities::<(R<Position>, R<Unused>)>()
+            .is_empty());
+    }
+
+    #[test]
+    pub fn ecs_query_with_entity_accessor() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 1.0, y: 2.0 },));
+        ecs.insert((Velocity { x: 3.0, y: 4.0 },));
+        ecs.insert((Position { x: 5.0, y: 6.0 }, Velocity { x: 7.0, y: 8.0 }));
+
+        let ids: Vec<EntityIndex> = ecs
+            .query::<(Entity, R<Position>)>()
+            .map(|(_, (entity, _))| entity)
+            .collect();
+        assert_eq!(ids, vec![0, 2]);
+    }
+
+    #[test]
+    pub fn ecs_get() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 1.0, y: 2.0 },));
+        let id = ecs.insert((Position { x: 5.0, y: 6.0 }, Velocity { x: 7.0, y: 8.0 }));
+
+        let (entity, position, velocity) =
+            ecs.get::<(Entity, R<Position>, R<Velocity>)>(id).unwrap();
+        assert_eq!(entity, id);
+        assert_eq!(*position, Position { x: 5.0, y: 6.0 });
+        assert_eq!(velocity.x, 7.0);
+    }
+
+    #[test]
+    pub fn ecs_get_missing_entity() {
+        let mut ecs = Ecs::new();
+        let id = ecs.insert((Position { x: 1.0, y: 2.0 },));
+        ecs.insert((Velocity { x: 3.0, y: 4.0 },));
+
+        assert!(ecs.get::<(R<Position>, R<Velocity>)>(id).is_none());
+        assert!(ecs.get::<(R<Position>,)>(42).is_none());
+    }
+
+    #[test]
+    pub fn entity_accessor_matches_only_live_entities() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 1.0, y: 2.0 },));
+        let deleted = ecs.insert((Position { x: 3.0, y: 4.0 },));
+        ecs.insert((Velocity { x: 5.0, y: 6.0 },));
+        ecs.delete_by_ids(&[deleted]);
+
+        let ids: Vec<EntityIndex> = ecs.query::<(Entity,)>().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![0, 2]);
+        assert_eq!(ecs.matching_entities::<(Entity,)>(), vec![0, 2]);
+        assert!(ecs.get::<(Entity,)>(deleted).is_none());
+    }
+
+    #[test]
+    pub fn entity_accessor_doesnt_get_entities_never_inserted() {
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 1.0, y: 2.0 },));
+
+        assert!(ecs.get::<(Entity,)>(42).is_none());
+        assert!(ecs.get::<(Entity,)>(usize::MAX).is_none());
+        assert_eq!(ecs.get::<(Entity,)>(0).map(|(entity,)| entity), Some(0));
+    }
+
+    #[test]
+    pub fn ecs_query_or() {
+        struct Unused;
+        let mut ecs = Ecs::new();
+        ecs.insert((Position { x: 1.0, y: 2.0 },));
+        ecs.insert((Velocity { x: 3.0, y: 4.0 },));
+        ecs.insert((Position { x: 5.0, y: 6.0 }, Velocity { x: 7.0, y: 8.0 }));
+        ecs.insert((Unused,));
+
+        let matches: Vec<(EntityIndex, bool, bool)> = ecs
+            .query::<(Or<(R<Position>, W<Velocity>)>,)>()
+            .map(|(id, ((position, velocity),))| (id, position.is_some(), velocity.is_some()))
+            .collect();
+        assert_eq!(
+            matches,
+            vec![(0, true, false), (1, false, true), (2, true, true)]
+        );
+        assert_eq!(
+            ecs.matching_entities::<(R<Velocity>, Or<(R<Position>, R<Unused>)>)>(),
+            vec![2]
+        );
+        assert!(ecs
+            .matching_entities::<(R<Position>, Or<(R<Velocity>, R<Unused>)>, R<Unused>)>()
+            .is_empty());
+    }
+
+    fn assert_optimized_matching<'a, Q: Query<'a>>(ecs: &'a Ecs) {
+        let expected: Vec<EntityIndex> = (0..ecs.entity_count())
+            .filter(|&index| Q::matches(index, &ecs.components))
+            .collect();
+
+        assert_eq!(ecs.matching_entities::<Q>(), expected);
+        assert_eq!(ecs.query_one::<Q>().is_some(), !expected.is_empty());
+    }
+
+    #[test]
+    pub fn ecs_optimized_matching_is_identical_to_matching_each_entity() {
+        struct Unused;
+        let mut ecs = Ecs::new();
+        for i in 0..100 {
+            let position = Position {
+                x: i as f32,
+                y: 0.0,
+            };
+            let velocity = Velocity { x: 0.0, y: 0.0 };
+            match i % 4 {
+                0 => ecs.insert((position,)),
+                1 => ecs.insert((velocity,)),
+                2 => ecs.insert((position, velocity)),
+                _ => ecs.insert((i,)),
+            };
+        }
+        ecs.delete_by_ids(&[2, 6, 9, 50]);
+
+        assert_optimized_matching::<(R<Position>,)>(&ecs);
+        assert_optimized_matching::<(R<Position>, W<Velocity>)>(&ecs);
+        assert_optimized_matching::<(Entity, R<Velocity>)>(&ecs);
+        assert_optimized_matching::<(Or<(R<Position>, R<Velocity>)>,)>(&ecs);
+        assert_optimized_matching::<(R<i32>, Or<(R<Position>, R<Velocity>)>)>(&ecs);
+        assert_optimized_matching::<(R<Position>, R<Unused>)>(&ecs);
+        assert_optimized_matching::<(Or<(R<Unused>, R<Velocity>)>, Entity)>(&ecs);
+    }
 }
@@ -1,5 +1,6 @@
 mod bitset;
 pub mod ecs;
+pub mod prefab;
 pub mod query;
 pub mod system;
 
@@ -0,0 +1,116 @@
+//! The prefab module defines entity templates described in JSON and instantiated by the Ecs
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+
+#[derive(Debug)]
+pub enum PrefabError {
+    FileOpenError(std::io::Error),
+    SerdeError(serde_json::error::Error),
+    UnregisteredComponent(String),
+}
+
+impl std::fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrefabError::FileOpenError(error) => write!(f, "couldn't open prefab file: {}", error),
+            PrefabError::SerdeError(error) => write!(f, "couldn't parse prefab: {}", error),
+            PrefabError::UnregisteredComponent(name) => {
+                write!(f, "component {} isn't registered", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PrefabError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PrefabError::FileOpenError(error) => Some(error),
+            PrefabError::SerdeError(error) => Some(error),
+            PrefabError::UnregisteredComponent(_) => None,
+        }
+    }
+}
+
+/// A template of entity, made of components keyed by the name they are registered with
+///
+/// ```json
+/// {
+///     "Position": { "x": 10.0, "y": 20.0 },
+///     "Health": 100
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Prefab {
+    components: Map<String, Value>,
+}
+
+impl Prefab {
+    /// Parses a prefab from a JSON string
+    pub fn from_json(json: &str) -> Result<Self, PrefabError> {
+        Ok(Self {
+            components: serde_json::from_str(json).map_err(PrefabError::SerdeError)?,
+        })
+    }
+
+    /// Loads a prefab from a JSON file
+    pub fn from_file(path: &str) -> Result<Self, PrefabError> {
+        let file = File::open(path).map_err(PrefabError::FileOpenError)?;
+        Ok(Self {
+            components: serde_json::from_reader(BufReader::new(file))
+                .map_err(PrefabError::SerdeError)?,
+        })
+    }
+
+    /// Returns the names of the components of the prefab
+    pub fn component_names(&self) -> impl Iterator<Item = &str> {
+        self.components.keys().map(String::as_str)
+    }
+}
+
+type ComponentDeserializer = Box<dyn Fn(&Value) -> Result<Box<dyn Any>, serde_json::Error>>;
+/// A deserialized component and its type
+type PrefabComponent = (TypeId, Box<dyn Any>);
+
+/// The components that can be deserialized from a prefab, keyed by name
+#[derive(Default)]
+pub(crate) struct ComponentRegistry {
+    deserializers: HashMap<String, (TypeId, ComponentDeserializer)>,
+}
+
+impl ComponentRegistry {
+    pub fn register<C: DeserializeOwned + 'static>(&mut self, name: &str) {
+        self.deserializers.insert(
+            name.to_owned(),
+            (
+                TypeId::of::<C>(),
+                Box::new(|value| {
+                    let component: C = C::deserialize(value)?;
+                    Ok(Box::new(component) as Box<dyn Any>)
+                }),
+            ),
+        );
+    }
+
+    /// Returns the type and the value of every component of a prefab
+    pub fn deserialize(&self, prefab: &Prefab) -> Result<Vec<PrefabComponent>, PrefabError> {
+        prefab
+            .components
+            .iter()
+            .map(|(name, value)| {
+                let (type_id, deserialize) = self
+                    .deserializers
+                    .get(name)
+                    .ok_or_else(|| PrefabError::UnregisteredComponent(name.clone()))?;
+                Ok((
+                    *type_id,
+                    deserialize(value).map_err(PrefabError::SerdeError)?,
+                ))
+            })
+            .collect()
+    }
+}
@@ -1,5 +1,5 @@
 use crate::bitset::BitSet;
-use crate::ecs::Components;
+use crate::ecs::{Components, EntitiesBitsetType};
 use crate::EntityIndex;
 use accessors::Accessor;
 use std::any::TypeId;
@@ -11,11 +11,43 @@ pub trait Query<'a> {
 
     fn fetch(index: EntityIndex, components: &'a Components) -> Option<Self::ResultType>;
     fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex>;
+    /// Returns the types of the components an entity needs to match the query
     fn type_ids() -> Vec<TypeId>;
+    /// Returns whether an entity matches the accessors of the query which don't need a
+    /// component, such as `Or`, without borrowing its components
+    fn matches_filters(index: EntityIndex, components: &Components) -> bool;
+
+    /// Returns whether an entity matches the query, without borrowing its components
+    fn matches(index: EntityIndex, components: &Components) -> bool {
+        Self::type_ids().iter().all(|type_id| {
+            components
+                .get(type_id)
+                .is_some_and(|component_store| component_store.entities_bitset.bit(index))
+        }) && Self::matches_filters(index, components)
+    }
+}
+
+/// The components of a single entity fetched by a tuple of accessors
+pub trait ComponentBundle<'a> {
+    type RefType: 'a;
+
+    fn fetch(index: EntityIndex, components: &'a Components) -> Option<Self::RefType>;
 }
 
 macro_rules! impl_query_tuples {
     ($th:tt, $($t:tt,)*) => {
+        impl<'a, $th, $($t,)*> ComponentBundle<'a> for ($th, $($t,)*)
+        where
+            $th: Accessor<'a>,
+            $($t: Accessor<'a>,)*
+        {
+            type RefType = ($th::RefType, $($t::RefType,)*);
+
+            fn fetch(index: EntityIndex, components: &'a Components) -> Option<Self::RefType> {
+                Some(($th::fetch(index, components)?, $($t::fetch(index, components)?,)*))
+            }
+        }
+
         impl<'a, $th, $($t,)*> Query<'a> for ($th, $($t,)*)
         where
             $th: Accessor<'a>,
@@ -24,7 +56,7 @@ macro_rules! impl_query_tuples {
             type ResultType = (EntityIndex, ($th::RefType, $($t::RefType,)*));
 
             fn fetch(index: EntityIndex, components: &'a Components) -> Option<Self::ResultType> {
-                Some((index, ($th::fetch(index, components)?, $($t::fetch(index, components)?,)*)))
+                Some((index, <Self as ComponentBundle>::fetch(index, components)?))
             }
 
             #[allow(unused_mut)]
@@ -35,7 +67,12 @@ macro_rules! impl_query_tuples {
             }
 
             fn type_ids() -> Vec<TypeId> {
-                vec![$th::type_id(), $($t::type_id(),)*]
+                vec![$th::type_id(), $($t::type_id(),)*].into_iter().flatten().collect()
+            }
+
+            fn matches_filters(index: EntityIndex, components: &Components) -> bool {
+                ($th::type_id().is_some() || $th::matches(index, components))
+                    $(&& ($t::type_id().is_some() || $t::matches(index, components)))*
             }
         }
     }
@@ -49,6 +86,42 @@ impl_query_tuples!(A, B, C, D, E,);
 impl_query_tuples!(A, B, C, D, E, F,);
 impl_query_tuples!(A, B, C, D, E, F, G,);
 impl_query_tuples!(A, B, C, D, E, F, G, H,);
+impl_query_tuples!(A, B, C, D, E, F, G, H, I,);
+impl_query_tuples!(A, B, C, D, E, F, G, H, I, J,);
+impl_query_tuples!(A, B, C, D, E, F, G, H, I, J, K,);
+impl_query_tuples!(A, B, C, D, E, F, G, H, I, J, K, L,);
+
+/// Returns the bitsets of the components needed by a query, `None` if one of them
+/// has never been stored
+pub(crate) fn required_bitsets<'a, 'c, Q: Query<'a>>(
+    components: &'c Components,
+) -> Option<Vec<&'c EntitiesBitsetType>> {
+    Q::type_ids()
+        .iter()
+        .map(|type_id| {
+            components
+                .get(type_id)
+                .map(|component_store| &component_store.entities_bitset)
+        })
+        .collect()
+}
+
+/// Returns the ids of the entities matching a query, in ascending order
+pub(crate) fn matching_entities<'a, Q: Query<'a>>(
+    entity_count: usize,
+    components: &Components,
+) -> Vec<EntityIndex> {
+    let bitsets = match required_bitsets::<Q>(components) {
+        Some(bitsets) => bitsets,
+        None => return vec![],
+    };
+
+    (0..entity_count)
+        .filter(|&index| {
+            bitsets.iter().all(|bitset| bitset.bit(index)) && Q::matches_filters(index, components)
+        })
+        .collect()
+}
 
 pub struct QueryIteratorByIds<'a, Q> {
     inner_iterator: QueryIterator<'a, Q>,
@@ -80,39 +153,20 @@ where
     }
 }
 
+/// Iterates over the entities matching a query in ascending index order
 pub struct QueryIterator<'a, Q> {
     index: EntityIndex,
     components: &'a Components,
-    matching_entities: Vec<EntityIndex>,
+    matching_entities: std::vec::IntoIter<EntityIndex>,
     marker: PhantomData<&'a Q>,
 }
 
 impl<'a, 'b, Q: Query<'b>> QueryIterator<'a, Q> {
     pub fn new(entity_count: usize, components: &'a Components) -> Self {
-        let mut bitsets = vec![];
-        for type_id in Q::type_ids() {
-            if let Some(component_store) = components.get(&type_id) {
-                bitsets.push(component_store.entities_bitset.clone());
-            }
-        }
-
-        let mut matching_entities = vec![];
-        if bitsets.len() == Q::type_ids().len() {
-            'outer: for i in 0..entity_count {
-                for bitset in bitsets.iter() {
-                    if !bitset.bit(i) {
-                        continue 'outer;
-                    }
-                }
-
-                matching_entities.push(i);
-            }
-        }
-
         Self {
             index: 0,
             components,
-            matching_entities,
+            matching_entities: matching_entities::<Q>(entity_count, components).into_iter(),
             marker: PhantomData,
         }
     }
@@ -125,7 +179,7 @@ where
     type Item = Q::ResultType;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.index = if let Some(index) = self.matching_entities.pop() {
+        self.index = if let Some(index) = self.matching_entities.next() {
             index
         } else {
             return None;
@@ -136,7 +190,7 @@ where
 
 pub mod accessors {
     use crate::bitset::BitSet;
-    use crate::ecs::Components;
+    use crate::ecs::{is_alive, Components};
     use crate::EntityIndex;
     use std::any::TypeId;
     use std::cell::{Ref, RefMut};
@@ -145,6 +199,11 @@ pub mod accessors {
 
     pub struct R<T>(PhantomData<T>);
     pub struct W<T>(PhantomData<T>);
+    /// Fetches the index of the entity, it matches every entity having at least one component
+    pub struct Entity;
+    /// Matches the entities matched by any of the accessors of a tuple, fetching an `Option`
+    /// per accessor
+    pub struct Or<T>(PhantomData<T>);
 
     pub trait Accessor<'a> {
         type RawType: 'a;
@@ -152,19 +211,37 @@ pub mod accessors {
 
         fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType>;
         fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex>;
-        fn type_id() -> TypeId;
+        /// Returns the type of the fetched component, if any
+        fn type_id() -> Option<TypeId>;
+
+        /// Returns whether an entity has the fetched component, without borrowing it
+        fn matches(index: EntityIndex, components: &Components) -> bool {
+            Self::type_id().is_none_or(|type_id| {
+                components
+                    .get(&type_id)
+                    .is_some_and(|component_store| component_store.entities_bitset.bit(index))
+            })
+        }
     }
     impl<'a, T: 'static> Accessor<'a> for R<T> {
         type RawType = T;
         type RefType = Ref<'a, T>;
 
         fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType> {
-            Some(Ref::map(
-                components.get(&TypeId::of::<T>())?.component_data[index]
-                    .as_ref()?
-                    .borrow(),
-                |r| r.downcast_ref().unwrap(),
-            ))
+            let component = components
+                .get(&TypeId::of::<T>())?
+                .component_data
+                .get(index)?
+                .as_ref()?;
+            let component = component.try_borrow().unwrap_or_else(|_| {
+                panic!(
+                    "Component {} of entity {} is already borrowed mutably, \
+                    drop the previous query results before querying it again",
+                    std::any::type_name::<T>(),
+                    index
+                )
+            });
+            Some(Ref::map(component, |r| r.downcast_ref().unwrap()))
         }
 
         fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex> {
@@ -180,8 +257,8 @@ pub mod accessors {
             result
         }
 
-        fn type_id() -> TypeId {
-            TypeId::of::<T>()
+        fn type_id() -> Option<TypeId> {
+            Some(TypeId::of::<T>())
         }
     }
     impl<'a, T: 'static> Accessor<'a> for W<T> {
@@ -189,12 +266,20 @@ pub mod accessors {
         type RefType = RefMut<'a, T>;
 
         fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType> {
-            Some(RefMut::map(
-                components.get(&TypeId::of::<T>())?.component_data[index]
-                    .as_ref()?
-                    .borrow_mut(),
-                |r| r.downcast_mut().unwrap(),
-            ))
+            let component = components
+                .get(&TypeId::of::<T>())?
+                .component_data
+                .get(index)?
+                .as_ref()?;
+            let component = component.try_borrow_mut().unwrap_or_else(|_| {
+                panic!(
+                    "Component {} of entity {} is already borrowed, \
+                    drop the previous query results before querying it mutably",
+                    std::any::type_name::<T>(),
+                    index
+                )
+            });
+            Some(RefMut::map(component, |r| r.downcast_mut().unwrap()))
         }
 
         fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex> {
@@ -210,8 +295,72 @@ pub mod accessors {
             result
         }
 
-        fn type_id() -> TypeId {
-            TypeId::of::<T>()
+        fn type_id() -> Option<TypeId> {
+            Some(TypeId::of::<T>())
         }
     }
+    impl<'a> Accessor<'a> for Entity {
+        type RawType = EntityIndex;
+        type RefType = EntityIndex;
+
+        fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType> {
+            if !is_alive(index, components) {
+                return None;
+            }
+
+            Some(index)
+        }
+
+        fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex> {
+            (0..entity_count)
+                .filter(|&index| is_alive(index, components))
+                .collect()
+        }
+
+        fn type_id() -> Option<TypeId> {
+            None
+        }
+
+        fn matches(index: EntityIndex, components: &Components) -> bool {
+            is_alive(index, components)
+        }
+    }
+
+    macro_rules! impl_or_tuples {
+        ($($t:tt,)*) => {
+            impl<'a, $($t,)*> Accessor<'a> for Or<($($t,)*)>
+            where
+                $($t: Accessor<'a>,)*
+            {
+                type RawType = ($($t::RawType,)*);
+                type RefType = ($(Option<$t::RefType>,)*);
+
+                fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType> {
+                    if !Self::matches(index, components) {
+                        return None;
+                    }
+
+                    Some(($($t::fetch(index, components),)*))
+                }
+
+                fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex> {
+                    let mut result = HashSet::new();
+                    $(result.extend($t::matching_ids(entity_count, components));)*
+                    result
+                }
+
+                fn type_id() -> Option<TypeId> {
+                    None
+                }
+
+                fn matches(index: EntityIndex, components: &Components) -> bool {
+                    false $(|| $t::matches(index, components))*
+                }
+            }
+        }
+    }
+
+    impl_or_tuples!(A, B,);
+    impl_or_tuples!(A, B, C,);
+    impl_or_tuples!(A, B, C, D,);
 }
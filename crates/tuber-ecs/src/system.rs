@@ -13,6 +13,18 @@ impl SystemBundle {
         self.systems.push(system.into_system());
     }
 
+    /// Adds a system that only runs when the given condition returns true
+    ///
+    /// The condition is evaluated each time the bundle is stepped.
+    pub fn add_system_if<S: IntoSystem>(&mut self, condition: fn(&Ecs) -> bool, system: S) {
+        let mut system = system.into_system();
+        self.systems.push(Box::new(move |ecs: &mut Ecs| {
+            if condition(ecs) {
+                (system)(ecs);
+            }
+        }));
+    }
+
     pub fn step(&mut self, ecs: &mut Ecs) {
         for system in &mut self.systems {
             (system)(ecs);
@@ -79,4 +91,34 @@ mod tests {
         assert!(result_set.contains(&Value(41)));
         assert!(result_set.contains(&Value(47)));
     }
+
+    #[test]
+    fn system_bundle_add_system_if() {
+        struct Counter(i32);
+        struct Running(bool);
+
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Counter(0));
+        ecs.insert_shared_resource(Running(true));
+
+        let mut system_bundle = SystemBundle::new();
+        system_bundle.add_system_if(
+            |ecs: &Ecs| ecs.shared_resource::<Running>().unwrap().0,
+            |ecs: &mut Ecs| {
+                ecs.shared_resource_mut::<Counter>().unwrap().0 += 1;
+            },
+        );
+
+        system_bundle.step(&mut ecs);
+        system_bundle.step(&mut ecs);
+        assert_eq!(ecs.shared_resource::<Counter>().unwrap().0, 2);
+
+        ecs.shared_resource_mut::<Running>().unwrap().0 = false;
+        system_bundle.step(&mut ecs);
+        assert_eq!(ecs.shared_resource::<Counter>().unwrap().0, 2);
+
+        ecs.shared_resource_mut::<Running>().unwrap().0 = true;
+        system_bundle.step(&mut ecs);
+        assert_eq!(ecs.shared_resource::<Counter>().unwrap().0, 3);
+    }
 }
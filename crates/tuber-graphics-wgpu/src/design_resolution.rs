@@ -0,0 +1,177 @@
+use crate::texture::{
+    create_depth_texture_view, create_multisampled_framebuffer_view, sampler_descriptor,
+};
+use tuber_graphics::design_resolution::FixedResolution;
+use tuber_graphics::texture::{TextureAddressMode, TextureFilter};
+use tuber_graphics::WindowSize;
+use wgpu::{Device, FragmentState, RenderPass, TextureFormat};
+
+/// An offscreen target the scene is rendered to at a fixed resolution, then scaled into the window
+pub(crate) struct DesignResolutionTarget {
+    resolution: FixedResolution,
+    color_view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+    multisampled_view: Option<wgpu::TextureView>,
+    blit_pipeline: wgpu::RenderPipeline,
+    blit_bind_group: wgpu::BindGroup,
+}
+
+impl DesignResolutionTarget {
+    pub fn new(
+        device: &Device,
+        texture_format: &TextureFormat,
+        resolution: FixedResolution,
+        sample_count: u32,
+    ) -> Self {
+        let size = resolution.size();
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("design_resolution_texture"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: *texture_format,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&sampler_descriptor(
+            TextureFilter::Nearest,
+            TextureAddressMode::ClampToEdge,
+            None,
+        ));
+        let multisampled_view = if sample_count > 1 {
+            Some(create_multisampled_framebuffer_view(
+                device,
+                *texture_format,
+                size,
+                sample_count,
+            ))
+        } else {
+            None
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("design_resolution_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        comparison: false,
+                        filtering: true,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let blit_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("design_resolution_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&color_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let vertex_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/blit.vert.spv"));
+        let fragment_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/blit.frag.spv"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("design_resolution_blit_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("design_resolution_blit_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &vertex_shader_module,
+                entry_point: "main",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &fragment_shader_module,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format: *texture_format,
+                    alpha_blend: wgpu::BlendState::REPLACE,
+                    color_blend: wgpu::BlendState::REPLACE,
+                    write_mask: wgpu::ColorWrite::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+            },
+            depth_stencil: None,
+            multisample: crate::multisample_state(1),
+        });
+
+        Self {
+            resolution,
+            color_view,
+            depth_view: create_depth_texture_view(device, size, sample_count),
+            multisampled_view,
+            blit_pipeline,
+            blit_bind_group,
+        }
+    }
+
+    pub fn size(&self) -> WindowSize {
+        self.resolution.size()
+    }
+
+    /// Returns the color attachment, its resolve target and the depth attachment the scene
+    /// is rendered to
+    pub fn scene_attachments(
+        &self,
+    ) -> (
+        &wgpu::TextureView,
+        Option<&wgpu::TextureView>,
+        &wgpu::TextureView,
+    ) {
+        match &self.multisampled_view {
+            Some(multisampled_view) => {
+                (multisampled_view, Some(&self.color_view), &self.depth_view)
+            }
+            None => (&self.color_view, None, &self.depth_view),
+        }
+    }
+
+    /// Draws the rendered scene scaled into a window of the given size
+    pub fn blit<'rpass>(
+        &'rpass self,
+        render_pass: &mut RenderPass<'rpass>,
+        window_size: WindowSize,
+    ) {
+        let (_, (x, y, width, height)) = self.resolution.destination(window_size);
+        render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+        render_pass.set_pipeline(&self.blit_pipeline);
+        render_pass.set_bind_group(0, &self.blit_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -1,20 +1,30 @@
-use crate::bounding_box_renderer::BoundingBoxRenderer;
+use crate::design_resolution::DesignResolutionTarget;
+use crate::line_renderer::LineRenderer;
+use crate::mesh_2d_renderer::Mesh2DRenderer;
 use crate::quad_renderer::QuadRenderer;
-use crate::texture::Texture;
+use crate::texture::{
+    create_depth_texture_view, create_multisampled_framebuffer_view, Texture, DEPTH_FORMAT,
+};
 use crate::tilemap_renderer::TilemapRenderer;
 use futures;
+use nalgebra::Matrix4;
 use std::collections::HashMap;
+use std::num::NonZeroU8;
+use std::ops::Range;
 use tuber_common::tilemap::Tilemap;
 use tuber_common::transform::Transform2D;
 use tuber_graphics::camera::OrthographicCamera;
+use tuber_graphics::design_resolution::FixedResolution;
 use tuber_graphics::texture::TextureData;
 use tuber_graphics::tilemap::TilemapRender;
 use tuber_graphics::{
-    low_level::LowLevelGraphicsAPI, low_level::QuadDescription, texture::TextureAtlas, Color,
-    Window, WindowSize,
+    low_level::LowLevelGraphicsAPI, low_level::MeshDescription, low_level::QuadDescription,
+    low_level::RenderStats, texture::TextureAtlas, ui::ClipRect, Color, Window, WindowSize,
 };
 
-mod bounding_box_renderer;
+mod design_resolution;
+mod line_renderer;
+mod mesh_2d_renderer;
 mod quad_renderer;
 mod texture;
 mod tilemap_renderer;
@@ -26,19 +36,91 @@ pub struct GraphicsWGPU {
     wgpu_state: Option<WGPUState>,
     textures: HashMap<String, Texture>,
     camera_id: Option<usize>,
+    camera: Option<OrthographicCamera>,
     clear_color: Color,
+    bounding_box_color: Color,
+    sample_count: u32,
+    /// The optional device features requested on top of the ones the renderer uses
+    features: wgpu::Features,
+    limits: wgpu::Limits,
+    anisotropy: u8,
+    fixed_resolution: Option<FixedResolution>,
+    wireframe_rendering: bool,
+    pixel_snapping: bool,
+    last_frame_stats: RenderStats,
 }
 
 pub struct WGPUState {
-    surface: wgpu::Surface,
+    /// The window surface, `None` when rendering offscreen
+    surface: Option<wgpu::Surface>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     sc_desc: wgpu::SwapChainDescriptor,
-    swap_chain: wgpu::SwapChain,
+    swap_chain: Option<wgpu::SwapChain>,
+    depth_texture_view: wgpu::TextureView,
+    multisampled_framebuffer_view: Option<wgpu::TextureView>,
     window_size: WindowSize,
     quad_renderer: QuadRenderer,
     tilemap_renderer: TilemapRenderer,
-    bounding_box_renderer: BoundingBoxRenderer,
+    line_renderer: LineRenderer,
+    mesh_2d_renderer: Mesh2DRenderer,
+    design_resolution_target: Option<DesignResolutionTarget>,
+    anisotropy_clamp: Option<NonZeroU8>,
+    draw_commands: Vec<DrawCommand>,
+}
+
+impl WGPUState {
+    /// Drops the bind groups referencing a replaced or unloaded texture
+    fn invalidate_texture(&mut self, texture_identifier: &str) {
+        self.quad_renderer.invalidate_texture(texture_identifier);
+        self.tilemap_renderer.invalidate_texture(texture_identifier);
+        self.mesh_2d_renderer.invalidate_texture(texture_identifier);
+    }
+
+    /// Returns the size of the target the scene is rendered to, the design resolution if any
+    fn scene_size(&self) -> WindowSize {
+        match &self.design_resolution_target {
+            Some(design_resolution_target) => design_resolution_target.size(),
+            None => (self.sc_desc.width, self.sc_desc.height),
+        }
+    }
+}
+
+/// A draw recorded during the frame, replayed in preparation order
+#[derive(Debug, PartialEq)]
+enum DrawCommand {
+    Quads(Range<usize>),
+    Tilemap(String),
+    Mesh(usize),
+    ClipRect(Option<ClipRect>),
+    /// Draws the following commands over the previous ones, whatever their depth
+    ClearDepth,
+}
+
+/// Records the draw of a quad instance, merging it with the previous quads if possible
+fn push_quad_command(draw_commands: &mut Vec<DrawCommand>, instance_index: usize) {
+    if let Some(DrawCommand::Quads(instances)) = draw_commands.last_mut() {
+        if instances.end == instance_index {
+            instances.end += 1;
+            return;
+        }
+    }
+
+    draw_commands.push(DrawCommand::Quads(instance_index..instance_index + 1));
+}
+
+/// Splits the draw commands into render passes at the depth clears, there is always at least one
+/// pass to clear the frame
+fn depth_passes(draw_commands: &[DrawCommand]) -> Vec<&[DrawCommand]> {
+    let mut passes: Vec<&[DrawCommand]> = draw_commands
+        .split(|draw_command| *draw_command == DrawCommand::ClearDepth)
+        .filter(|pass| !pass.is_empty())
+        .collect();
+    if passes.is_empty() {
+        passes.push(&[]);
+    }
+
+    passes
 }
 
 impl GraphicsWGPU {
@@ -47,52 +129,116 @@ impl GraphicsWGPU {
             wgpu_state: None,
             textures: HashMap::new(),
             camera_id: None,
-            clear_color: (0.0, 0.0, 0.0),
+            camera: None,
+            clear_color: Color::BLACK,
+            bounding_box_color: Color::rgb(1.0, 0.0, 1.0),
+            sample_count: 1,
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            anisotropy: 1,
+            fixed_resolution: None,
+            wireframe_rendering: false,
+            pixel_snapping: false,
+            last_frame_stats: RenderStats::default(),
         }
     }
-}
 
-impl LowLevelGraphicsAPI for GraphicsWGPU {
-    fn initialize(&mut self, window: Window, window_size: WindowSize) {
-        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
-        let surface = unsafe { instance.create_surface(&window) };
-        let adapter = async {
-            instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                })
-                .await
-        };
-        let adapter = futures::executor::block_on(adapter).unwrap();
+    /// Sets the number of samples per pixel used for anti-aliasing, 1 disables it
+    ///
+    /// Counts unsupported by the adapter fall back to the greatest supported lower count.
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
 
-        let device_and_queue = async {
-            adapter
-                .request_device(
-                    &wgpu::DeviceDescriptor {
-                        features: wgpu::Features::empty(),
-                        limits: wgpu::Limits::default(),
-                        label: None,
-                    },
-                    None,
-                )
-                .await
-        };
+    /// Requests optional device features, such as `PUSH_CONSTANTS`
+    ///
+    /// The features unsupported by the adapter are left out.
+    pub fn with_features(mut self, features: wgpu::Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Requests device limits, such as a greater `max_bind_groups`
+    ///
+    /// The limits are lowered to the ones supported by the adapter.
+    pub fn with_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the number of samples taken by the anisotropic filtering of linearly filtered
+    /// textures, a power of two up to 16, 1 disables it
+    ///
+    /// Unsupported values fall back to 1, devices without anisotropic filtering ignore it.
+    pub fn with_anisotropy(mut self, anisotropy: u8) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    /// Renders the scene at a fixed resolution, upscaled into the window by the greatest
+    /// whole factor and centered between black bars
+    pub fn with_design_resolution(mut self, width: u32, height: u32) -> Self {
+        self.fixed_resolution = Some(FixedResolution::Design {
+            design_size: (width, height),
+        });
+        self
+    }
+
+    /// Renders the scene at a fixed resolution for pixel art, scaled into the window by the
+    /// greatest whole factor, at least 1, with nearest-neighbor sampling and centered between
+    /// black bars
+    pub fn with_pixel_perfect_resolution(mut self, width: u32, height: u32) -> Self {
+        self.fixed_resolution = Some(FixedResolution::PixelPerfect {
+            internal_size: (width, height),
+        });
+        self
+    }
+
+    /// Creates the device and the renderers drawing to targets of the given format, and the swap
+    /// chain presenting to the surface if there is one
+    fn initialize_state(
+        &mut self,
+        adapter: &wgpu::Adapter,
+        surface: Option<wgpu::Surface>,
+        format: wgpu::TextureFormat,
+        window_size: WindowSize,
+    ) {
+        let device_descriptor = device_descriptor(
+            self.features,
+            &self.limits,
+            adapter.features(),
+            &adapter.limits(),
+        );
+        let device_and_queue = async { adapter.request_device(&device_descriptor, None).await };
         let (device, queue) = futures::executor::block_on(device_and_queue).unwrap();
 
         let sc_desc = wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-            format: adapter.get_swap_chain_preferred_format(&surface),
+            format,
             width: window_size.0,
             height: window_size.1,
             present_mode: wgpu::PresentMode::Immediate,
         };
-        let format = sc_desc.format;
 
-        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
-        let quad_renderer = QuadRenderer::new(&device, &queue, &format);
-        let tilemap_renderer = TilemapRenderer::new(&device, &format);
-        let bounding_box_renderer = BoundingBoxRenderer::new(&device, &format);
+        self.sample_count = supported_sample_count(
+            self.sample_count,
+            adapter_sample_counts(&adapter.get_info()),
+        );
+        let swap_chain = surface
+            .as_ref()
+            .map(|surface| device.create_swap_chain(surface, &sc_desc));
+        let depth_texture_view = create_depth_texture_view(&device, window_size, self.sample_count);
+        let multisampled_framebuffer_view =
+            create_multisampled_framebuffer(&device, &sc_desc, self.sample_count);
+        let quad_renderer = QuadRenderer::new(&device, &queue, &format, self.sample_count);
+        let tilemap_renderer = TilemapRenderer::new(&device, &format, self.sample_count);
+        let line_renderer = LineRenderer::new(&device, &format, self.sample_count);
+        let mesh_2d_renderer = Mesh2DRenderer::new(&device, &format, self.sample_count);
+        let sample_count = self.sample_count;
+        let design_resolution_target = self.fixed_resolution.map(|fixed_resolution| {
+            DesignResolutionTarget::new(&device, &format, fixed_resolution, sample_count)
+        });
 
         self.wgpu_state = Some(WGPUState {
             surface,
@@ -100,47 +246,394 @@ impl LowLevelGraphicsAPI for GraphicsWGPU {
             queue,
             sc_desc,
             swap_chain,
+            depth_texture_view,
+            multisampled_framebuffer_view,
             window_size,
             quad_renderer,
             tilemap_renderer,
-            bounding_box_renderer,
+            line_renderer,
+            mesh_2d_renderer,
+            design_resolution_target,
+            anisotropy_clamp: supported_anisotropy_clamp(self.anisotropy),
+            draw_commands: vec![],
         });
     }
 
-    fn render(&mut self) {
+    /// Renders the frame prepared since the last render into a view of the size of the window
+    fn render_to(&mut self, target_view: &wgpu::TextureView) {
         let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
-        let frame = state.swap_chain.get_current_frame().unwrap().output;
         let mut encoder = state
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+        let mut frame_stats = RenderStats::default();
+        state.mesh_2d_renderer.upload(&state.device, &state.queue);
+        state.line_renderer.upload(&state.queue);
 
         {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
+            let (attachment, resolve_target, depth_attachment) = match (
+                &state.design_resolution_target,
+                &state.multisampled_framebuffer_view,
+            ) {
+                (Some(design_resolution_target), _) => design_resolution_target.scene_attachments(),
+                (None, Some(multisampled_framebuffer_view)) => (
+                    multisampled_framebuffer_view,
+                    Some(target_view),
+                    &state.depth_texture_view,
+                ),
+                (None, None) => (target_view, None, &state.depth_texture_view),
+            };
+            let clear_color = srgb_to_linear(self.clear_color);
+            let framebuffer_size = state.scene_size();
+            let viewport = match &self.camera {
+                Some(camera) => camera.viewport(framebuffer_size),
+                None => (0, 0, framebuffer_size.0, framebuffer_size.1),
+            };
+            // `None` while the clip rectangle is empty, the draws being skipped until the next one
+            let mut scissor = Some((0, 0, framebuffer_size.0, framebuffer_size.1));
+            let passes = depth_passes(&state.draw_commands);
+            let pass_count = passes.len();
+            for (pass_index, draw_commands) in passes.into_iter().enumerate() {
+                // The following passes draw over the previous ones, with a cleared depth buffer
+                let load = if pass_index == 0 {
+                    wgpu::LoadOp::Clear(wgpu::Color {
+                        r: clear_color.r as f64,
+                        g: clear_color.g as f64,
+                        b: clear_color.b as f64,
+                        a: clear_color.a as f64,
+                    })
+                } else {
+                    wgpu::LoadOp::Load
+                };
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Render Pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment,
+                        resolve_target,
+                        ops: wgpu::Operations { load, store: true },
+                    }],
+                    depth_stencil_attachment: Some(
+                        wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                            attachment: depth_attachment,
+                            depth_ops: Some(wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(1.0),
+                                store: true,
+                            }),
+                            stencil_ops: None,
+                        },
+                    ),
+                });
+                render_pass.set_viewport(
+                    viewport.0 as f32,
+                    viewport.1 as f32,
+                    viewport.2 as f32,
+                    viewport.3 as f32,
+                    0.0,
+                    1.0,
+                );
+                if let Some(scissor) = scissor {
+                    render_pass.set_scissor_rect(scissor.0, scissor.1, scissor.2, scissor.3);
+                }
+
+                for draw_command in draw_commands {
+                    match draw_command {
+                        DrawCommand::ClipRect(clip_rect) => {
+                            scissor = match (clip_rect, &self.camera) {
+                                (Some(clip_rect), Some(camera)) => {
+                                    scissor_rect(clip_rect, camera, viewport)
+                                }
+                                _ => Some((0, 0, framebuffer_size.0, framebuffer_size.1)),
+                            };
+                            if let Some(scissor) = scissor {
+                                render_pass
+                                    .set_scissor_rect(scissor.0, scissor.1, scissor.2, scissor.3);
+                            }
+                        }
+                        _ if scissor.is_none() => {}
+                        DrawCommand::Quads(instances) => state.quad_renderer.render(
+                            &mut render_pass,
+                            instances.clone(),
+                            self.wireframe_rendering,
+                            &mut frame_stats,
+                        ),
+                        DrawCommand::Tilemap(identifier) => state.tilemap_renderer.render(
+                            &mut render_pass,
+                            identifier,
+                            self.wireframe_rendering,
+                            &mut frame_stats,
+                        ),
+                        DrawCommand::Mesh(mesh_index) => state.mesh_2d_renderer.render(
+                            &mut render_pass,
+                            *mesh_index,
+                            self.wireframe_rendering,
+                            &mut frame_stats,
+                        ),
+                        // The passes are split at the depth clears
+                        DrawCommand::ClearDepth => {}
+                    }
+                }
+
+                if pass_index + 1 == pass_count {
+                    render_pass.set_scissor_rect(0, 0, framebuffer_size.0, framebuffer_size.1);
+                    state
+                        .line_renderer
+                        .render(&mut render_pass, &mut frame_stats);
+                }
+            }
+        }
+
+        if let Some(design_resolution_target) = &state.design_resolution_target {
+            let mut blit_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Design Resolution Blit Pass"),
                 color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: &frame.view,
+                    attachment: target_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: self.clear_color.0 as f64,
-                            g: self.clear_color.1 as f64,
-                            b: self.clear_color.2 as f64,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                         store: true,
                     },
                 }],
                 depth_stencil_attachment: None,
             });
-
-            state.quad_renderer.render(&mut render_pass);
-            state.tilemap_renderer.render(&mut render_pass);
-            state.bounding_box_renderer.render(&mut render_pass);
+            design_resolution_target
+                .blit(&mut blit_pass, (state.sc_desc.width, state.sc_desc.height));
         }
 
         state.queue.submit(std::iter::once(encoder.finish()));
+        state.quad_renderer.clear();
+        state.mesh_2d_renderer.clear();
+        state.draw_commands.clear();
+        self.last_frame_stats = frame_stats;
+    }
+}
+
+/// Returns the sample counts an adapter supports for its render targets
+///
+/// wgpu doesn't report the counts supported by the adapter, so they are derived from its
+/// backend: WebGPU only allows 1 and 4, the native backends support up to 8 on desktop GPUs.
+fn adapter_sample_counts(adapter_info: &wgpu::AdapterInfo) -> &'static [u32] {
+    match adapter_info.backend {
+        wgpu::Backend::BrowserWebGpu => &[1, 4],
+        _ => &[1, 2, 4, 8],
+    }
+}
+
+/// Returns the greatest of the adapter sample counts not above the requested one, 1 if none
+fn supported_sample_count(requested_sample_count: u32, adapter_sample_counts: &[u32]) -> u32 {
+    let sample_count = adapter_sample_counts
+        .iter()
+        .copied()
+        .filter(|&sample_count| sample_count <= requested_sample_count)
+        .max()
+        .unwrap_or(1);
+    if sample_count != requested_sample_count {
+        eprintln!(
+            "Sample count {} is not supported, {} is used instead",
+            requested_sample_count, sample_count
+        );
+    }
+
+    sample_count
+}
+
+/// The device features used by the renderer when available, `NON_FILL_POLYGON_MODE` for wireframes
+const RENDERER_FEATURES: wgpu::Features = wgpu::Features::NON_FILL_POLYGON_MODE;
+
+/// Returns the descriptor of the device requesting the renderer features and the given
+/// features and limits, lowered to the ones supported by the adapter
+fn device_descriptor(
+    features: wgpu::Features,
+    limits: &wgpu::Limits,
+    adapter_features: wgpu::Features,
+    adapter_limits: &wgpu::Limits,
+) -> wgpu::DeviceDescriptor<'static> {
+    let unsupported_features = features - adapter_features;
+    if !unsupported_features.is_empty() {
+        eprintln!(
+            "Device features {:?} are not supported and are disabled",
+            unsupported_features
+        );
+    }
+
+    wgpu::DeviceDescriptor {
+        features: (features | RENDERER_FEATURES) & adapter_features,
+        limits: wgpu::Limits {
+            max_bind_groups: limits.max_bind_groups.min(adapter_limits.max_bind_groups),
+            max_dynamic_uniform_buffers_per_pipeline_layout: limits
+                .max_dynamic_uniform_buffers_per_pipeline_layout
+                .min(adapter_limits.max_dynamic_uniform_buffers_per_pipeline_layout),
+            max_dynamic_storage_buffers_per_pipeline_layout: limits
+                .max_dynamic_storage_buffers_per_pipeline_layout
+                .min(adapter_limits.max_dynamic_storage_buffers_per_pipeline_layout),
+            max_sampled_textures_per_shader_stage: limits
+                .max_sampled_textures_per_shader_stage
+                .min(adapter_limits.max_sampled_textures_per_shader_stage),
+            max_samplers_per_shader_stage: limits
+                .max_samplers_per_shader_stage
+                .min(adapter_limits.max_samplers_per_shader_stage),
+            max_storage_buffers_per_shader_stage: limits
+                .max_storage_buffers_per_shader_stage
+                .min(adapter_limits.max_storage_buffers_per_shader_stage),
+            max_storage_textures_per_shader_stage: limits
+                .max_storage_textures_per_shader_stage
+                .min(adapter_limits.max_storage_textures_per_shader_stage),
+            max_uniform_buffers_per_shader_stage: limits
+                .max_uniform_buffers_per_shader_stage
+                .min(adapter_limits.max_uniform_buffers_per_shader_stage),
+            max_uniform_buffer_binding_size: limits
+                .max_uniform_buffer_binding_size
+                .min(adapter_limits.max_uniform_buffer_binding_size),
+            max_push_constant_size: limits
+                .max_push_constant_size
+                .min(adapter_limits.max_push_constant_size),
+        },
+        label: None,
+    }
+}
+
+/// The greatest anisotropy clamp wgpu accepts
+const MAX_ANISOTROPY: u8 = 16;
+
+/// Returns the requested anisotropy clamp if supported, `None` if it is 1 or unsupported
+fn supported_anisotropy_clamp(requested_anisotropy: u8) -> Option<NonZeroU8> {
+    if requested_anisotropy == 1 {
+        return None;
+    }
+
+    if requested_anisotropy.is_power_of_two() && requested_anisotropy <= MAX_ANISOTROPY {
+        NonZeroU8::new(requested_anisotropy)
+    } else {
+        eprintln!(
+            "Anisotropy {} is not supported, anisotropic filtering is disabled",
+            requested_anisotropy
+        );
+        None
+    }
+}
+
+/// Returns the sRGB variant of a swap chain format
+///
+/// Rendering to an sRGB surface keeps textures, which are sampled in linear space,
+/// looking the same on every platform.
+fn srgb_format(format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+    match format {
+        wgpu::TextureFormat::Bgra8Unorm => wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8UnormSrgb,
+        format => format,
+    }
+}
+
+/// Converts a user-provided sRGB color to the linear space expected by the sRGB surface
+pub(crate) fn srgb_to_linear(color: Color) -> Color {
+    let to_linear = |component: f32| {
+        if component <= 0.04045 {
+            component / 12.92
+        } else {
+            ((component + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    Color::rgba(
+        to_linear(color.r),
+        to_linear(color.g),
+        to_linear(color.b),
+        color.a,
+    )
+}
+
+/// Converts a clip rectangle in screen coordinates to a (x, y, width, height) scissor
+/// rectangle in framebuffer pixels, clamped to the viewport the camera draws to
+///
+/// Returns `None` if the scissor rectangle is empty, which wgpu rejects: nothing is drawn in it.
+fn scissor_rect(
+    clip_rect: &ClipRect,
+    camera: &OrthographicCamera,
+    viewport: (u32, u32, u32, u32),
+) -> Option<(u32, u32, u32, u32)> {
+    let to_pixels = |coordinate: f32, start: f32, end: f32, offset: u32, size: u32| {
+        offset
+            + ((coordinate - start) / (end - start) * size as f32)
+                .round()
+                .max(0.0)
+                .min(size as f32) as u32
+    };
+    let horizontal = |x| to_pixels(x, camera.left, camera.right, viewport.0, viewport.2);
+    let vertical = |y| to_pixels(y, camera.top, camera.bottom, viewport.1, viewport.3);
+
+    let (left, right) = (
+        horizontal(clip_rect.x),
+        horizontal(clip_rect.x + clip_rect.width),
+    );
+    let (top, bottom) = (
+        vertical(clip_rect.y),
+        vertical(clip_rect.y + clip_rect.height),
+    );
+    let (width, height) = (
+        left.max(right) - left.min(right),
+        top.max(bottom) - top.min(bottom),
+    );
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    Some((left.min(right), top.min(bottom), width, height))
+}
+
+/// The multisampling shared by every pipeline
+pub(crate) fn multisample_state(sample_count: u32) -> wgpu::MultisampleState {
+    wgpu::MultisampleState {
+        count: sample_count,
+        mask: !0,
+        alpha_to_coverage_enabled: false,
+    }
+}
+
+fn create_multisampled_framebuffer(
+    device: &wgpu::Device,
+    sc_desc: &wgpu::SwapChainDescriptor,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count == 1 {
+        return None;
+    }
+
+    Some(create_multisampled_framebuffer_view(
+        device,
+        sc_desc.format,
+        (sc_desc.width, sc_desc.height),
+        sample_count,
+    ))
+}
+
+impl LowLevelGraphicsAPI for GraphicsWGPU {
+    fn initialize(&mut self, window: Window, window_size: WindowSize) {
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let surface = unsafe { instance.create_surface(&window) };
+        let adapter = async {
+            instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: Some(&surface),
+                })
+                .await
+        };
+        let adapter = futures::executor::block_on(adapter).unwrap();
+        let format = srgb_format(adapter.get_swap_chain_preferred_format(&surface));
+
+        self.initialize_state(&adapter, Some(surface), format, window_size);
+    }
+
+    fn render(&mut self) {
+        let state = self.wgpu_state.as_ref().expect("Graphics is uninitialized");
+        let frame = state
+            .swap_chain
+            .as_ref()
+            .expect("Graphics has no surface to render to")
+            .get_current_frame()
+            .unwrap()
+            .output;
+        self.render_to(&frame.view);
     }
 
     fn prepare_quad(
@@ -151,6 +644,10 @@ impl LowLevelGraphicsAPI for GraphicsWGPU {
         bounding_box_rendering: bool,
     ) {
         let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
+        push_quad_command(
+            &mut state.draw_commands,
+            state.quad_renderer.instance_count(),
+        );
         state.quad_renderer.prepare(
             &state.device,
             &state.queue,
@@ -161,15 +658,20 @@ impl LowLevelGraphicsAPI for GraphicsWGPU {
         );
 
         if bounding_box_rendering {
-            state.bounding_box_renderer.prepare(
-                &state.queue,
+            state.line_renderer.prepare_bounding_box(
                 quad_description.width,
                 quad_description.height,
                 transform,
+                self.bounding_box_color,
             );
         }
     }
 
+    fn prepare_line(&mut self, start: (f32, f32), end: (f32, f32), color: Color) {
+        let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
+        state.line_renderer.prepare_line(start, end, color);
+    }
+
     fn prepare_tilemap(
         &mut self,
         tilemap: &Tilemap,
@@ -187,6 +689,27 @@ impl LowLevelGraphicsAPI for GraphicsWGPU {
             transform,
             &self.textures,
         );
+        state
+            .draw_commands
+            .push(DrawCommand::Tilemap(tilemap_render.identifier.clone()));
+    }
+
+    fn prepare_mesh(
+        &mut self,
+        mesh_description: &MeshDescription,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) {
+        let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
+        if let Some(mesh_index) = state.mesh_2d_renderer.prepare(
+            &state.device,
+            mesh_description,
+            transform,
+            apply_view_transform,
+            &self.textures,
+        ) {
+            state.draw_commands.push(DrawCommand::Mesh(mesh_index));
+        }
     }
 
     fn is_texture_in_memory(&self, texture_identifier: &str) -> bool {
@@ -194,13 +717,26 @@ impl LowLevelGraphicsAPI for GraphicsWGPU {
     }
 
     fn load_texture(&mut self, texture_data: TextureData) {
-        let state = self.wgpu_state.as_ref().expect("Graphics is uninitialized");
+        let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
         let identifier = texture_data.identifier.clone();
-        let texture =
-            Texture::from_texture_data(&state.device, &state.queue, texture_data).unwrap();
+        let texture = Texture::from_texture_data(
+            &state.device,
+            &state.queue,
+            texture_data,
+            state.anisotropy_clamp,
+        )
+        .unwrap();
+        state.invalidate_texture(&identifier);
         self.textures.insert(identifier, texture);
     }
 
+    fn unload_texture(&mut self, texture_identifier: &str) {
+        self.textures.remove(texture_identifier);
+        if let Some(state) = self.wgpu_state.as_mut() {
+            state.invalidate_texture(texture_identifier);
+        }
+    }
+
     fn update_camera(
         &mut self,
         camera_id: usize,
@@ -208,30 +744,146 @@ impl LowLevelGraphicsAPI for GraphicsWGPU {
         transform: &Transform2D,
     ) {
         let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
+        let camera = &camera.scaled_to_window(state.scene_size());
         self.camera_id = Some(camera_id);
+        self.camera = Some(*camera);
+        let view_matrix = if self.pixel_snapping {
+            camera.snapped_view_matrix(transform)
+        } else {
+            camera.view_matrix(transform)
+        };
         state
             .quad_renderer
-            .set_camera(&state.queue, camera, transform);
+            .set_camera(&state.queue, camera, &view_matrix);
         state
             .tilemap_renderer
-            .set_camera(&state.queue, camera, transform);
+            .set_camera(&state.queue, camera, &view_matrix);
         state
-            .bounding_box_renderer
-            .set_camera(&state.queue, camera, transform);
+            .line_renderer
+            .set_camera(&state.queue, camera, &view_matrix);
+        state
+            .mesh_2d_renderer
+            .set_camera(&state.queue, camera, &view_matrix);
+    }
+
+    fn set_clip_rect(&mut self, clip_rect: Option<ClipRect>) {
+        let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
+        state.draw_commands.push(DrawCommand::ClipRect(clip_rect));
+    }
+
+    fn clear_depth(&mut self) {
+        let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
+        state.draw_commands.push(DrawCommand::ClearDepth);
     }
 
-    fn set_clear_color(&mut self, color: (f32, f32, f32)) {
+    fn set_clear_color(&mut self, color: Color) {
         self.clear_color = color;
     }
 
+    fn set_bounding_box_color(&mut self, color: Color) {
+        self.bounding_box_color = color;
+    }
+
+    fn set_wireframe_rendering(&mut self, enabled: bool) {
+        self.wireframe_rendering = enabled;
+    }
+
+    fn set_pixel_snapping(&mut self, enabled: bool) {
+        self.pixel_snapping = enabled;
+    }
+
     fn on_window_resized(&mut self, new_size: WindowSize) {
         let state = self.wgpu_state.as_mut().expect("Graphics is uninitialized");
         state.window_size = new_size;
         state.sc_desc.width = new_size.0;
         state.sc_desc.height = new_size.1;
-        state.swap_chain = state
-            .device
-            .create_swap_chain(&state.surface, &state.sc_desc);
+        if let Some(surface) = &state.surface {
+            state.swap_chain = Some(state.device.create_swap_chain(surface, &state.sc_desc));
+        }
+        state.depth_texture_view =
+            create_depth_texture_view(&state.device, new_size, self.sample_count);
+        state.multisampled_framebuffer_view =
+            create_multisampled_framebuffer(&state.device, &state.sc_desc, self.sample_count);
+    }
+
+    fn last_frame_stats(&self) -> RenderStats {
+        self.last_frame_stats
+    }
+
+    fn fixed_resolution(&self) -> Option<FixedResolution> {
+        self.fixed_resolution
+    }
+}
+
+/// A render pipeline built for filled polygons and, if the device supports it, for wireframes
+pub(crate) struct PolygonModePipelines<P = wgpu::RenderPipeline> {
+    fill: P,
+    line: Option<P>,
+}
+
+impl PolygonModePipelines {
+    /// Creates the pipelines, the line one only if the device has the `NON_FILL_POLYGON_MODE` feature
+    pub fn new(
+        device: &wgpu::Device,
+        create_pipeline: impl Fn(wgpu::PolygonMode) -> wgpu::RenderPipeline,
+    ) -> Self {
+        let wireframe_supported = device
+            .features()
+            .contains(wgpu::Features::NON_FILL_POLYGON_MODE);
+        Self::with_support(wireframe_supported, create_pipeline)
+    }
+}
+
+impl<P> PolygonModePipelines<P> {
+    fn with_support(
+        wireframe_supported: bool,
+        create_pipeline: impl Fn(wgpu::PolygonMode) -> P,
+    ) -> Self {
+        Self {
+            fill: create_pipeline(wgpu::PolygonMode::Fill),
+            line: if wireframe_supported {
+                Some(create_pipeline(wgpu::PolygonMode::Line))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Returns the pipeline to draw with, the fill one if wireframes are unsupported
+    pub fn active(&self, wireframe: bool) -> &P {
+        match (wireframe, &self.line) {
+            (true, Some(line)) => line,
+            _ => &self.fill,
+        }
+    }
+}
+
+/// Maps the [-1, 1] depth range of the camera projection to the [0, 1] range of wgpu
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.5,
+    0.0, 0.0, 0.0, 1.0,
+);
+
+/// Returns the projection matrix of a camera, the greater the z of a vertex the closer it is
+pub(crate) fn projection_matrix(camera: &OrthographicCamera) -> Matrix4<f32> {
+    OPENGL_TO_WGPU_MATRIX * camera.projection_matrix()
+}
+
+/// The depth test shared by every pipeline, equal depths are drawn in submission order
+///
+/// The depth buffer is cleared at every `DrawCommand::ClearDepth`, so only the draws between two
+/// clears are ordered by depth.
+pub(crate) fn depth_stencil_state() -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::LessEqual,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+        clamp_depth: false,
     }
 }
 
@@ -268,3 +920,635 @@ impl Vertex {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::render_target_descriptor;
+    use nalgebra::Point3;
+    use tuber_common::transform::IntoMatrix4;
+    use tuber_graphics::low_level::TextureDescription;
+    use tuber_graphics::texture::{TextureAddressMode, TextureFilter, TextureRegion};
+
+    /// The size of the offscreen targets rendered to by the pixel tests
+    const OFFSCREEN_SIZE: WindowSize = (80, 60);
+
+    /// Returns a renderer drawing to offscreen targets of `OFFSCREEN_SIZE`, with a camera showing
+    /// their pixels from the origin, `None` if there is no GPU adapter to render with
+    fn offscreen_graphics() -> Option<GraphicsWGPU> {
+        initialize_offscreen(GraphicsWGPU::new())
+    }
+
+    /// Initializes a configured renderer like `offscreen_graphics`
+    fn initialize_offscreen(mut graphics: GraphicsWGPU) -> Option<GraphicsWGPU> {
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let adapter =
+            futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+            }));
+        let adapter = match adapter {
+            Some(adapter) => adapter,
+            None => {
+                eprintln!("No GPU adapter, skipping the pixel test");
+                return None;
+            }
+        };
+
+        graphics.initialize_state(
+            &adapter,
+            None,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            OFFSCREEN_SIZE,
+        );
+        graphics.update_camera(
+            0,
+            &OrthographicCamera {
+                right: OFFSCREEN_SIZE.0 as f32,
+                bottom: OFFSCREEN_SIZE.1 as f32,
+                ..Default::default()
+            },
+            &Transform2D::default(),
+        );
+        Some(graphics)
+    }
+
+    /// Renders the prepared frame to an offscreen target and returns its RGBA pixels, row by row
+    fn render_offscreen(graphics: &mut GraphicsWGPU) -> Vec<[u8; 4]> {
+        let (width, height) = OFFSCREEN_SIZE;
+        let state = graphics.wgpu_state.as_ref().unwrap();
+        let target = state.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen_target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: state.sc_desc.format,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+        });
+        graphics.render_to(&target.create_view(&wgpu::TextureViewDescriptor::default()));
+
+        let state = graphics.wgpu_state.as_ref().unwrap();
+        let padded_row_size = (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = state.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("offscreen_readback_buffer"),
+            size: (padded_row_size * height) as u64,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = state
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: &target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: padded_row_size,
+                    rows_per_image: height,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+        );
+        state.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        state.device.poll(wgpu::Maintain::Wait);
+        futures::executor::block_on(mapping).unwrap();
+        let bytes = slice.get_mapped_range();
+        bytes
+            .chunks(padded_row_size as usize)
+            .flat_map(|row| row[..(width * 4) as usize].chunks(4))
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect()
+    }
+
+    fn pixel(pixels: &[[u8; 4]], x: u32, y: u32) -> [u8; 4] {
+        pixels[(y * OFFSCREEN_SIZE.0 + x) as usize]
+    }
+
+    fn colored_quad(width: f32, height: f32, color: Color) -> QuadDescription {
+        QuadDescription {
+            width,
+            height,
+            color,
+            texture: None,
+            corner_radius: 0.0,
+            outline_thickness: 0.0,
+            blend_mode: tuber_graphics::low_level::BlendMode::Alpha,
+        }
+    }
+
+    #[test]
+    fn mid_gray_is_displayed_as_mid_gray_on_the_srgb_surface() {
+        // What the sRGB surface does when storing a linear color as 8 bits
+        let encode = |component: f32| {
+            let srgb = if component <= 0.003_130_8 {
+                component * 12.92
+            } else {
+                1.055 * component.powf(1.0 / 2.4) - 0.055
+            };
+            (srgb * 255.0).round() as u8
+        };
+
+        let linear = srgb_to_linear(Color::rgba(0.5, 0.0, 1.0, 0.5));
+        assert!((linear.r - 0.214).abs() < 1e-3);
+        assert_eq!(
+            (encode(linear.r), encode(linear.g), encode(linear.b)),
+            (128, 0, 255)
+        );
+        assert_eq!(linear.a, 0.5);
+        assert_eq!(
+            srgb_format(wgpu::TextureFormat::Bgra8Unorm),
+            wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+    }
+
+    #[test]
+    fn clip_rects_are_converted_to_framebuffer_pixels() {
+        let camera = OrthographicCamera::default();
+        let clip_rect = |x, y, width, height| ClipRect {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        assert_eq!(
+            scissor_rect(
+                &clip_rect(100.0, 50.0, 200.0, 100.0),
+                &camera,
+                (0, 0, 1600, 1200)
+            ),
+            Some((200, 100, 400, 200))
+        );
+        assert_eq!(
+            scissor_rect(
+                &clip_rect(700.0, -50.0, 200.0, 100.0),
+                &camera,
+                (0, 0, 800, 600)
+            ),
+            Some((700, 0, 100, 50))
+        );
+        assert_eq!(
+            scissor_rect(
+                &clip_rect(100.0, 50.0, 200.0, 100.0),
+                &camera,
+                (100, 50, 800, 600)
+            ),
+            Some((200, 100, 200, 100))
+        );
+    }
+
+    #[test]
+    fn empty_clip_rects_have_no_scissor_rect() {
+        let camera = OrthographicCamera::default();
+        let clip_rect = |x, y, width, height| ClipRect {
+            x,
+            y,
+            width,
+            height,
+        };
+        let visible = clip_rect(100.0, 50.0, 200.0, 100.0);
+
+        for empty_clip_rect in &[
+            clip_rect(100.0, 50.0, 0.0, 100.0),
+            clip_rect(900.0, 50.0, 200.0, 100.0),
+            visible.intersection(&clip_rect(400.0, 50.0, 200.0, 100.0)),
+        ] {
+            assert_eq!(
+                scissor_rect(empty_clip_rect, &camera, (0, 0, 800, 600)),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn draws_outside_the_clip_rect_leave_the_pixels_untouched() {
+        let mut graphics = match offscreen_graphics() {
+            Some(graphics) => graphics,
+            None => return,
+        };
+        let clip_rect = |x, width| ClipRect {
+            x,
+            y: 0.0,
+            width,
+            height: 60.0,
+        };
+
+        graphics.set_clip_rect(Some(clip_rect(0.0, 40.0)));
+        graphics.prepare_quad(
+            &colored_quad(80.0, 60.0, Color::WHITE),
+            &Transform2D::default(),
+            true,
+            false,
+        );
+        graphics.set_clip_rect(Some(clip_rect(100.0, 40.0)));
+        graphics.prepare_quad(
+            &colored_quad(80.0, 60.0, Color::rgb(1.0, 0.0, 0.0)),
+            &Transform2D::default(),
+            true,
+            false,
+        );
+        graphics.set_clip_rect(None);
+        let pixels = render_offscreen(&mut graphics);
+
+        assert_eq!(pixel(&pixels, 10, 30), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 39, 59), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 40, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 70, 30), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn rounded_corners_leave_the_pixels_untouched() {
+        let mut graphics = match offscreen_graphics() {
+            Some(graphics) => graphics,
+            None => return,
+        };
+
+        graphics.prepare_quad(
+            &QuadDescription {
+                corner_radius: 8.0,
+                ..colored_quad(40.0, 20.0, Color::WHITE)
+            },
+            &Transform2D {
+                translation: (20.0, 20.0),
+                ..Default::default()
+            },
+            true,
+            false,
+        );
+        let pixels = render_offscreen(&mut graphics);
+
+        assert_eq!(pixel(&pixels, 40, 30), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 40, 20), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 20, 30), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 20, 20), [0, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 59, 39), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn textures_are_sampled_with_the_address_mode_of_the_quad() {
+        let mut graphics = match offscreen_graphics() {
+            Some(graphics) => graphics,
+            None => return,
+        };
+        graphics.load_texture(TextureData {
+            identifier: "stripes".into(),
+            size: (2, 1),
+            bytes: vec![255, 0, 0, 255, 0, 255, 0, 255],
+            filter: TextureFilter::Nearest,
+            address_mode: TextureAddressMode::ClampToEdge,
+        });
+        let striped_quad = |address_mode| QuadDescription {
+            texture: Some(TextureDescription {
+                identifier: "stripes".into(),
+                texture_region: TextureRegion::new(0.0, 0.0, 2.0, 1.0),
+                address_mode,
+            }),
+            ..colored_quad(80.0, 30.0, Color::WHITE)
+        };
+
+        graphics.prepare_quad(
+            &striped_quad(Some(TextureAddressMode::Repeat)),
+            &Transform2D::default(),
+            true,
+            false,
+        );
+        graphics.prepare_quad(
+            &striped_quad(None),
+            &Transform2D {
+                translation: (0.0, 30.0),
+                ..Default::default()
+            },
+            true,
+            false,
+        );
+        let pixels = render_offscreen(&mut graphics);
+
+        assert_eq!(pixel(&pixels, 10, 15), [255, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 30, 15), [0, 255, 0, 255]);
+        assert_eq!(pixel(&pixels, 50, 15), [255, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 70, 15), [0, 255, 0, 255]);
+        assert_eq!(pixel(&pixels, 10, 45), [255, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 50, 45), [0, 255, 0, 255]);
+        assert_eq!(pixel(&pixels, 70, 45), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn outlined_circles_only_cover_their_outline() {
+        let mut graphics = match offscreen_graphics() {
+            Some(graphics) => graphics,
+            None => return,
+        };
+
+        graphics.prepare_quad(
+            &QuadDescription {
+                corner_radius: 20.0,
+                outline_thickness: 4.0,
+                ..colored_quad(40.0, 40.0, Color::WHITE)
+            },
+            &Transform2D {
+                translation: (20.0, 10.0),
+                ..Default::default()
+            },
+            true,
+            false,
+        );
+        let pixels = render_offscreen(&mut graphics);
+
+        assert_eq!(pixel(&pixels, 21, 30), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 40, 11), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 40, 30), [0, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 20, 10), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn last_frame_stats_count_the_rendered_draws() {
+        let mut graphics = match offscreen_graphics() {
+            Some(graphics) => graphics,
+            None => return,
+        };
+
+        for _ in 0..3 {
+            graphics.prepare_quad(
+                &colored_quad(10.0, 10.0, Color::WHITE),
+                &Transform2D::default(),
+                true,
+                false,
+            );
+        }
+        graphics.prepare_line((0.0, 0.0), (10.0, 10.0), Color::WHITE);
+        assert_eq!(graphics.last_frame_stats(), RenderStats::default());
+
+        render_offscreen(&mut graphics);
+        assert_eq!(
+            graphics.last_frame_stats(),
+            RenderStats {
+                draw_calls: 4,
+                quads: 3,
+                vertices: 3 * 6 + 2,
+            }
+        );
+
+        render_offscreen(&mut graphics);
+        assert_eq!(graphics.last_frame_stats(), RenderStats::default());
+    }
+
+    #[test]
+    fn nearer_vertices_have_smaller_depth() {
+        let camera = OrthographicCamera::default();
+        let projection_matrix = projection_matrix(&camera);
+
+        let near = projection_matrix.transform_point(&Point3::new(400.0, 300.0, 50.0));
+        let middle = projection_matrix.transform_point(&Point3::new(400.0, 300.0, 0.0));
+        let far = projection_matrix.transform_point(&Point3::new(400.0, 300.0, -50.0));
+
+        assert!(near.z < middle.z && middle.z < far.z);
+        for point in &[near, middle, far] {
+            assert!((0.0..=1.0).contains(&point.z));
+        }
+        let gl_point = camera
+            .projection_matrix()
+            .transform_point(&Point3::new(400.0, 300.0, 50.0));
+        assert_eq!((near.x, near.y), (gl_point.x, gl_point.y));
+    }
+
+    #[test]
+    fn higher_quads_occlude_lower_ones_whatever_the_draw_order() {
+        let camera = OrthographicCamera::default();
+        let depth_at_center = |transform: Transform2D| {
+            (projection_matrix(&camera) * transform.into_matrix4())
+                .transform_point(&Point3::new(400.0, 300.0, 0.0))
+                .z
+        };
+        let lower_quad = ("lower", depth_at_center(Transform2D::default()));
+        let higher_quad = (
+            "higher",
+            depth_at_center(Transform2D {
+                z: 10.0,
+                ..Default::default()
+            }),
+        );
+        assert_eq!(
+            depth_stencil_state().depth_compare,
+            wgpu::CompareFunction::LessEqual
+        );
+
+        for draw_order in &[[lower_quad, higher_quad], [higher_quad, lower_quad]] {
+            // The fragments written to the center pixel of a cleared depth buffer
+            let mut depth_buffer = 1.0;
+            let mut drawn_quad = None;
+            for &(quad, depth) in draw_order {
+                if depth <= depth_buffer {
+                    depth_buffer = depth;
+                    drawn_quad = Some(quad);
+                }
+            }
+
+            assert_eq!(drawn_quad, Some("higher"));
+        }
+    }
+
+    fn adapter_info(backend: wgpu::Backend) -> wgpu::AdapterInfo {
+        wgpu::AdapterInfo {
+            name: "adapter".into(),
+            vendor: 0,
+            device: 0,
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            backend,
+        }
+    }
+
+    #[test]
+    fn unsupported_sample_counts_fall_back_to_the_nearest_lower_one() {
+        let native_sample_counts = adapter_sample_counts(&adapter_info(wgpu::Backend::Vulkan));
+        let web_sample_counts = adapter_sample_counts(&adapter_info(wgpu::Backend::BrowserWebGpu));
+
+        for &sample_count in &[1, 2, 4, 8] {
+            assert_eq!(
+                supported_sample_count(sample_count, native_sample_counts),
+                sample_count
+            );
+        }
+        assert_eq!(supported_sample_count(3, native_sample_counts), 2);
+        assert_eq!(supported_sample_count(64, native_sample_counts), 8);
+        assert_eq!(supported_sample_count(0, native_sample_counts), 1);
+        assert_eq!(supported_sample_count(2, web_sample_counts), 1);
+        assert_eq!(supported_sample_count(8, web_sample_counts), 4);
+    }
+
+    #[test]
+    fn requested_features_and_limits_are_passed_to_the_device() {
+        let graphics = GraphicsWGPU::new()
+            .with_features(wgpu::Features::PUSH_CONSTANTS)
+            .with_limits(wgpu::Limits {
+                max_bind_groups: 8,
+                ..Default::default()
+            });
+        let adapter_features = wgpu::Features::PUSH_CONSTANTS
+            | wgpu::Features::NON_FILL_POLYGON_MODE
+            | wgpu::Features::DEPTH_CLAMPING;
+        let adapter_limits = wgpu::Limits {
+            max_bind_groups: 8,
+            ..Default::default()
+        };
+
+        let descriptor = device_descriptor(
+            graphics.features,
+            &graphics.limits,
+            adapter_features,
+            &adapter_limits,
+        );
+        assert_eq!(
+            descriptor.features,
+            wgpu::Features::PUSH_CONSTANTS | wgpu::Features::NON_FILL_POLYGON_MODE
+        );
+        assert_eq!(descriptor.limits.max_bind_groups, 8);
+    }
+
+    #[test]
+    fn unsupported_features_and_limits_are_lowered_to_the_adapter_ones() {
+        let limits = wgpu::Limits {
+            max_bind_groups: 8,
+            ..Default::default()
+        };
+
+        let descriptor = device_descriptor(
+            wgpu::Features::PUSH_CONSTANTS,
+            &limits,
+            wgpu::Features::empty(),
+            &wgpu::Limits::default(),
+        );
+        assert_eq!(descriptor.features, wgpu::Features::empty());
+        assert_eq!(descriptor.limits, wgpu::Limits::default());
+    }
+
+    #[test]
+    fn unsupported_anisotropy_falls_back_to_none() {
+        assert_eq!(supported_anisotropy_clamp(1), None);
+        assert_eq!(supported_anisotropy_clamp(16), NonZeroU8::new(16));
+        assert_eq!(supported_anisotropy_clamp(3), None);
+        assert_eq!(supported_anisotropy_clamp(32), None);
+        assert_eq!(supported_anisotropy_clamp(0), None);
+    }
+
+    #[test]
+    fn pipelines_and_render_targets_use_the_same_sample_count() {
+        let adapter_sample_counts = adapter_sample_counts(&adapter_info(wgpu::Backend::Vulkan));
+
+        for &requested_sample_count in &[1, 2, 3, 4, 8, 16] {
+            let sample_count =
+                supported_sample_count(requested_sample_count, adapter_sample_counts);
+            let pipeline_sample_count = multisample_state(sample_count).count;
+
+            for &(label, format) in &[
+                (
+                    "multisampled_framebuffer",
+                    wgpu::TextureFormat::Bgra8UnormSrgb,
+                ),
+                ("depth_texture", DEPTH_FORMAT),
+            ] {
+                assert_eq!(
+                    render_target_descriptor(label, format, (800, 600), sample_count).sample_count,
+                    pipeline_sample_count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn multisampled_frames_are_resolved_into_the_target() {
+        let mut graphics = match initialize_offscreen(GraphicsWGPU::new().with_sample_count(4)) {
+            Some(graphics) => graphics,
+            None => return,
+        };
+
+        graphics.prepare_quad(
+            &colored_quad(20.0, 20.0, Color::WHITE),
+            &Transform2D {
+                translation: (10.0, 10.0),
+                ..Default::default()
+            },
+            true,
+            false,
+        );
+        let pixels = render_offscreen(&mut graphics);
+
+        assert_eq!(pixel(&pixels, 20, 20), [255, 255, 255, 255]);
+        assert_eq!(pixel(&pixels, 50, 50), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn depth_clears_split_the_render_passes() {
+        let draw_commands = vec![
+            DrawCommand::ClearDepth,
+            DrawCommand::Quads(0..2),
+            DrawCommand::ClearDepth,
+            DrawCommand::ClearDepth,
+            DrawCommand::Tilemap("ui".into()),
+            DrawCommand::Quads(2..3),
+            DrawCommand::ClearDepth,
+        ];
+
+        assert_eq!(
+            depth_passes(&draw_commands),
+            vec![
+                &[DrawCommand::Quads(0..2)][..],
+                &[DrawCommand::Tilemap("ui".into()), DrawCommand::Quads(2..3)][..],
+            ]
+        );
+        assert_eq!(depth_passes(&[DrawCommand::ClearDepth]).len(), 1);
+        assert_eq!(depth_passes(&[]).len(), 1);
+    }
+
+    #[test]
+    fn consecutive_quads_are_merged_around_tilemaps() {
+        let mut draw_commands = vec![];
+
+        push_quad_command(&mut draw_commands, 0);
+        push_quad_command(&mut draw_commands, 1);
+        draw_commands.push(DrawCommand::Tilemap("foreground".into()));
+        push_quad_command(&mut draw_commands, 2);
+
+        assert_eq!(
+            draw_commands,
+            vec![
+                DrawCommand::Quads(0..2),
+                DrawCommand::Tilemap("foreground".into()),
+                DrawCommand::Quads(2..3),
+            ]
+        );
+    }
+
+    #[test]
+    fn wireframe_toggle_switches_the_active_pipeline() {
+        let pipelines = PolygonModePipelines::with_support(true, |polygon_mode| polygon_mode);
+
+        assert_eq!(*pipelines.active(false), wgpu::PolygonMode::Fill);
+        assert_eq!(*pipelines.active(true), wgpu::PolygonMode::Line);
+        assert_eq!(*pipelines.active(false), wgpu::PolygonMode::Fill);
+    }
+
+    #[test]
+    fn wireframe_falls_back_to_fill_when_unsupported() {
+        let pipelines = PolygonModePipelines::with_support(false, |polygon_mode| polygon_mode);
+
+        assert_eq!(*pipelines.active(true), wgpu::PolygonMode::Fill);
+    }
+}
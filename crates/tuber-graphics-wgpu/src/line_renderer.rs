@@ -0,0 +1,296 @@
+use crate::{srgb_to_linear, Vertex};
+use nalgebra::{Matrix4, Point3};
+use tuber_common::transform::{IntoMatrix4, Transform2D};
+use tuber_graphics::camera::OrthographicCamera;
+use tuber_graphics::low_level::RenderStats;
+use tuber_graphics::Color;
+use wgpu::util::DeviceExt;
+use wgpu::{
+    BindGroupLayout, BufferDescriptor, BufferUsage, Device, FragmentState, Queue, RenderPass,
+    RenderPipeline, TextureFormat,
+};
+
+const MAX_VERTEX_COUNT: u64 = 100_000;
+
+pub(crate) struct LineRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    vertices: Vec<Vertex>,
+    uniform_bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+}
+
+impl LineRenderer {
+    pub fn new(device: &Device, texture_format: &TextureFormat, sample_count: u32) -> Self {
+        let uniforms = Uniforms::new();
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("line_renderer_uniform_buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("line_renderer_uniform_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("line_renderer_uniform_bind_group"),
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let render_pipeline = Self::create_render_pipeline(
+            &device,
+            &uniform_bind_group_layout,
+            texture_format,
+            sample_count,
+        );
+
+        let vertex_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("line_renderer_vertex_buffer"),
+            size: MAX_VERTEX_COUNT * std::mem::size_of::<Vertex>() as u64,
+            usage: BufferUsage::VERTEX | BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            vertices: vec![],
+            uniform_bind_group,
+            uniform_buffer,
+        }
+    }
+
+    fn create_render_pipeline(
+        device: &Device,
+        uniform_bind_group_layout: &BindGroupLayout,
+        texture_format: &TextureFormat,
+        sample_count: u32,
+    ) -> RenderPipeline {
+        let vertex_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/line_shader.vert.spv"));
+        let fragment_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/line_shader.frag.spv"));
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("line_renderer_colored_render_pipeline_layout"),
+            bind_group_layouts: &[&uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("line_renderer_colored_render_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &vertex_shader_module,
+                entry_point: "main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(FragmentState {
+                module: &fragment_shader_module,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format: *texture_format,
+                    alpha_blend: wgpu::BlendState::REPLACE,
+                    color_blend: wgpu::BlendState::REPLACE,
+                    write_mask: wgpu::ColorWrite::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::Back,
+                polygon_mode: wgpu::PolygonMode::Fill,
+            },
+            depth_stencil: Some(crate::depth_stencil_state()),
+            multisample: crate::multisample_state(sample_count),
+        })
+    }
+
+    pub fn prepare_line(&mut self, start: (f32, f32), end: (f32, f32), color: Color) {
+        self.vertices
+            .extend_from_slice(&line_vertices(start, end, color));
+    }
+
+    pub fn prepare_bounding_box(
+        &mut self,
+        width: f32,
+        height: f32,
+        transform_2d: &Transform2D,
+        color: Color,
+    ) {
+        self.vertices
+            .extend(bounding_box_vertices(width, height, transform_2d, color));
+    }
+
+    /// Writes the lines of the frame to the vertex buffer
+    pub fn upload(&self, queue: &Queue) {
+        upload_vertices(&self.vertices, |offset, data| {
+            queue.write_buffer(&self.vertex_buffer, offset, data)
+        });
+    }
+
+    pub fn render<'rpass>(
+        &'rpass mut self,
+        render_pass: &mut RenderPass<'rpass>,
+        frame_stats: &mut RenderStats,
+    ) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        let vertex_count = self.vertices.len().min(MAX_VERTEX_COUNT as usize);
+        render_pass.draw(0..vertex_count as u32, 0..1);
+        frame_stats.record_draw(vertex_count, 1);
+        self.vertices.clear();
+    }
+
+    pub fn set_camera(
+        &mut self,
+        queue: &Queue,
+        camera: &OrthographicCamera,
+        view_matrix: &Matrix4<f32>,
+    ) {
+        let projection_matrix = crate::projection_matrix(camera);
+        let view_proj = projection_matrix * view_matrix;
+        let uniform = Uniforms {
+            view_proj: view_proj.into(),
+        };
+        queue.write_buffer(&self.uniform_buffer, 0u64, bytemuck::cast_slice(&[uniform]));
+    }
+}
+
+/// Writes the vertices fitting the vertex buffer in a single write, if there are any
+fn upload_vertices(vertices: &[Vertex], mut write: impl FnMut(u64, &[u8])) {
+    if vertices.is_empty() {
+        return;
+    }
+
+    let vertex_count = vertices.len().min(MAX_VERTEX_COUNT as usize);
+    write(0, bytemuck::cast_slice(&vertices[..vertex_count]));
+}
+
+fn line_vertices(start: (f32, f32), end: (f32, f32), color: Color) -> [Vertex; 2] {
+    let color = srgb_to_linear(color);
+    let color = [color.r, color.g, color.b];
+    [
+        Vertex {
+            position: [start.0, start.1, 0.0],
+            color,
+            tex_coords: [0.0, 0.0],
+        },
+        Vertex {
+            position: [end.0, end.1, 0.0],
+            color,
+            tex_coords: [0.0, 0.0],
+        },
+    ]
+}
+
+/// Returns the vertices of the four lines outlining a quad
+fn bounding_box_vertices(
+    width: f32,
+    height: f32,
+    transform_2d: &Transform2D,
+    color: Color,
+) -> Vec<Vertex> {
+    let transform_matrix: Matrix4<f32> = transform_2d.into_matrix4();
+    let corners: Vec<(f32, f32)> = [(0.0, height), (0.0, 0.0), (width, 0.0), (width, height)]
+        .iter()
+        .map(|&(x, y)| {
+            let corner = transform_matrix.transform_point(&Point3::new(x, y, 0.0));
+            (corner.x, corner.y)
+        })
+        .collect();
+
+    (0..corners.len())
+        .flat_map(|i| line_vertices(corners[i], corners[(i + 1) % corners.len()], color).to_vec())
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct Uniforms {
+    view_proj: [[f32; 4]; 4],
+}
+
+impl Uniforms {
+    fn new() -> Self {
+        Self {
+            view_proj: Matrix4::new_orthographic(0.0, 800.0, 600.0, 0.0, -100.0, 100.0).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounding_box_vertices_carry_the_color() {
+        let transform = Transform2D {
+            translation: (10.0, 20.0),
+            ..Default::default()
+        };
+
+        let vertices = bounding_box_vertices(30.0, 40.0, &transform, Color::rgb(1.0, 0.0, 1.0));
+
+        assert_eq!(vertices.len(), 8);
+        assert!(vertices
+            .iter()
+            .all(|vertex| vertex.color == [1.0, 0.0, 1.0]));
+        assert_eq!(vertices[0].position, [10.0, 60.0, 0.0]);
+        assert_eq!(vertices[1].position, [10.0, 20.0, 0.0]);
+        assert_eq!(vertices[7].position, [10.0, 60.0, 0.0]);
+    }
+
+    #[test]
+    fn bounding_boxes_are_uploaded_in_a_single_write() {
+        for &box_count in &[1, 50] {
+            let vertices: Vec<Vertex> = (0..box_count)
+                .flat_map(|_| {
+                    bounding_box_vertices(
+                        30.0,
+                        40.0,
+                        &Transform2D::default(),
+                        Color::rgb(1.0, 0.0, 1.0),
+                    )
+                })
+                .collect();
+            let mut writes = vec![];
+
+            upload_vertices(&vertices, |offset, data| writes.push((offset, data.len())));
+
+            assert_eq!(
+                writes,
+                vec![(0, box_count * 8 * std::mem::size_of::<Vertex>())]
+            );
+        }
+    }
+
+    #[test]
+    fn nothing_is_uploaded_without_lines() {
+        let mut write_count = 0;
+        upload_vertices(&[], |_, _| write_count += 1);
+        assert_eq!(write_count, 0);
+    }
+}
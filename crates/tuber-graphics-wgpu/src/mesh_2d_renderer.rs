@@ -0,0 +1,465 @@
+use crate::texture::{Texture, TextureBindGroupKey};
+use crate::{srgb_to_linear, PolygonModePipelines, Vertex};
+use nalgebra::{Matrix4, Point3};
+use std::collections::HashMap;
+use std::ops::Range;
+use tuber_common::transform::{IntoMatrix4, Transform2D};
+use tuber_graphics::camera::OrthographicCamera;
+use tuber_graphics::low_level::{MeshDescription, RenderStats};
+use tuber_graphics::Color;
+use wgpu::util::DeviceExt;
+use wgpu::{
+    BindGroupLayout, BufferDescriptor, BufferUsage, Device, FragmentState, Queue, RenderPass,
+    TextureFormat,
+};
+
+/// The number of vertices the vertex buffer holds at first, it grows when a frame needs more
+const INITIAL_VERTEX_CAPACITY: usize = 1024;
+
+pub(crate) struct Mesh2DRenderer {
+    pipelines: PolygonModePipelines,
+    vertex_buffer: wgpu::Buffer,
+    /// The number of vertices the vertex buffer holds
+    vertex_capacity: usize,
+    /// The view and projection matrices, for the meshes drawn in world space
+    view_proj_uniform_buffer: wgpu::Buffer,
+    view_proj_bind_group: wgpu::BindGroup,
+    /// The projection matrix alone, for the meshes drawn in screen space
+    proj_uniform_buffer: wgpu::Buffer,
+    proj_bind_group: wgpu::BindGroup,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    texture_bind_groups: HashMap<TextureBindGroupKey, wgpu::BindGroup>,
+    batch: MeshBatch,
+}
+
+struct PreparedMesh {
+    vertices: Range<u32>,
+    texture_bind_group: TextureBindGroupKey,
+    apply_view_transform: bool,
+}
+
+/// The meshes prepared during a frame, their vertices being uploaded at once before rendering
+#[derive(Default)]
+struct MeshBatch {
+    vertices: Vec<Vertex>,
+    meshes: Vec<PreparedMesh>,
+}
+
+impl MeshBatch {
+    /// Adds the vertices of a mesh and returns its index
+    fn push(
+        &mut self,
+        vertices: Vec<Vertex>,
+        texture_bind_group: TextureBindGroupKey,
+        apply_view_transform: bool,
+    ) -> usize {
+        let first_vertex = self.vertices.len() as u32;
+        self.vertices.extend(vertices);
+        self.meshes.push(PreparedMesh {
+            vertices: first_vertex..self.vertices.len() as u32,
+            texture_bind_group,
+            apply_view_transform,
+        });
+        self.meshes.len() - 1
+    }
+
+    fn clear(&mut self) {
+        self.vertices.clear();
+        self.meshes.clear();
+    }
+}
+
+/// Returns the capacity doubled until it holds the required number of vertices
+fn grown_vertex_capacity(capacity: usize, required_vertex_count: usize) -> usize {
+    let mut capacity = capacity.max(1);
+    while capacity < required_vertex_count {
+        capacity *= 2;
+    }
+    capacity
+}
+
+fn create_vertex_buffer(device: &Device, vertex_capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("mesh_2d_renderer_vertex_buffer"),
+        size: (vertex_capacity * std::mem::size_of::<Vertex>()) as u64,
+        usage: BufferUsage::VERTEX | BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+impl Mesh2DRenderer {
+    pub fn new(device: &Device, texture_format: &TextureFormat, sample_count: u32) -> Self {
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("mesh_2d_renderer_uniform_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let (view_proj_uniform_buffer, view_proj_bind_group) =
+            Self::create_uniforms(device, &uniform_bind_group_layout, "view_proj");
+        let (proj_uniform_buffer, proj_bind_group) =
+            Self::create_uniforms(device, &uniform_bind_group_layout, "proj");
+
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("mesh_2d_renderer_texture_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler {
+                            comparison: false,
+                            filtering: true,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let vertex_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/mesh_2d.vert.spv"));
+        let fragment_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/mesh_2d.frag.spv"));
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("mesh_2d_renderer_render_pipeline_layout"),
+            bind_group_layouts: &[&texture_bind_group_layout, &uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipelines = PolygonModePipelines::new(device, |polygon_mode| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("mesh_2d_renderer_render_pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &vertex_shader_module,
+                    entry_point: "main",
+                    buffers: &[Vertex::desc()],
+                },
+                fragment: Some(FragmentState {
+                    module: &fragment_shader_module,
+                    entry_point: "main",
+                    targets: &[wgpu::ColorTargetState {
+                        format: *texture_format,
+                        alpha_blend: wgpu::BlendState::REPLACE,
+                        color_blend: wgpu::BlendState {
+                            src_factor: wgpu::BlendFactor::SrcAlpha,
+                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        write_mask: wgpu::ColorWrite::ALL,
+                    }],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: wgpu::CullMode::None,
+                    polygon_mode,
+                },
+                depth_stencil: Some(crate::depth_stencil_state()),
+                multisample: crate::multisample_state(sample_count),
+            })
+        });
+
+        Self {
+            pipelines,
+            vertex_buffer: create_vertex_buffer(device, INITIAL_VERTEX_CAPACITY),
+            vertex_capacity: INITIAL_VERTEX_CAPACITY,
+            view_proj_uniform_buffer,
+            view_proj_bind_group,
+            proj_uniform_buffer,
+            proj_bind_group,
+            texture_bind_group_layout,
+            texture_bind_groups: HashMap::new(),
+            batch: MeshBatch::default(),
+        }
+    }
+
+    fn create_uniforms(
+        device: &Device,
+        uniform_bind_group_layout: &BindGroupLayout,
+        name: &str,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("mesh_2d_renderer_{}_uniform_buffer", name)),
+            contents: bytemuck::cast_slice(&[Uniforms::new()]),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("mesh_2d_renderer_{}_uniform_bind_group", name)),
+            layout: uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        (uniform_buffer, uniform_bind_group)
+    }
+
+    /// Records the vertices of a mesh and returns its index, `None` if its texture isn't loaded
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        mesh_description: &MeshDescription,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+        textures: &HashMap<String, Texture>,
+    ) -> Option<usize> {
+        let texture = textures.get(&mesh_description.texture.identifier)?;
+        let key = (
+            mesh_description.texture.identifier.clone(),
+            mesh_description.texture.address_mode,
+        );
+        if !self.texture_bind_groups.contains_key(&key) {
+            let bind_group = texture.create_bind_group(
+                device,
+                &self.texture_bind_group_layout,
+                "mesh_2d_renderer_texture_bind_group",
+                mesh_description.texture.address_mode,
+            );
+            self.texture_bind_groups.insert(key.clone(), bind_group);
+        }
+
+        Some(self.batch.push(
+            mesh_vertices(mesh_description, transform),
+            key,
+            apply_view_transform,
+        ))
+    }
+
+    /// Writes the vertices of the frame, growing the vertex buffer if they don't fit
+    pub fn upload(&mut self, device: &Device, queue: &Queue) {
+        if self.batch.vertices.is_empty() {
+            return;
+        }
+
+        if self.batch.vertices.len() > self.vertex_capacity {
+            self.vertex_capacity =
+                grown_vertex_capacity(self.vertex_capacity, self.batch.vertices.len());
+            self.vertex_buffer = create_vertex_buffer(device, self.vertex_capacity);
+        }
+
+        queue.write_buffer(
+            &self.vertex_buffer,
+            0,
+            bytemuck::cast_slice(&self.batch.vertices),
+        );
+    }
+
+    pub fn render<'rpass>(
+        &'rpass self,
+        render_pass: &mut RenderPass<'rpass>,
+        mesh_index: usize,
+        wireframe: bool,
+        frame_stats: &mut RenderStats,
+    ) {
+        let mesh = &self.batch.meshes[mesh_index];
+        let uniform_bind_group = if mesh.apply_view_transform {
+            &self.view_proj_bind_group
+        } else {
+            &self.proj_bind_group
+        };
+
+        render_pass.set_pipeline(self.pipelines.active(wireframe));
+        render_pass.set_bind_group(0, &self.texture_bind_groups[&mesh.texture_bind_group], &[]);
+        render_pass.set_bind_group(1, uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(mesh.vertices.clone(), 0..1);
+        frame_stats.record_draw(mesh.vertices.len(), 1);
+    }
+
+    /// Forgets the meshes of the frame once it has been rendered
+    pub fn clear(&mut self) {
+        self.batch.clear();
+    }
+
+    /// Drops the bind groups of a replaced or unloaded texture
+    pub fn invalidate_texture(&mut self, texture_identifier: &str) {
+        self.texture_bind_groups
+            .retain(|(identifier, _), _| identifier != texture_identifier);
+    }
+
+    pub fn set_camera(
+        &mut self,
+        queue: &Queue,
+        camera: &OrthographicCamera,
+        view_matrix: &Matrix4<f32>,
+    ) {
+        let projection_matrix = crate::projection_matrix(camera);
+        let view_proj = Uniforms {
+            view_proj: (projection_matrix * view_matrix).into(),
+        };
+        let proj = Uniforms {
+            view_proj: projection_matrix.into(),
+        };
+        queue.write_buffer(
+            &self.view_proj_uniform_buffer,
+            0u64,
+            bytemuck::cast_slice(&[view_proj]),
+        );
+        queue.write_buffer(
+            &self.proj_uniform_buffer,
+            0u64,
+            bytemuck::cast_slice(&[proj]),
+        );
+    }
+}
+
+/// Returns the vertices of a mesh transformed by its transform, with texture coordinates
+/// mapped into its texture region
+fn mesh_vertices(mesh_description: &MeshDescription, transform: &Transform2D) -> Vec<Vertex> {
+    let transform_matrix: Matrix4<f32> = transform.into_matrix4();
+    let texture_region = mesh_description.texture.texture_region;
+    mesh_description
+        .vertices
+        .iter()
+        .map(|vertex| {
+            let (x, y, z) = vertex.position;
+            let position = transform_matrix.transform_point(&Point3::new(x, y, z));
+            let (r, g, b) = vertex.color;
+            let color = srgb_to_linear(Color::rgb(r, g, b));
+            let (u, v) = vertex.texture_coordinates;
+            Vertex {
+                position: [position.x, position.y, position.z],
+                color: [color.r, color.g, color.b],
+                tex_coords: [
+                    texture_region.x + u * texture_region.width,
+                    texture_region.y + v * texture_region.height,
+                ],
+            }
+        })
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct Uniforms {
+    view_proj: [[f32; 4]; 4],
+}
+
+impl Uniforms {
+    fn new() -> Self {
+        Self {
+            view_proj: Matrix4::new_orthographic(0.0, 800.0, 600.0, 0.0, -100.0, 100.0).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use tuber_graphics::low_level::{TextureDescription, VertexDescription};
+    use tuber_graphics::texture::TextureRegion;
+
+    #[test]
+    fn vertex_capacity_doubles_until_the_vertices_fit() {
+        assert_eq!(grown_vertex_capacity(1024, 1000), 1024);
+        assert_eq!(grown_vertex_capacity(1024, 1025), 2048);
+        assert_eq!(grown_vertex_capacity(1024, 60_000), 65_536);
+    }
+
+    #[test]
+    fn batched_meshes_keep_all_their_vertices() {
+        let mut batch = MeshBatch::default();
+        let triangle = || vec![Vertex::zeroed(); 3];
+
+        let mesh_indices: Vec<usize> = (0..400)
+            .map(|_| batch.push(triangle(), ("texture".into(), None), true))
+            .collect();
+
+        assert_eq!(mesh_indices, (0..400).collect::<Vec<_>>());
+        assert_eq!(batch.vertices.len(), 1200);
+        assert_eq!(batch.meshes[0].vertices, 0..3);
+        assert_eq!(batch.meshes[399].vertices, 1197..1200);
+        assert_eq!(
+            batch
+                .meshes
+                .iter()
+                .map(|mesh| mesh.vertices.len())
+                .sum::<usize>(),
+            1200
+        );
+    }
+
+    #[test]
+    fn mesh_vertices_are_transformed_and_mapped_into_the_texture_region() {
+        let vertex = |position: (f32, f32), texture_coordinates| VertexDescription {
+            position: (position.0, position.1, 0.0),
+            color: (1.0, 0.0, 1.0),
+            texture_coordinates,
+        };
+        let mesh_description = MeshDescription {
+            vertices: vec![
+                vertex((0.0, 0.0), (0.0, 0.0)),
+                vertex((0.0, 10.0), (0.0, 1.0)),
+                vertex((20.0, 10.0), (1.0, 1.0)),
+            ],
+            texture: TextureDescription {
+                identifier: "texture".into(),
+                texture_region: TextureRegion::new(0.5, 0.0, 0.5, 0.25),
+                address_mode: None,
+            },
+        };
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+
+        let vertices = mesh_vertices(&mesh_description, &transform);
+
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(vertices[0].position, [100.0, 50.0, 0.0]);
+        assert_eq!(vertices[2].position, [120.0, 60.0, 0.0]);
+        assert_eq!(vertices[0].tex_coords, [0.5, 0.0]);
+        assert_eq!(vertices[1].tex_coords, [0.5, 0.25]);
+        assert_eq!(vertices[2].tex_coords, [1.0, 0.25]);
+        assert!(vertices
+            .iter()
+            .all(|vertex| vertex.color == [1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn mesh_vertices_are_offset_by_the_transform_z() {
+        let mesh_description = MeshDescription {
+            vertices: vec![VertexDescription {
+                position: (0.0, 0.0, 1.0),
+                color: (1.0, 1.0, 1.0),
+                texture_coordinates: (0.0, 0.0),
+            }],
+            texture: TextureDescription {
+                identifier: "texture".into(),
+                texture_region: TextureRegion::new(0.0, 0.0, 1.0, 1.0),
+                address_mode: None,
+            },
+        };
+        let transform = Transform2D {
+            z: 3.0,
+            ..Default::default()
+        };
+
+        let vertices = mesh_vertices(&mesh_description, &transform);
+
+        assert_eq!(vertices[0].position, [0.0, 0.0, 4.0]);
+    }
+}
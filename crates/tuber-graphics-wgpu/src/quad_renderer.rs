@@ -1,16 +1,17 @@
-use crate::texture::Texture;
-use crate::Vertex;
-use nalgebra::{Matrix, Matrix4, Vector2, Vector3, Vector4};
+use crate::texture::{Texture, TextureBindGroupKey};
+use crate::{srgb_to_linear, PolygonModePipelines, Vertex};
+use nalgebra::{Matrix, Matrix4, Vector2, Vector4};
 use num_traits::identities::Zero;
 use std::collections::HashMap;
+use std::ops::Range;
 use tuber_common::transform::{IntoMatrix4, Transform2D};
 use tuber_graphics::camera::OrthographicCamera;
-use tuber_graphics::low_level::QuadDescription;
+use tuber_graphics::low_level::{BlendMode, QuadDescription, RenderStats};
 use tuber_graphics::texture::TextureData;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::{
     BindGroupLayout, BufferDescriptor, Device, FragmentState, Queue, RenderPass, RenderPipeline,
-    ShaderModule, TextureFormat,
+    TextureFormat,
 };
 
 const MAX_INSTANCE_COUNT: u64 = 100_000;
@@ -18,12 +19,13 @@ const VERTEX_COUNT_PER_INSTANCE: u32 = 6;
 const INSTANCE_BUFFER_SIZE: u64 = MAX_INSTANCE_COUNT * std::mem::size_of::<InstanceRaw>() as u64;
 
 pub struct QuadInstanceMetadata {
-    pub instance_bind_group: Option<String>,
+    pub instance_bind_group: Option<TextureBindGroupKey>,
+    pub blend_mode: BlendMode,
 }
 
 pub(crate) struct QuadRenderer {
-    colored_pipeline: wgpu::RenderPipeline,
-    textured_pipeline: wgpu::RenderPipeline,
+    colored_pipelines: HashMap<BlendMode, PolygonModePipelines>,
+    textured_pipelines: HashMap<BlendMode, PolygonModePipelines>,
     uniform_bind_group: wgpu::BindGroup,
     uniform_buffer: wgpu::Buffer,
     _texture_bind_group: wgpu::BindGroup,
@@ -33,11 +35,16 @@ pub(crate) struct QuadRenderer {
     instance_buffer: wgpu::Buffer,
     instances_metadata: Vec<QuadInstanceMetadata>,
     instances: Vec<Instance>,
-    texture_bind_groups: HashMap<String, wgpu::BindGroup>,
+    texture_bind_groups: HashMap<TextureBindGroupKey, wgpu::BindGroup>,
 }
 
 impl QuadRenderer {
-    pub fn new(device: &Device, queue: &Queue, texture_format: &TextureFormat) -> Self {
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        texture_format: &TextureFormat,
+        sample_count: u32,
+    ) -> Self {
         let uniforms = Uniforms::new();
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("quad_renderer_uniform_buffer"),
@@ -69,16 +76,12 @@ impl QuadRenderer {
             }],
         });
 
-        let textured_vertex_shader_module =
-            device.create_shader_module(&wgpu::include_spirv!("shaders/textured_shader.vert.spv"));
-        let textured_fragment_shader_module =
-            device.create_shader_module(&wgpu::include_spirv!("shaders/textured_shader.frag.spv"));
-
         let default_texture_bytes = include_bytes!("./textures/default_texture.png");
         let default_texture = Texture::from_texture_data(
             device,
             queue,
             TextureData::from_bytes("default_texture", default_texture_bytes).unwrap(),
+            None,
         )
         .unwrap();
 
@@ -90,7 +93,7 @@ impl QuadRenderer {
                         binding: 0,
                         visibility: wgpu::ShaderStage::FRAGMENT,
                         ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
                             view_dimension: wgpu::TextureViewDimension::D2,
                             multisampled: false,
                         },
@@ -123,19 +126,37 @@ impl QuadRenderer {
             ],
         });
 
-        let textured_pipeline = Self::create_textured_render_pipeline(
-            device,
-            &textured_vertex_shader_module,
-            &textured_fragment_shader_module,
-            texture_format,
-            &uniform_bind_group_layout,
-            &texture_bind_group_layout,
-        );
-        let colored_pipeline = Self::create_colored_quad_render_pipeline(
-            &device,
-            &uniform_bind_group_layout,
-            texture_format,
-        );
+        let mut textured_pipelines = HashMap::new();
+        let mut colored_pipelines = HashMap::new();
+        for &blend_mode in &BlendMode::ALL {
+            textured_pipelines.insert(
+                blend_mode,
+                PolygonModePipelines::new(device, |polygon_mode| {
+                    Self::create_textured_render_pipeline(
+                        device,
+                        texture_format,
+                        &uniform_bind_group_layout,
+                        &texture_bind_group_layout,
+                        sample_count,
+                        polygon_mode,
+                        blend_mode,
+                    )
+                }),
+            );
+            colored_pipelines.insert(
+                blend_mode,
+                PolygonModePipelines::new(device, |polygon_mode| {
+                    Self::create_colored_quad_render_pipeline(
+                        &device,
+                        &uniform_bind_group_layout,
+                        texture_format,
+                        sample_count,
+                        polygon_mode,
+                        blend_mode,
+                    )
+                }),
+            );
+        }
 
         let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: Some("quad_renderer_vertex_buffer"),
@@ -182,8 +203,8 @@ impl QuadRenderer {
         });
 
         Self {
-            colored_pipeline,
-            textured_pipeline,
+            colored_pipelines,
+            textured_pipelines,
             uniform_bind_group,
             uniform_buffer,
             texture: default_texture,
@@ -199,12 +220,19 @@ impl QuadRenderer {
 
     fn create_textured_render_pipeline(
         device: &Device,
-        textured_vertex_shader_module: &ShaderModule,
-        textured_fragment_shader_module: &ShaderModule,
         texture_format: &TextureFormat,
         uniform_bind_group_layout: &BindGroupLayout,
         texture_bind_group_layout: &BindGroupLayout,
+        sample_count: u32,
+        polygon_mode: wgpu::PolygonMode,
+        blend_mode: BlendMode,
     ) -> RenderPipeline {
+        let (color_blend, alpha_blend) = blend_states(blend_mode);
+        let textured_vertex_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/textured_shader.vert.spv"));
+        let textured_fragment_shader_module =
+            device.create_shader_module(&wgpu::include_spirv!("shaders/textured_shader.frag.spv"));
+
         let textured_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("quad_renderer_textured_render_pipeline_layout"),
@@ -225,8 +253,8 @@ impl QuadRenderer {
                 entry_point: "main",
                 targets: &[wgpu::ColorTargetState {
                     format: *texture_format,
-                    alpha_blend: wgpu::BlendState::REPLACE,
-                    color_blend: wgpu::BlendState::REPLACE,
+                    alpha_blend,
+                    color_blend,
                     write_mask: wgpu::ColorWrite::ALL,
                 }],
             }),
@@ -235,14 +263,10 @@ impl QuadRenderer {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: wgpu::CullMode::Back,
-                polygon_mode: wgpu::PolygonMode::Fill,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
+                polygon_mode,
             },
+            depth_stencil: Some(crate::depth_stencil_state()),
+            multisample: crate::multisample_state(sample_count),
         })
     }
 
@@ -250,7 +274,11 @@ impl QuadRenderer {
         device: &Device,
         uniform_bind_group_layout: &BindGroupLayout,
         texture_format: &TextureFormat,
+        sample_count: u32,
+        polygon_mode: wgpu::PolygonMode,
+        blend_mode: BlendMode,
     ) -> RenderPipeline {
+        let (color_blend, alpha_blend) = blend_states(blend_mode);
         let colored_vertex_shader_module =
             device.create_shader_module(&wgpu::include_spirv!("shaders/colored_shader.vert.spv"));
         let colored_fragment_shader_module =
@@ -276,8 +304,8 @@ impl QuadRenderer {
                 entry_point: "main",
                 targets: &[wgpu::ColorTargetState {
                     format: *texture_format,
-                    alpha_blend: wgpu::BlendState::REPLACE,
-                    color_blend: wgpu::BlendState::REPLACE,
+                    alpha_blend,
+                    color_blend,
                     write_mask: wgpu::ColorWrite::ALL,
                 }],
             }),
@@ -286,14 +314,10 @@ impl QuadRenderer {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: wgpu::CullMode::Back,
-                polygon_mode: wgpu::PolygonMode::Fill,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
+                polygon_mode,
             },
+            depth_stencil: Some(crate::depth_stencil_state()),
+            multisample: crate::multisample_state(sample_count),
         })
     }
 
@@ -306,19 +330,18 @@ impl QuadRenderer {
         apply_view_transform: bool,
         textures: &HashMap<String, Texture>,
     ) {
-        if self.instances.len() == 0 {
-            self.instances_metadata.clear();
-        }
-
+        let color = srgb_to_linear(quad.color);
         let instance = Instance {
             model: (*transform_2d).into_matrix4(),
-            color: Vector3::new(quad.color.0, quad.color.1, quad.color.2),
+            color: Vector4::new(color.r, color.g, color.b, color.a),
             size: Vector2::new(quad.width, quad.height),
             texture_rectangle: match &quad.texture {
                 Some(texture_description) => texture_description.texture_region.into(),
                 None => Vector4::zero(),
             },
             apply_view_transform: apply_view_transform as i32,
+            corner_radius: quad.corner_radius,
+            outline_thickness: quad.outline_thickness,
         };
 
         queue.write_buffer(
@@ -327,39 +350,34 @@ impl QuadRenderer {
             bytemuck::cast_slice(&[instance.to_raw()]),
         );
 
-        let instance_metadata = if let Some(texture_path) = &quad.texture {
-            if !self
-                .texture_bind_groups
-                .contains_key(&texture_path.identifier)
-            {
+        let instance_metadata = if let Some(texture_description) = &quad.texture {
+            let key = (
+                texture_description.identifier.clone(),
+                texture_description.address_mode,
+            );
+            if !self.texture_bind_groups.contains_key(&key) {
                 let texture = textures
-                    .get(&texture_path.identifier)
+                    .get(&texture_description.identifier)
                     .unwrap_or(&self.texture);
                 self.texture_bind_groups.insert(
-                    texture_path.identifier.clone(),
-                    device.create_bind_group(&wgpu::BindGroupDescriptor {
-                        label: Some("quad_renderer_textured_instance_bind_group"),
-                        layout: &self.texture_bind_group_layout,
-                        entries: &[
-                            wgpu::BindGroupEntry {
-                                binding: 0,
-                                resource: wgpu::BindingResource::TextureView(&texture.view),
-                            },
-                            wgpu::BindGroupEntry {
-                                binding: 1,
-                                resource: wgpu::BindingResource::Sampler(&texture.sampler),
-                            },
-                        ],
-                    }),
+                    key.clone(),
+                    texture.create_bind_group(
+                        device,
+                        &self.texture_bind_group_layout,
+                        "quad_renderer_textured_instance_bind_group",
+                        texture_description.address_mode,
+                    ),
                 );
             }
 
             QuadInstanceMetadata {
-                instance_bind_group: Some(texture_path.identifier.clone()),
+                instance_bind_group: Some(key),
+                blend_mode: quad.blend_mode,
             }
         } else {
             QuadInstanceMetadata {
                 instance_bind_group: None,
+                blend_mode: quad.blend_mode,
             }
         };
 
@@ -367,65 +385,156 @@ impl QuadRenderer {
         self.instances.push(instance);
     }
 
-    pub fn render<'rpass>(&'rpass mut self, render_pass: &mut RenderPass<'rpass>) {
-        for (i, instance_metadata) in self.instances_metadata.iter().enumerate() {
-            let instance_index = i as u32;
+    pub fn render<'rpass>(
+        &'rpass self,
+        render_pass: &mut RenderPass<'rpass>,
+        instances: Range<usize>,
+        wireframe: bool,
+        frame_stats: &mut RenderStats,
+    ) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        // Consecutive quads sharing a texture and a blend mode don't switch pipelines
+        let mut current_state = None;
+        for instance_index in grouped_by_blend_mode(&self.instances_metadata, instances) {
+            let instance_metadata = &self.instances_metadata[instance_index];
+            let instance_index = instance_index as u32;
+            let state = (
+                instance_metadata.instance_bind_group.as_ref(),
+                instance_metadata.blend_mode,
+            );
 
-            if let Some(instance_bind_group) = &instance_metadata.instance_bind_group {
-                render_pass.set_pipeline(&self.textured_pipeline);
-                render_pass.set_bind_group(0, &self.texture_bind_groups[instance_bind_group], &[]);
-                render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
-            } else {
-                render_pass.set_pipeline(&self.colored_pipeline);
-                render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            if current_state != Some(state) {
+                if let Some(instance_bind_group) = state.0 {
+                    render_pass.set_pipeline(self.textured_pipelines[&state.1].active(wireframe));
+                    render_pass.set_bind_group(
+                        0,
+                        &self.texture_bind_groups[instance_bind_group],
+                        &[],
+                    );
+                    render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+                } else {
+                    render_pass.set_pipeline(self.colored_pipelines[&state.1].active(wireframe));
+                    render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                }
+                current_state = Some(state);
             }
 
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
             render_pass.draw(
                 0..VERTEX_COUNT_PER_INSTANCE,
                 instance_index..instance_index + 1,
             );
+            frame_stats.record_draw(VERTEX_COUNT_PER_INSTANCE as usize, 1);
+            frame_stats.quads += 1;
         }
+    }
+
+    /// Discards the instances prepared for the rendered frame
+    pub fn clear(&mut self) {
         self.instances.clear();
+        self.instances_metadata.clear();
+    }
+
+    /// Drops the bind groups of a texture so that they are recreated on the next use
+    pub fn invalidate_texture(&mut self, texture_identifier: &str) {
+        self.texture_bind_groups
+            .retain(|(identifier, _), _| identifier != texture_identifier);
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.instances.len()
     }
 
     pub fn set_camera(
         &mut self,
         queue: &Queue,
         camera: &OrthographicCamera,
-        transform: &Transform2D,
+        view_matrix: &Matrix4<f32>,
     ) {
-        let projection_matrix: Matrix4<f32> = Matrix4::new_orthographic(
-            camera.left,
-            camera.right,
-            camera.bottom,
-            camera.top,
-            camera.near,
-            camera.far,
-        );
-        let view_matrix: Matrix4<f32> = (*transform).into_matrix4();
+        let projection_matrix = crate::projection_matrix(camera);
         let uniform = Uniforms {
             proj: projection_matrix.into(),
-            view: view_matrix.try_inverse().unwrap().into(),
+            view: (*view_matrix).into(),
         };
         queue.write_buffer(&self.uniform_buffer, 0u64, bytemuck::cast_slice(&[uniform]));
     }
 }
 
+/// Returns the quads of a draw command grouped by blend mode, so that the pipeline is switched
+/// once per blend mode rather than at every change of blend mode
+///
+/// The opaque quads are drawn first so that the others blend over them, then the alpha blended,
+/// multiplied and additive ones. The quads of a blend mode keep their preparation order, the ones
+/// of different blend modes overlapping at the same depth are layered by blend mode.
+fn grouped_by_blend_mode(
+    instances_metadata: &[QuadInstanceMetadata],
+    instances: Range<usize>,
+) -> Vec<usize> {
+    let draw_rank = |blend_mode| match blend_mode {
+        BlendMode::Opaque => 0,
+        BlendMode::Alpha => 1,
+        BlendMode::Multiply => 2,
+        BlendMode::Additive => 3,
+    };
+
+    let mut instances: Vec<usize> = instances.collect();
+    instances
+        .sort_by_key(|&instance_index| draw_rank(instances_metadata[instance_index].blend_mode));
+    instances
+}
+
+/// Returns the (color, alpha) blend states of a blend mode
+fn blend_states(blend_mode: BlendMode) -> (wgpu::BlendState, wgpu::BlendState) {
+    let keep_destination_alpha = wgpu::BlendState {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+
+    match blend_mode {
+        BlendMode::Alpha => (
+            wgpu::BlendState {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+            wgpu::BlendState::REPLACE,
+        ),
+        BlendMode::Additive => (
+            wgpu::BlendState {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            keep_destination_alpha,
+        ),
+        BlendMode::Multiply => (
+            wgpu::BlendState {
+                src_factor: wgpu::BlendFactor::DstColor,
+                dst_factor: wgpu::BlendFactor::Zero,
+                operation: wgpu::BlendOperation::Add,
+            },
+            keep_destination_alpha,
+        ),
+        BlendMode::Opaque => (wgpu::BlendState::REPLACE, wgpu::BlendState::REPLACE),
+    }
+}
+
 struct Instance {
     model: Matrix4<f32>,
-    color: Vector3<f32>,
+    color: Vector4<f32>,
     size: Vector2<f32>,
     texture_rectangle: Vector4<f32>,
     apply_view_transform: i32,
+    corner_radius: f32,
+    outline_thickness: f32,
 }
 
 impl Instance {
     fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
             model: self.model.into(),
-            color: [self.color.x, self.color.y, self.color.z],
+            color: [self.color.x, self.color.y, self.color.z, self.color.w],
             size: [self.size.x, self.size.y],
             texture_rectangle: [
                 self.texture_rectangle.x,
@@ -434,6 +543,8 @@ impl Instance {
                 self.texture_rectangle.w,
             ],
             apply_view_transform: self.apply_view_transform,
+            corner_radius: self.corner_radius,
+            outline_thickness: self.outline_thickness,
         }
     }
 }
@@ -442,10 +553,12 @@ impl Instance {
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 struct InstanceRaw {
     model: [[f32; 4]; 4],
-    color: [f32; 3],
+    color: [f32; 4],
     size: [f32; 2],
     texture_rectangle: [f32; 4],
     apply_view_transform: i32,
+    corner_radius: f32,
+    outline_thickness: f32,
 }
 
 impl InstanceRaw {
@@ -476,25 +589,35 @@ impl InstanceRaw {
                     shader_location: 6,
                 },
                 wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float3,
+                    format: wgpu::VertexFormat::Float4,
                     offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
                     shader_location: 7,
                 },
                 wgpu::VertexAttribute {
                     format: wgpu::VertexFormat::Float2,
-                    offset: mem::size_of::<[f32; 19]>() as wgpu::BufferAddress,
+                    offset: mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
                     shader_location: 8,
                 },
                 wgpu::VertexAttribute {
                     format: wgpu::VertexFormat::Float4,
-                    offset: mem::size_of::<[f32; 21]>() as wgpu::BufferAddress,
+                    offset: mem::size_of::<[f32; 22]>() as wgpu::BufferAddress,
                     shader_location: 9,
                 },
                 wgpu::VertexAttribute {
                     format: wgpu::VertexFormat::Int,
-                    offset: mem::size_of::<[f32; 25]>() as wgpu::BufferAddress,
+                    offset: mem::size_of::<[f32; 26]>() as wgpu::BufferAddress,
                     shader_location: 10,
                 },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float,
+                    offset: mem::size_of::<[f32; 27]>() as wgpu::BufferAddress,
+                    shader_location: 11,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float,
+                    offset: mem::size_of::<[f32; 28]>() as wgpu::BufferAddress,
+                    shader_location: 12,
+                },
             ],
         }
     }
@@ -515,3 +638,99 @@ impl Uniforms {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Rgba = [f32; 4];
+
+    fn factor(factor: wgpu::BlendFactor, source: Rgba, destination: Rgba, channel: usize) -> f32 {
+        match factor {
+            wgpu::BlendFactor::Zero => 0.0,
+            wgpu::BlendFactor::One => 1.0,
+            wgpu::BlendFactor::SrcAlpha => source[3],
+            wgpu::BlendFactor::OneMinusSrcAlpha => 1.0 - source[3],
+            wgpu::BlendFactor::DstColor => destination[channel],
+            factor => panic!("Unexpected blend factor {:?}", factor),
+        }
+    }
+
+    /// Composites a color over the framebuffer as the blend states of a blend mode do
+    fn composite(blend_mode: BlendMode, source: Rgba, destination: Rgba) -> Rgba {
+        let (color_blend, alpha_blend) = blend_states(blend_mode);
+        let mut result = [0.0; 4];
+        for channel in 0..4 {
+            let blend = if channel == 3 {
+                &alpha_blend
+            } else {
+                &color_blend
+            };
+            assert_eq!(blend.operation, wgpu::BlendOperation::Add);
+            result[channel] = source[channel]
+                * factor(blend.src_factor, source, destination, channel)
+                + destination[channel] * factor(blend.dst_factor, source, destination, channel);
+        }
+        result
+    }
+
+    #[test]
+    fn quads_are_grouped_by_blend_mode_in_preparation_order() {
+        let instances_metadata: Vec<QuadInstanceMetadata> = [
+            BlendMode::Alpha,
+            BlendMode::Additive,
+            BlendMode::Alpha,
+            BlendMode::Opaque,
+            BlendMode::Multiply,
+            BlendMode::Additive,
+            BlendMode::Alpha,
+        ]
+        .iter()
+        .map(|&blend_mode| QuadInstanceMetadata {
+            instance_bind_group: None,
+            blend_mode,
+        })
+        .collect();
+
+        assert_eq!(
+            grouped_by_blend_mode(&instances_metadata, 0..7),
+            vec![3, 0, 2, 6, 4, 1, 5]
+        );
+        assert_eq!(
+            grouped_by_blend_mode(&instances_metadata, 1..4),
+            vec![3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn additive_quads_sum_their_colors() {
+        let light = [0.25, 0.5, 0.0, 1.0];
+        let framebuffer = composite(BlendMode::Additive, light, [0.0, 0.0, 0.0, 1.0]);
+        let framebuffer = composite(BlendMode::Additive, light, framebuffer);
+
+        assert_eq!(framebuffer, [0.5, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn blend_modes_combine_with_the_framebuffer() {
+        let background = [0.5, 0.5, 1.0, 1.0];
+        let half_transparent_red = [1.0, 0.0, 0.0, 0.5];
+
+        assert_eq!(
+            composite(BlendMode::Alpha, half_transparent_red, background),
+            [0.75, 0.25, 0.5, 0.5]
+        );
+        assert_eq!(
+            composite(BlendMode::Additive, half_transparent_red, background),
+            [1.0, 0.5, 1.0, 1.0]
+        );
+        assert_eq!(
+            composite(BlendMode::Multiply, half_transparent_red, background),
+            [0.5, 0.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            composite(BlendMode::Opaque, half_transparent_red, background),
+            half_transparent_red
+        );
+    }
+}
@@ -0,0 +1,10 @@
+#version 450
+
+layout(location=0) out vec2 v_tex_coords;
+
+// Covers the viewport with a single triangle whose texture coordinates span [0, 1] on screen
+void main() {
+    vec2 position = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+    v_tex_coords = position;
+    gl_Position = vec4(position * vec2(2.0, -2.0) + vec2(-1.0, 1.0), 0.0, 1.0);
+}
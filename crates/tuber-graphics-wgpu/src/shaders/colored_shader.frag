@@ -1,9 +1,30 @@
 #version 450
 
-layout(location=0) in vec3 v_color;
+layout(location=0) in vec4 v_color;
+layout(location=1) in vec2 v_local_position;
+layout(location=2) in vec2 v_size;
+layout(location=3) in float v_corner_radius;
+layout(location=4) in float v_outline_thickness;
 layout(location=0) out vec4 f_color;
 
+// Signed distance to the border of a rounded rectangle, negative inside
+float rounded_rectangle_distance(vec2 position, vec2 half_size, float radius) {
+    vec2 q = abs(position - half_size) - half_size + radius;
+    return length(max(q, 0.0)) + min(max(q.x, q.y), 0.0) - radius;
+}
 
 void main() {
-    f_color = vec4(v_color, 1.0);
+    float alpha = 1.0;
+    if (v_corner_radius > 0.0 || v_outline_thickness > 0.0) {
+        vec2 half_size = v_size / 2.0;
+        float radius = min(v_corner_radius, min(half_size.x, half_size.y));
+        float distance = rounded_rectangle_distance(v_local_position, half_size, radius);
+        alpha = clamp(0.5 - distance, 0.0, 1.0);
+        if (v_outline_thickness > 0.0)
+            alpha = min(alpha, clamp(0.5 + distance + v_outline_thickness, 0.0, 1.0));
+        if (alpha <= 0.0)
+            discard;
+    }
+
+    f_color = vec4(v_color.rgb, v_color.a * alpha);
 }
\ No newline at end of file
@@ -8,11 +8,17 @@ layout(location=3) in vec4 model_matrix_0;
 layout(location=4) in vec4 model_matrix_1;
 layout(location=5) in vec4 model_matrix_2;
 layout(location=6) in vec4 model_matrix_3;
-layout(location=7) in vec3 color;
+layout(location=7) in vec4 color;
 layout(location=8) in vec2 size;
 layout(location=10) in int apply_view_transform;
+layout(location=11) in float corner_radius;
+layout(location=12) in float outline_thickness;
 
-layout(location=0) out vec3 v_color;
+layout(location=0) out vec4 v_color;
+layout(location=1) out vec2 v_local_position;
+layout(location=2) out vec2 v_size;
+layout(location=3) out float v_corner_radius;
+layout(location=4) out float v_outline_thickness;
 
 layout(set=0, binding=0)
 uniform Uniforms {
@@ -29,6 +35,10 @@ void main() {
     );
 
     v_color = color;
+    v_local_position = vec2(a_position.x * size.x, a_position.y * size.y);
+    v_size = size;
+    v_corner_radius = corner_radius;
+    v_outline_thickness = outline_thickness;
     mat4 view_proj;
     if (apply_view_transform != 0) {
         view_proj = u_proj * u_view;
@@ -1,6 +1,6 @@
 #version 450
 
-layout(location=0) in vec3 v_color;
+layout(location=0) in vec4 v_color;
 layout(location=1) in vec2 v_tex_coords;
 layout(location=0) out vec4 f_color;
 
@@ -12,5 +12,5 @@ void main() {
     vec4 texColor = texture(sampler2D(t_diffuse, s_diffuse), v_tex_coords);
     if(texColor.a < 0.1)
         discard;
-    f_color = texColor;
+    f_color = texColor * v_color;
 }
\ No newline at end of file
@@ -8,12 +8,12 @@ layout(location=3) in vec4 model_matrix_0;
 layout(location=4) in vec4 model_matrix_1;
 layout(location=5) in vec4 model_matrix_2;
 layout(location=6) in vec4 model_matrix_3;
-layout(location=7) in vec3 color;
+layout(location=7) in vec4 color;
 layout(location=8) in vec2 size;
 layout(location=9) in vec4 texture_rectangle;
 layout(location=10) in int apply_view_transform;
 
-layout(location=0) out vec3 v_color;
+layout(location=0) out vec4 v_color;
 layout(location=1) out vec2 v_tex_coords;
 
 layout(set=1, binding=0)
@@ -37,7 +37,7 @@ void main() {
         view_proj = u_proj;
     }
 
-    v_color = a_color;
+    v_color = color;
     v_tex_coords = vec2(texture_rectangle.x + a_tex_coords.x * texture_rectangle.z, texture_rectangle.y + a_tex_coords.y * texture_rectangle.w);
     gl_Position = view_proj * model_matrix * vec4(a_position.x * size.x, a_position.y * size.y, 0.0, 1.0);
 }
\ No newline at end of file
@@ -12,5 +12,5 @@ void main() {
     vec4 texColor = texture(sampler2D(t_diffuse, s_diffuse), v_tex_coords);
     if(texColor.a < 0.1)
         discard;
-    f_color = texColor;
+    f_color = vec4(texColor.rgb * v_color, texColor.a);
 }
\ No newline at end of file
@@ -16,5 +16,5 @@ uniform Uniforms {
 void main() {
     v_color = a_color;
     v_tex_coords = a_tex_coords;
-    gl_Position = u_view_proj * vec4(a_position.xy, 0.0, 1.0);
+    gl_Position = u_view_proj * vec4(a_position, 1.0);
 }
\ No newline at end of file
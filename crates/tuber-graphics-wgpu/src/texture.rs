@@ -1,13 +1,82 @@
 use crate::TuberGraphicsWGPUError;
-use tuber_graphics::texture::{TextureData, TextureSize};
+use std::num::NonZeroU8;
+use tuber_graphics::texture::{TextureAddressMode, TextureData, TextureFilter, TextureSize};
+use tuber_graphics::WindowSize;
 use wgpu::{TextureDimension, TextureFormat};
 
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// Identifies the bind group of a texture, sampled with the address mode overriding its own if any
+pub(crate) type TextureBindGroupKey = (String, Option<TextureAddressMode>);
+
+/// Creates the depth buffer of a render target of the given size
+pub fn create_depth_texture_view(
+    device: &wgpu::Device,
+    size: WindowSize,
+    sample_count: u32,
+) -> wgpu::TextureView {
+    create_render_target_view(device, "depth_texture", DEPTH_FORMAT, size, sample_count)
+}
+
+/// Creates the multisampled color buffer resolved into the swap chain frame
+pub fn create_multisampled_framebuffer_view(
+    device: &wgpu::Device,
+    format: TextureFormat,
+    size: WindowSize,
+    sample_count: u32,
+) -> wgpu::TextureView {
+    create_render_target_view(
+        device,
+        "multisampled_framebuffer",
+        format,
+        size,
+        sample_count,
+    )
+}
+
+fn create_render_target_view(
+    device: &wgpu::Device,
+    label: &str,
+    format: TextureFormat,
+    size: WindowSize,
+    sample_count: u32,
+) -> wgpu::TextureView {
+    let texture =
+        device.create_texture(&render_target_descriptor(label, format, size, sample_count));
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Describes a render target of the given size, with as many samples as the pipelines drawing
+/// to it
+pub(crate) fn render_target_descriptor(
+    label: &str,
+    format: TextureFormat,
+    size: WindowSize,
+    sample_count: u32,
+) -> wgpu::TextureDescriptor<'_> {
+    wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+    }
+}
+
 pub struct Texture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
     pub bind_group: wgpu::BindGroup,
     pub size: TextureSize,
+    filter: TextureFilter,
+    anisotropy_clamp: Option<NonZeroU8>,
 }
 
 impl Texture {
@@ -15,6 +84,7 @@ impl Texture {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         texture_data: TextureData,
+        anisotropy_clamp: Option<NonZeroU8>,
     ) -> Result<Self, TuberGraphicsWGPUError> {
         let rgba = texture_data.bytes;
         let size = texture_data.size;
@@ -50,15 +120,11 @@ impl Texture {
         );
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let sampler = device.create_sampler(&sampler_descriptor(
+            texture_data.filter,
+            texture_data.address_mode,
+            anisotropy_clamp,
+        ));
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("texture_bind_group_layout"),
@@ -69,7 +135,7 @@ impl Texture {
                     ty: wgpu::BindingType::Texture {
                         multisampled: false,
                         view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
                     },
                     count: None,
                 },
@@ -106,6 +172,129 @@ impl Texture {
             sampler,
             bind_group,
             size: (size.0, size.1),
+            filter: texture_data.filter,
+            anisotropy_clamp,
         })
     }
+
+    /// Creates a bind group of the texture view at binding 0 and a sampler at binding 1, the
+    /// sampler of the texture unless another address mode is given
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        address_mode: Option<TextureAddressMode>,
+    ) -> wgpu::BindGroup {
+        let address_mode_sampler = address_mode.map(|address_mode| {
+            device.create_sampler(&sampler_descriptor(
+                self.filter,
+                address_mode,
+                self.anisotropy_clamp,
+            ))
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(
+                        address_mode_sampler.as_ref().unwrap_or(&self.sampler),
+                    ),
+                },
+            ],
+        })
+    }
+}
+
+/// Returns the descriptor of a texture sampler
+///
+/// The anisotropy clamp only applies to linearly filtered textures, so that nearest filtered
+/// ones stay sharp.
+pub(crate) fn sampler_descriptor<'a>(
+    filter: TextureFilter,
+    address_mode: TextureAddressMode,
+    anisotropy_clamp: Option<NonZeroU8>,
+) -> wgpu::SamplerDescriptor<'a> {
+    let filter_mode = match filter {
+        TextureFilter::Nearest => wgpu::FilterMode::Nearest,
+        TextureFilter::Linear => wgpu::FilterMode::Linear,
+    };
+    let address_mode = match address_mode {
+        TextureAddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        TextureAddressMode::Repeat => wgpu::AddressMode::Repeat,
+        TextureAddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+    };
+
+    wgpu::SamplerDescriptor {
+        address_mode_u: address_mode,
+        address_mode_v: address_mode,
+        address_mode_w: address_mode,
+        mag_filter: filter_mode,
+        min_filter: filter_mode,
+        mipmap_filter: filter_mode,
+        anisotropy_clamp: match filter {
+            TextureFilter::Nearest => None,
+            TextureFilter::Linear => anisotropy_clamp,
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampler_descriptor_nearest() {
+        let descriptor = sampler_descriptor(
+            TextureFilter::Nearest,
+            TextureAddressMode::ClampToEdge,
+            None,
+        );
+        assert_eq!(descriptor.mag_filter, wgpu::FilterMode::Nearest);
+        assert_eq!(descriptor.min_filter, wgpu::FilterMode::Nearest);
+        assert_eq!(descriptor.mipmap_filter, wgpu::FilterMode::Nearest);
+    }
+
+    #[test]
+    fn sampler_descriptor_linear() {
+        let descriptor =
+            sampler_descriptor(TextureFilter::Linear, TextureAddressMode::ClampToEdge, None);
+        assert_eq!(descriptor.mag_filter, wgpu::FilterMode::Linear);
+        assert_eq!(descriptor.min_filter, wgpu::FilterMode::Linear);
+        assert_eq!(descriptor.mipmap_filter, wgpu::FilterMode::Linear);
+    }
+
+    #[test]
+    fn sampler_descriptor_repeat() {
+        let descriptor =
+            sampler_descriptor(TextureFilter::Nearest, TextureAddressMode::Repeat, None);
+        assert_eq!(descriptor.address_mode_u, wgpu::AddressMode::Repeat);
+        assert_eq!(descriptor.address_mode_v, wgpu::AddressMode::Repeat);
+    }
+
+    #[test]
+    fn only_linear_samplers_are_anisotropic() {
+        let anisotropy_clamp = NonZeroU8::new(16);
+
+        let descriptor = sampler_descriptor(
+            TextureFilter::Linear,
+            TextureAddressMode::ClampToEdge,
+            anisotropy_clamp,
+        );
+        assert_eq!(descriptor.anisotropy_clamp, anisotropy_clamp);
+        let descriptor = sampler_descriptor(
+            TextureFilter::Nearest,
+            TextureAddressMode::ClampToEdge,
+            anisotropy_clamp,
+        );
+        assert_eq!(descriptor.anisotropy_clamp, None);
+    }
 }
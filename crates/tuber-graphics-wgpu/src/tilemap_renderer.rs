@@ -1,18 +1,20 @@
 use crate::texture::Texture;
-use crate::Vertex;
+use crate::{PolygonModePipelines, Vertex};
+use bytemuck::Zeroable;
 use nalgebra::{Matrix4, Point4};
 use std::collections::HashMap;
-use tuber_common::tilemap::Tilemap;
+use tuber_common::tilemap::{Tile, Tilemap};
 use tuber_common::transform::{IntoMatrix4, Transform2D};
 use tuber_graphics::camera::OrthographicCamera;
+use tuber_graphics::low_level::RenderStats;
 use tuber_graphics::texture::TextureAtlas;
 use tuber_graphics::texture::TextureRegion;
-use tuber_graphics::tilemap::TilemapRender;
+use tuber_graphics::tilemap::{TilemapProjection, TilemapRender};
 use wgpu::util::DeviceExt;
 use wgpu::{BufferDescriptor, Device, FragmentState, Queue, RenderPass, TextureFormat};
 
 pub(crate) struct TilemapRenderer {
-    pipeline: wgpu::RenderPipeline,
+    pipelines: PolygonModePipelines,
     uniform_bind_group: wgpu::BindGroup,
     uniform_buffer: wgpu::Buffer,
     bind_group_layout: wgpu::BindGroupLayout,
@@ -20,7 +22,7 @@ pub(crate) struct TilemapRenderer {
 }
 
 impl TilemapRenderer {
-    pub fn new(device: &Device, texture_format: &TextureFormat) -> Self {
+    pub fn new(device: &Device, texture_format: &TextureFormat, sample_count: u32) -> Self {
         let uniforms = Uniforms::new();
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("tilemap_renderer_uniform_buffer"),
@@ -62,7 +64,7 @@ impl TilemapRenderer {
                     binding: 0,
                     visibility: wgpu::ShaderStage::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
                         view_dimension: wgpu::TextureViewDimension::D2,
                         multisampled: false,
                     },
@@ -86,41 +88,39 @@ impl TilemapRenderer {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("tilemap_renderer_render_pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &vertex_shader_module,
-                entry_point: "main",
-                buffers: &[Vertex::desc()],
-            },
-            fragment: Some(FragmentState {
-                module: &fragment_shader_module,
-                entry_point: "main",
-                targets: &[wgpu::ColorTargetState {
-                    format: *texture_format,
-                    alpha_blend: wgpu::BlendState::REPLACE,
-                    color_blend: wgpu::BlendState::REPLACE,
-                    write_mask: wgpu::ColorWrite::ALL,
-                }],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: wgpu::CullMode::Back,
-                polygon_mode: wgpu::PolygonMode::Fill,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+        let pipelines = PolygonModePipelines::new(device, |polygon_mode| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("tilemap_renderer_render_pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &vertex_shader_module,
+                    entry_point: "main",
+                    buffers: &[Vertex::desc()],
+                },
+                fragment: Some(FragmentState {
+                    module: &fragment_shader_module,
+                    entry_point: "main",
+                    targets: &[wgpu::ColorTargetState {
+                        format: *texture_format,
+                        alpha_blend: wgpu::BlendState::REPLACE,
+                        color_blend: wgpu::BlendState::REPLACE,
+                        write_mask: wgpu::ColorWrite::ALL,
+                    }],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: wgpu::CullMode::Back,
+                    polygon_mode,
+                },
+                depth_stencil: Some(crate::depth_stencil_state()),
+                multisample: crate::multisample_state(sample_count),
+            })
         });
 
         Self {
-            pipeline,
+            pipelines,
             uniform_bind_group,
             uniform_buffer,
             bind_group_layout,
@@ -138,178 +138,104 @@ impl TilemapRenderer {
         transform: &Transform2D,
         textures: &HashMap<String, Texture>,
     ) {
-        if !tilemap_render.dirty {
+        let built_vertex_count = self
+            .tilemap_data
+            .get(&tilemap_render.identifier)
+            .map(|tilemap_render_data| tilemap_render_data.vertex_count);
+        let tiles_to_update = tiles_to_update(tilemap, tilemap_render, built_vertex_count);
+        if tiles_to_update.is_empty() {
             return;
         }
 
-        let buffer = device.create_buffer(&BufferDescriptor {
-            label: None,
-            size: (tilemap.width * tilemap.height * 6 * std::mem::size_of::<Vertex>()) as u64,
-            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
-            mapped_at_creation: false,
-        });
-
         let texture_identifier = texture_atlas.texture_identifier();
         let texture = textures.get(texture_identifier).unwrap();
-        let texture_width = texture.size.0 as f32;
-        let texture_height = texture.size.1 as f32;
-
-        for j in 0..tilemap.height {
-            for i in 0..tilemap.width {
-                let texture_region_identifier = if let Some(texture_region_identifier) =
-                    (tilemap_render.tile_texture_function)(&tilemap.tiles[i + j * tilemap.width])
-                {
-                    texture_region_identifier
-                } else {
-                    continue;
-                };
-                let texture_region = texture_atlas
-                    .texture_region(texture_region_identifier)
-                    .unwrap();
-                let texture_region = TextureRegion {
-                    x: texture_region.x / texture_width,
-                    y: texture_region.y / texture_height,
-                    width: texture_region.width / texture_width,
-                    height: texture_region.height / texture_height,
-                };
-
-                let transform_matrix = transform.into_matrix4();
-
-                queue.write_buffer(
-                    &buffer,
-                    ((i + j * tilemap.width) * 6 * std::mem::size_of::<Vertex>()) as u64,
-                    bytemuck::cast_slice(&[
-                        Vertex {
-                            position: (transform_matrix
-                                * Point4::new(
-                                    (i * tilemap.tile_width) as f32,
-                                    (j * tilemap.tile_height) as f32,
-                                    0.0,
-                                    1.0,
-                                ))
-                            .xyz()
-                            .into(),
-                            color: [1.0, 1.0, 1.0],
-                            tex_coords: [texture_region.x, texture_region.y],
-                        },
-                        Vertex {
-                            position: (transform_matrix
-                                * Point4::new(
-                                    (i * tilemap.tile_width) as f32,
-                                    (j * tilemap.tile_height + tilemap.tile_height) as f32,
-                                    0.0,
-                                    1.0,
-                                ))
-                            .xyz()
-                            .into(),
-                            color: [1.0, 1.0, 1.0],
-                            tex_coords: [
-                                texture_region.x,
-                                texture_region.y + texture_region.height,
-                            ],
-                        },
-                        Vertex {
-                            position: (transform_matrix
-                                * Point4::new(
-                                    (i * tilemap.tile_width + tilemap.tile_width) as f32,
-                                    (j * tilemap.tile_height) as f32,
-                                    0.0,
-                                    1.0,
-                                ))
-                            .xyz()
-                            .into(),
-                            color: [1.0, 1.0, 1.0],
-                            tex_coords: [texture_region.x + texture_region.width, texture_region.y],
-                        },
-                        Vertex {
-                            position: (transform_matrix
-                                * Point4::new(
-                                    (i * tilemap.tile_width + tilemap.tile_width) as f32,
-                                    (j * tilemap.tile_height) as f32,
-                                    0.0,
-                                    1.0,
-                                ))
-                            .xyz()
-                            .into(),
-                            color: [1.0, 1.0, 1.0],
-                            tex_coords: [texture_region.x + texture_region.width, texture_region.y],
-                        },
-                        Vertex {
-                            position: (transform_matrix
-                                * Point4::new(
-                                    (i * tilemap.tile_width) as f32,
-                                    (j * tilemap.tile_height + tilemap.tile_height) as f32,
-                                    0.0,
-                                    1.0,
-                                ))
-                            .xyz()
-                            .into(),
-                            color: [1.0, 1.0, 1.0],
-                            tex_coords: [
-                                texture_region.x,
-                                texture_region.y + texture_region.height,
-                            ],
-                        },
-                        Vertex {
-                            position: (transform_matrix
-                                * Point4::new(
-                                    (i * tilemap.tile_width + tilemap.tile_width) as f32,
-                                    (j * tilemap.tile_height + tilemap.tile_height) as f32,
-                                    0.0,
-                                    1.0,
-                                ))
-                            .xyz()
-                            .into(),
-                            color: [1.0, 1.0, 1.0],
-                            tex_coords: [
-                                texture_region.x + texture_region.width,
-                                texture_region.y + texture_region.height,
-                            ],
-                        },
-                    ]),
-                )
-            }
+        let transform_matrix = transform.into_matrix4();
+
+        if needs_rebuild(tilemap, tilemap_render, built_vertex_count) {
+            let buffer = device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: (tilemap.width * tilemap.height * 6 * std::mem::size_of::<Vertex>()) as u64,
+                usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = self.create_texture_bind_group(device, texture);
+
+            self.tilemap_data.insert(
+                tilemap_render.identifier.to_owned(),
+                TilemapRenderData {
+                    vertex_data: buffer,
+                    vertex_count: tilemap.width * tilemap.height * 6,
+                    bind_group,
+                    texture_identifier: texture_identifier.to_owned(),
+                },
+            );
         }
 
-        let bind_group = self.create_texture_bind_group(device, texture);
+        let buffer = &self.tilemap_data[&tilemap_render.identifier].vertex_data;
+        for tile_index in tiles_to_update {
+            let tile = &tilemap.tiles[tile_index];
+            let texture_region = tilemap_render.texture_region_identifier(tile).and_then(
+                |texture_region_identifier| {
+                    normalized_tile_region(
+                        texture_atlas,
+                        texture_region_identifier,
+                        texture.size,
+                        tilemap_render.texel_inset,
+                    )
+                },
+            );
 
-        self.tilemap_data.insert(
-            tilemap_render.identifier.to_owned(),
-            TilemapRenderData {
-                vertex_data: buffer,
-                vertex_count: tilemap.width * tilemap.height * 6,
-                bind_group,
-            },
-        );
+            let draw_index = tilemap_render.projection.draw_index(tilemap, tile_index);
+            queue.write_buffer(
+                buffer,
+                tile_vertex_offset(draw_index),
+                bytemuck::cast_slice(&tile_vertices(
+                    tilemap,
+                    tilemap_render.projection,
+                    tile_index,
+                    texture_region,
+                    tile_color(tilemap_render, tile),
+                    &transform_matrix,
+                )),
+            );
+        }
     }
 
-    pub fn render<'rpass>(&'rpass mut self, render_pass: &mut RenderPass<'rpass>) {
-        for tilemap_render_data in self.tilemap_data.values() {
-            render_pass.set_pipeline(&self.pipeline);
+    pub fn render<'rpass>(
+        &'rpass self,
+        render_pass: &mut RenderPass<'rpass>,
+        identifier: &str,
+        wireframe: bool,
+        frame_stats: &mut RenderStats,
+    ) {
+        let tilemap_render_data = match self.tilemap_data.get(identifier) {
+            Some(tilemap_render_data) => tilemap_render_data,
+            None => return,
+        };
 
-            render_pass.set_bind_group(0, &tilemap_render_data.bind_group, &[]);
-            render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, tilemap_render_data.vertex_data.slice(..));
-            render_pass.draw(0..tilemap_render_data.vertex_count as u32, 0..1);
-        }
+        render_pass.set_pipeline(self.pipelines.active(wireframe));
+        render_pass.set_bind_group(0, &tilemap_render_data.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, tilemap_render_data.vertex_data.slice(..));
+        render_pass.draw(0..tilemap_render_data.vertex_count as u32, 0..1);
+        frame_stats.record_draw(tilemap_render_data.vertex_count, 1);
+    }
+
+    /// Drops the tilemaps using a texture so that they are rebuilt on their next preparation
+    pub fn invalidate_texture(&mut self, texture_identifier: &str) {
+        self.tilemap_data.retain(|_, tilemap_render_data| {
+            tilemap_render_data.texture_identifier != texture_identifier
+        });
     }
 
     pub fn set_camera(
         &mut self,
         queue: &Queue,
         camera: &OrthographicCamera,
-        transform: &Transform2D,
+        view_matrix: &Matrix4<f32>,
     ) {
-        let projection_matrix: Matrix4<f32> = Matrix4::new_orthographic(
-            camera.left,
-            camera.right,
-            camera.bottom,
-            camera.top,
-            camera.near,
-            camera.far,
-        );
-        let view_matrix: Matrix4<f32> = (*transform).into_matrix4();
-        let view_proj = projection_matrix * view_matrix.try_inverse().unwrap();
+        let projection_matrix = crate::projection_matrix(camera);
+        let view_proj = projection_matrix * view_matrix;
         let uniform = Uniforms {
             view_proj: view_proj.into(),
         };
@@ -348,8 +274,404 @@ impl Uniforms {
     }
 }
 
+/// Returns whether the vertex buffer of a tilemap must be created again, given the vertex count
+/// of its current buffer if it has one
+///
+/// The buffer is rebuilt when the tilemap is dirty or doesn't have the size of the buffer anymore.
+fn needs_rebuild(
+    tilemap: &Tilemap,
+    tilemap_render: &TilemapRender,
+    built_vertex_count: Option<usize>,
+) -> bool {
+    tilemap_render.dirty || built_vertex_count != Some(tilemap.width * tilemap.height * 6)
+}
+
+/// Returns the indices of the tiles whose vertices must be written
+fn tiles_to_update(
+    tilemap: &Tilemap,
+    tilemap_render: &TilemapRender,
+    built_vertex_count: Option<usize>,
+) -> Vec<usize> {
+    if needs_rebuild(tilemap, tilemap_render, built_vertex_count) {
+        return (0..tilemap.width * tilemap.height).collect();
+    }
+
+    let mut dirty_tiles: Vec<usize> = tilemap_render
+        .dirty_tiles
+        .iter()
+        .copied()
+        .filter(|&tile_index| tile_index < tilemap.width * tilemap.height)
+        .collect();
+    dirty_tiles.sort_unstable();
+    dirty_tiles
+}
+
+/// Returns the offset in bytes of the vertices of a tile in the tilemap vertex buffer, given its
+/// rank in the draw order
+fn tile_vertex_offset(draw_index: usize) -> u64 {
+    (draw_index * 6 * std::mem::size_of::<Vertex>()) as u64
+}
+
+/// Returns the region of a tile in normalized texture coordinates, `None` if it isn't in the atlas
+fn normalized_tile_region(
+    texture_atlas: &TextureAtlas,
+    texture_region_identifier: &str,
+    texture_size: (u32, u32),
+    texel_inset: bool,
+) -> Option<TextureRegion> {
+    let texture_region = texture_atlas.texture_region(texture_region_identifier)?;
+    let texture_region = if texel_inset {
+        texture_region.inset(0.5)
+    } else {
+        texture_region
+    };
+
+    Some(texture_region.normalize(texture_size.0, texture_size.1))
+}
+
+/// Returns the vertex color of a tile in linear space
+fn tile_color(tilemap_render: &TilemapRender, tile: &Tile) -> [f32; 3] {
+    let tint = crate::srgb_to_linear(tilemap_render.tile_tint(tile));
+    [tint.r, tint.g, tint.b]
+}
+
+/// Returns the vertices of a tile, degenerate if the tile has no texture
+///
+/// The texture coordinates are flipped according to the flip of the tile.
+fn tile_vertices(
+    tilemap: &Tilemap,
+    projection: TilemapProjection,
+    tile_index: usize,
+    texture_region: Option<TextureRegion>,
+    color: [f32; 3],
+    transform_matrix: &Matrix4<f32>,
+) -> [Vertex; 6] {
+    let texture_region = match texture_region {
+        Some(texture_region) => texture_region,
+        None => return [Vertex::zeroed(); 6],
+    };
+
+    let (left, top) = projection.tile_position(tilemap, tile_index);
+    let right = left + tilemap.tile_width as f32;
+    let bottom = top + tilemap.tile_height as f32;
+    let vertex = |x: f32, y: f32, tex_coords: [f32; 2]| Vertex {
+        position: (transform_matrix * Point4::new(x, y, 0.0, 1.0))
+            .xyz()
+            .into(),
+        color,
+        tex_coords,
+    };
+
+    let flip = tilemap.tiles[tile_index].flip;
+    let tex_coords = |x: f32, y: f32| {
+        let x = if flip.horizontal { 1.0 - x } else { x };
+        let y = if flip.vertical { 1.0 - y } else { y };
+        let (x, y) = if flip.diagonal { (y, x) } else { (x, y) };
+        [
+            texture_region.x + x * texture_region.width,
+            texture_region.y + y * texture_region.height,
+        ]
+    };
+
+    [
+        vertex(left, top, tex_coords(0.0, 0.0)),
+        vertex(left, bottom, tex_coords(0.0, 1.0)),
+        vertex(right, top, tex_coords(1.0, 0.0)),
+        vertex(right, top, tex_coords(1.0, 0.0)),
+        vertex(left, bottom, tex_coords(0.0, 1.0)),
+        vertex(right, bottom, tex_coords(1.0, 1.0)),
+    ]
+}
+
 struct TilemapRenderData {
     vertex_data: wgpu::Buffer,
     vertex_count: usize,
     bind_group: wgpu::BindGroup,
+    texture_identifier: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use tuber_common::tilemap::TileFlip;
+    use tuber_graphics::Color;
+
+    fn tilemap_render(dirty: bool, dirty_tiles: &[usize]) -> TilemapRender {
+        TilemapRender {
+            identifier: "tilemap".into(),
+            texture_atlas_identifier: "atlas".into(),
+            tile_texture_function: Box::new(|_| Some("tile")),
+            layer: 0,
+            dirty,
+            dirty_tiles: dirty_tiles.iter().copied().collect::<HashSet<_>>(),
+            animations: Default::default(),
+            animation_time: 0.0,
+            texel_inset: false,
+            tint: Color::WHITE,
+            tile_tint_function: None,
+            projection: TilemapProjection::Orthogonal,
+        }
+    }
+
+    #[test]
+    fn first_build_updates_every_tile() {
+        let tilemap = Tilemap::new(3, 2, 16, 16, &[]);
+
+        let tiles = tiles_to_update(&tilemap, &tilemap_render(false, &[]), None);
+
+        assert_eq!(tiles, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn single_changed_tile_updates_only_its_vertices() {
+        let tilemap = Tilemap::new(3, 2, 16, 16, &[]);
+
+        let tiles = tiles_to_update(&tilemap, &tilemap_render(false, &[4]), Some(36));
+
+        assert_eq!(tiles, vec![4]);
+        let vertex_size = std::mem::size_of::<Vertex>() as u64;
+        assert_eq!(tile_vertex_offset(4), 4 * 6 * vertex_size);
+        assert_eq!(
+            tile_vertex_offset(5) - tile_vertex_offset(4),
+            6 * vertex_size
+        );
+    }
+
+    #[test]
+    fn unchanged_tilemap_updates_nothing() {
+        let tilemap = Tilemap::new(3, 2, 16, 16, &[]);
+
+        assert!(tiles_to_update(&tilemap, &tilemap_render(false, &[]), Some(36)).is_empty());
+    }
+
+    #[test]
+    fn resized_tilemap_updates_every_tile() {
+        let tilemap = Tilemap::new(4, 2, 16, 16, &[]);
+
+        let tiles = tiles_to_update(&tilemap, &tilemap_render(false, &[7]), Some(36));
+
+        assert_eq!(tiles, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tile_vertices_are_placed_on_the_grid() {
+        let tilemap = Tilemap::new(3, 2, 16, 8, &[]);
+
+        let vertices = tile_vertices(
+            &tilemap,
+            TilemapProjection::Orthogonal,
+            4,
+            Some(TextureRegion::new(0.0, 0.0, 0.5, 0.5)),
+            [1.0, 1.0, 1.0],
+            &Matrix4::identity(),
+        );
+
+        assert_eq!(vertices[0].position, [16.0, 8.0, 0.0]);
+        assert_eq!(vertices[5].position, [32.0, 16.0, 0.0]);
+        assert_eq!(vertices[5].tex_coords, [0.5, 0.5]);
+    }
+
+    #[test]
+    fn tile_vertices_are_at_the_depth_of_the_tilemap() {
+        let tilemap = Tilemap::new(3, 2, 16, 8, &[]);
+        let transform = Transform2D {
+            z: -2.0,
+            ..Default::default()
+        };
+
+        let vertices = tile_vertices(
+            &tilemap,
+            TilemapProjection::Orthogonal,
+            0,
+            Some(TextureRegion::new(0.0, 0.0, 1.0, 1.0)),
+            [1.0, 1.0, 1.0],
+            &transform.into_matrix4(),
+        );
+
+        assert!(vertices.iter().all(|vertex| vertex.position[2] == -2.0));
+    }
+
+    #[test]
+    fn isometric_tile_vertices_are_placed_in_a_diamond() {
+        let tilemap = Tilemap::new(3, 2, 32, 16, &[]);
+
+        let vertices = tile_vertices(
+            &tilemap,
+            TilemapProjection::Isometric,
+            3,
+            Some(TextureRegion::new(0.0, 0.0, 1.0, 1.0)),
+            [1.0, 1.0, 1.0],
+            &Matrix4::identity(),
+        );
+
+        assert_eq!(vertices[0].position, [-16.0, 8.0, 0.0]);
+        assert_eq!(vertices[5].position, [16.0, 24.0, 0.0]);
+    }
+
+    #[test]
+    fn tile_vertices_carry_the_tilemap_tint() {
+        let mut tilemap = Tilemap::new(2, 1, 16, 16, &[]);
+        tilemap.tiles[1] = Tile::with_tags(&["damaged".into()]);
+        let mut tilemap_render = tilemap_render(true, &[]);
+        tilemap_render.tint = Color::rgb(1.0, 1.0, 0.0);
+        tilemap_render.tile_tint_function = Some(Box::new(|tile: &Tile| {
+            if tile.tags.contains("damaged") {
+                Color::rgb(1.0, 0.0, 1.0)
+            } else {
+                Color::WHITE
+            }
+        }));
+
+        for (tile_index, expected_color) in [(0, [1.0, 1.0, 0.0]), (1, [1.0, 0.0, 0.0])] {
+            let vertices = tile_vertices(
+                &tilemap,
+                TilemapProjection::Orthogonal,
+                tile_index,
+                Some(TextureRegion::new(0.0, 0.0, 1.0, 1.0)),
+                tile_color(&tilemap_render, &tilemap.tiles[tile_index]),
+                &Matrix4::identity(),
+            );
+            assert!(vertices.iter().all(|vertex| vertex.color == expected_color));
+        }
+    }
+
+    #[test]
+    fn flipped_tiles_swap_their_uv_corners() {
+        let flip = |horizontal, vertical, diagonal| TileFlip {
+            horizontal,
+            vertical,
+            diagonal,
+        };
+        // The texture coordinates of the top left, bottom left, top right and bottom right corners
+        let cases = vec![
+            (
+                flip(false, false, false),
+                [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]],
+            ),
+            (
+                flip(true, false, false),
+                [[1.0, 0.0], [1.0, 1.0], [0.0, 0.0], [0.0, 1.0]],
+            ),
+            (
+                flip(false, true, false),
+                [[0.0, 1.0], [0.0, 0.0], [1.0, 1.0], [1.0, 0.0]],
+            ),
+            (
+                flip(true, true, false),
+                [[1.0, 1.0], [1.0, 0.0], [0.0, 1.0], [0.0, 0.0]],
+            ),
+            (
+                flip(false, false, true),
+                [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]],
+            ),
+            (
+                flip(true, false, true),
+                [[0.0, 1.0], [1.0, 1.0], [0.0, 0.0], [1.0, 0.0]],
+            ),
+            (
+                flip(false, true, true),
+                [[1.0, 0.0], [0.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            ),
+            (
+                flip(true, true, true),
+                [[1.0, 1.0], [0.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+            ),
+        ];
+
+        for (flip, [top_left, bottom_left, top_right, bottom_right]) in cases {
+            let mut tilemap = Tilemap::new(1, 1, 16, 16, &[]);
+            tilemap.tiles[0].flip = flip;
+
+            let vertices = tile_vertices(
+                &tilemap,
+                TilemapProjection::Orthogonal,
+                0,
+                Some(TextureRegion::new(0.0, 0.0, 1.0, 1.0)),
+                [1.0, 1.0, 1.0],
+                &Matrix4::identity(),
+            );
+
+            let tex_coords: Vec<[f32; 2]> =
+                vertices.iter().map(|vertex| vertex.tex_coords).collect();
+            assert_eq!(
+                tex_coords,
+                vec![
+                    top_left,
+                    bottom_left,
+                    top_right,
+                    top_right,
+                    bottom_left,
+                    bottom_right
+                ],
+                "{:?}",
+                flip
+            );
+        }
+    }
+
+    #[test]
+    fn rotated_tile_stays_within_its_atlas_region() {
+        let mut tilemap = Tilemap::new(1, 1, 16, 16, &[]);
+        tilemap.tiles[0].flip = TileFlip::rotation(1);
+
+        let vertices = tile_vertices(
+            &tilemap,
+            TilemapProjection::Orthogonal,
+            0,
+            Some(TextureRegion::new(0.25, 0.0, 0.25, 0.5)),
+            [1.0, 1.0, 1.0],
+            &Matrix4::identity(),
+        );
+
+        assert_eq!(vertices[0].tex_coords, [0.25, 0.5]);
+        assert_eq!(vertices[2].tex_coords, [0.25, 0.0]);
+        assert_eq!(vertices[5].tex_coords, [0.5, 0.0]);
+    }
+
+    #[test]
+    fn tile_uvs_span_their_atlas_region() {
+        let tilemap = Tilemap::new(2, 1, 32, 32, &[]);
+        let texture_atlas = TextureAtlas {
+            texture_identifier: "texture".into(),
+            textures: vec![(
+                "grass".to_string(),
+                TextureRegion::new(32.0, 0.0, 32.0, 32.0),
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        let texture_region = normalized_tile_region(&texture_atlas, "grass", (128, 64), false);
+        assert_eq!(
+            texture_region,
+            Some(TextureRegion::new(0.25, 0.0, 0.25, 0.5))
+        );
+        assert_eq!(
+            normalized_tile_region(&texture_atlas, "water", (128, 64), false),
+            None
+        );
+
+        let vertices = tile_vertices(
+            &tilemap,
+            TilemapProjection::Orthogonal,
+            1,
+            texture_region,
+            [1.0, 1.0, 1.0],
+            &Matrix4::identity(),
+        );
+        assert_eq!(vertices[0].tex_coords, [0.25, 0.0]);
+        assert_eq!(vertices[5].tex_coords, [0.5, 0.5]);
+        assert_eq!(vertices[5].position, [64.0, 32.0, 0.0]);
+
+        assert_eq!(
+            normalized_tile_region(&texture_atlas, "grass", (128, 64), true),
+            Some(TextureRegion::new(
+                32.5 / 128.0,
+                0.5 / 64.0,
+                31.0 / 128.0,
+                31.0 / 64.0
+            ))
+        );
+    }
 }
@@ -20,6 +20,9 @@ pub struct BitmapFont {
     ignore_case: bool,
     /// The glyphs data
     glyphs: HashMap<char, BitmapGlyph>,
+    /// The character drawn in place of the characters missing from the font
+    #[serde(default)]
+    fallback_glyph: Option<char>,
 }
 
 impl BitmapFont {
@@ -35,6 +38,32 @@ impl BitmapFont {
         self.glyphs.get(&character)
     }
 
+    /// Returns the glyph to draw for a character, taking the case and the fallback glyph in account
+    ///
+    /// Returns `None` if the character should be skipped.
+    pub fn glyph_or_fallback(&self, character: char) -> Option<&BitmapGlyph> {
+        let glyph = if self.ignore_case {
+            self.glyph(character.to_ascii_uppercase())
+                .or_else(|| self.glyph(character.to_ascii_lowercase()))
+        } else {
+            self.glyph(character)
+        };
+
+        glyph.or_else(|| {
+            self.fallback_glyph
+                .and_then(|fallback| self.glyph(fallback))
+        })
+    }
+
+    pub fn fallback_glyph(&self) -> Option<char> {
+        self.fallback_glyph
+    }
+
+    /// Sets the character drawn in place of missing characters, which are skipped if `None`
+    pub fn set_fallback_glyph(&mut self, fallback_glyph: Option<char>) {
+        self.fallback_glyph = fallback_glyph;
+    }
+
     pub fn line_height(&self) -> u32 {
         self.line_height
     }
@@ -126,6 +155,43 @@ mod tests {
         assert_eq!(bitmap_font.glyphs.len(), 2);
         assert!(bitmap_font.glyphs.contains_key(&'A'));
         assert!(bitmap_font.glyphs.contains_key(&'D'));
+        assert_eq!(bitmap_font.fallback_glyph, None);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_glyph_falls_back() -> Result<(), GraphicsError> {
+        let json = r#"
+        {
+            "font_atlas_path": "font_atlas",
+            "font_atlas_region": { "x": 0, "y": 0, "width": 0, "height": 0 },
+            "line_height": 32,
+            "line_spacing": 4,
+            "ignore_case": true,
+            "letter_spacing": 2,
+            "glyphs": {
+                "A": { "region": { "x": 0, "y": 0, "width": 32, "height": 32 } },
+                "?": { "region": { "x": 32, "y": 0, "width": 16, "height": 32 } }
+            }
+        }
+        "#;
+
+        let mut bitmap_font = BitmapFont::from_str(json)?;
+        assert_eq!(
+            bitmap_font.glyph_or_fallback('a').unwrap().region().width,
+            32.0
+        );
+        assert!(bitmap_font.glyph_or_fallback('é').is_none());
+
+        bitmap_font.set_fallback_glyph(Some('?'));
+        assert_eq!(
+            bitmap_font.glyph_or_fallback('é').unwrap().region().width,
+            16.0
+        );
+        assert_eq!(
+            bitmap_font.glyph_or_fallback('a').unwrap().region().width,
+            32.0
+        );
         Ok(())
     }
 }
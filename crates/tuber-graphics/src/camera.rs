@@ -1,3 +1,23 @@
+use nalgebra::{Matrix4, Point3, Vector3};
+use tuber_common::time::DeltaTime;
+use tuber_common::transform::{IntoMatrix4, Transform2D};
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::query::accessors::{R, W};
+use tuber_ecs::EntityIndex;
+
+/// How a camera adapts to a window whose aspect ratio differs from its own
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum ScalingMode {
+    /// Stretches the camera bounds over the whole window
+    #[default]
+    Stretch,
+    /// Shows the whole camera bounds, leaving bars of the clear color on the sides
+    FitLetterbox,
+    /// Covers the whole window, cropping the camera bounds around their center
+    FillCrop,
+}
+
+#[derive(Debug, Copy, Clone)]
 pub struct OrthographicCamera {
     pub left: f32,
     pub right: f32,
@@ -5,6 +25,471 @@ pub struct OrthographicCamera {
     pub bottom: f32,
     pub near: f32,
     pub far: f32,
+    /// Magnification around the center of the camera, 2.0 shows half of the world
+    pub zoom: f32,
+    pub scaling_mode: ScalingMode,
+    /// The culling layers drawn by the camera, bit `n` stands for `CullingLayer(n)` and
+    /// `u32::MAX`, the default, draws every layer
+    pub layer_mask: u32,
+}
+
+impl Default for OrthographicCamera {
+    /// Returns an 800x600 camera whose top left corner is the origin, without zoom, stretched
+    /// over the window and drawing every layer
+    fn default() -> Self {
+        Self {
+            left: 0.0,
+            right: 800.0,
+            top: 0.0,
+            bottom: 600.0,
+            near: -100.0,
+            far: 100.0,
+            zoom: 1.0,
+            scaling_mode: ScalingMode::default(),
+            layer_mask: u32::MAX,
+        }
+    }
+}
+
+/// The layer an element is culled by, the cameras draw it only if it is in their layer mask
+///
+/// Elements without a culling layer are on layer 0.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CullingLayer(pub u8);
+
+impl CullingLayer {
+    /// Returns whether the layer is one of the layers of a camera layer mask
+    pub fn is_in(&self, layer_mask: u32) -> bool {
+        u32::from(self.0) < u32::BITS && layer_mask & (1 << self.0) != 0
+    }
+}
+
+impl OrthographicCamera {
+    /// Returns the camera with its bounds cropped to the aspect ratio of the window
+    /// if its scaling mode is `FillCrop`, unchanged otherwise
+    pub fn scaled_to_window(&self, window_size: (u32, u32)) -> OrthographicCamera {
+        if self.scaling_mode != ScalingMode::FillCrop || window_size.0 == 0 || window_size.1 == 0 {
+            return *self;
+        }
+
+        let window_aspect_ratio = window_size.0 as f32 / window_size.1 as f32;
+        let (center_x, center_y) = (
+            (self.left + self.right) / 2.0,
+            (self.top + self.bottom) / 2.0,
+        );
+        let (mut half_width, mut half_height) = (
+            (self.right - self.left) / 2.0,
+            (self.bottom - self.top) / 2.0,
+        );
+        if window_aspect_ratio > self.aspect_ratio() {
+            half_height = half_height.signum() * half_width.abs() / window_aspect_ratio;
+        } else {
+            half_width = half_width.signum() * half_height.abs() * window_aspect_ratio;
+        }
+
+        OrthographicCamera {
+            left: center_x - half_width,
+            right: center_x + half_width,
+            top: center_y - half_height,
+            bottom: center_y + half_height,
+            ..*self
+        }
+    }
+
+    /// Returns the (x, y, width, height) rectangle of the window the camera draws to, in pixels
+    ///
+    /// It is centered in the window and keeps the aspect ratio of the camera if its
+    /// scaling mode is `FitLetterbox`, it covers the whole window otherwise.
+    pub fn viewport(&self, window_size: (u32, u32)) -> (u32, u32, u32, u32) {
+        let (window_width, window_height) = window_size;
+        if self.scaling_mode != ScalingMode::FitLetterbox || window_width == 0 || window_height == 0
+        {
+            return (0, 0, window_width, window_height);
+        }
+
+        let aspect_ratio = self.aspect_ratio();
+        if window_width as f32 / window_height as f32 > aspect_ratio {
+            let width = ((window_height as f32 * aspect_ratio).round() as u32).min(window_width);
+            ((window_width - width) / 2, 0, width, window_height)
+        } else {
+            let height = ((window_width as f32 / aspect_ratio).round() as u32).min(window_height);
+            (0, (window_height - height) / 2, window_width, height)
+        }
+    }
+
+    fn aspect_ratio(&self) -> f32 {
+        ((self.right - self.left) / (self.bottom - self.top)).abs()
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_orthographic(
+            self.left,
+            self.right,
+            self.bottom,
+            self.top,
+            self.near,
+            self.far,
+        )
+    }
+
+    /// Returns the view matrix of the camera placed at the given transform
+    ///
+    /// The zoom is applied to the view rather than to the projection so elements
+    /// drawn without the view transform (such as the UI) aren't zoomed.
+    pub fn view_matrix(&self, transform: &Transform2D) -> Matrix4<f32> {
+        let center = Vector3::new(
+            (self.left + self.right) / 2.0,
+            (self.top + self.bottom) / 2.0,
+            0.0,
+        );
+
+        Matrix4::new_translation(&center)
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(self.zoom, self.zoom, 1.0))
+            * Matrix4::new_translation(&-center)
+            * transform.into_matrix4().try_inverse().unwrap()
+    }
+
+    /// Returns the view matrix with its translation rounded to whole pixels
+    ///
+    /// Moving the camera by fractions of a pixel makes textures sampled across texel
+    /// boundaries, showing seams between tiles. Snapping removes them, assuming one unit
+    /// per pixel, at the cost of less smooth slow camera movements.
+    pub fn snapped_view_matrix(&self, transform: &Transform2D) -> Matrix4<f32> {
+        let mut view_matrix = self.view_matrix(transform);
+        view_matrix[(0, 3)] = view_matrix[(0, 3)].round();
+        view_matrix[(1, 3)] = view_matrix[(1, 3)].round();
+        view_matrix
+    }
+
+    /// Returns the (left, top, right, bottom) world-space rectangle shown by the camera
+    /// placed at the given transform, enclosing it if the camera is rotated
+    pub fn visible_world_bounds(&self, transform: &Transform2D) -> (f32, f32, f32, f32) {
+        let inverse_view_matrix = self.view_matrix(transform).try_inverse().unwrap();
+        let corners = [
+            (self.left, self.top),
+            (self.right, self.top),
+            (self.right, self.bottom),
+            (self.left, self.bottom),
+        ];
+
+        corners.iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(left, top, right, bottom), &(x, y)| {
+                let corner = inverse_view_matrix.transform_point(&Point3::new(x, y, 0.0));
+                (
+                    left.min(corner.x),
+                    top.min(corner.y),
+                    right.max(corner.x),
+                    bottom.max(corner.y),
+                )
+            },
+        )
+    }
+
+    /// Returns the point of the camera bounds shown at a position of a window, in pixels,
+    /// which is where elements drawn without the view transform are placed
+    pub fn screen_to_camera(
+        &self,
+        window_size: (u32, u32),
+        screen_position: (f32, f32),
+    ) -> (f32, f32) {
+        let camera = self.scaled_to_window(window_size);
+        let (x, y, width, height) = camera.viewport(window_size);
+        (
+            camera.left
+                + (screen_position.0 - x as f32) / width as f32 * (camera.right - camera.left),
+            camera.top
+                + (screen_position.1 - y as f32) / height as f32 * (camera.bottom - camera.top),
+        )
+    }
+
+    /// Returns the world-space point shown at a position of a window, in pixels, by the camera
+    /// placed at the given transform
+    pub fn screen_to_world(
+        &self,
+        transform: &Transform2D,
+        window_size: (u32, u32),
+        screen_position: (f32, f32),
+    ) -> (f32, f32) {
+        let (x, y) = self.screen_to_camera(window_size, screen_position);
+        let world_position = self
+            .view_matrix(transform)
+            .try_inverse()
+            .unwrap()
+            .transform_point(&Point3::new(x, y, 0.0));
+        (world_position.x, world_position.y)
+    }
 }
 
 pub struct Active;
+
+/// Makes a camera entity follow the translation of a target entity
+pub struct CameraFollow {
+    pub target: EntityIndex,
+    /// Added to the translation of the target, (-400, -300) centers an 800x600 camera on it
+    pub offset: (f32, f32),
+    /// The fraction of the distance to the target covered every 60th of a second, 1.0 snaps
+    /// to it
+    pub lerp: f32,
+}
+
+/// Moves the cameras having a `CameraFollow` toward their target, by the same distance over
+/// the same time whatever the frame rate
+pub fn camera_follow_system(ecs: &mut Ecs) {
+    let DeltaTime(delta_time) = *ecs
+        .shared_resource::<DeltaTime>()
+        .expect("DeltaTime resource not found");
+    for camera_id in ecs.matching_entities::<(R<CameraFollow>, W<Transform2D>)>() {
+        let (_, (camera_follow,)) = ecs
+            .query_one_by_id::<(R<CameraFollow>,)>(camera_id)
+            .unwrap();
+        let target_translation =
+            match ecs.query_one_by_id::<(R<Transform2D>,)>(camera_follow.target) {
+                Some((_, (target_transform,))) => target_transform.translation,
+                None => continue,
+            };
+        let destination = (
+            target_translation.0 + camera_follow.offset.0,
+            target_translation.1 + camera_follow.offset.1,
+        );
+
+        let (_, (mut camera_transform,)) =
+            ecs.query_one_by_id::<(W<Transform2D>,)>(camera_id).unwrap();
+        // The remaining distance decays exponentially, by (1 - lerp) every 60th of a second
+        let fraction = 1.0 - (1.0 - camera_follow.lerp).powf(delta_time as f32 * 60.0);
+        let (x, y) = camera_transform.translation;
+        camera_transform.translation = (
+            x + (destination.0 - x) * fraction,
+            y + (destination.1 - y) * fraction,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(zoom: f32) -> OrthographicCamera {
+        OrthographicCamera {
+            zoom,
+            ..Default::default()
+        }
+    }
+
+    fn camera_with_scaling_mode(scaling_mode: ScalingMode) -> OrthographicCamera {
+        OrthographicCamera {
+            scaling_mode,
+            ..camera(1.0)
+        }
+    }
+
+    fn project(camera: &OrthographicCamera, transform: &Transform2D, x: f32, y: f32) -> (f32, f32) {
+        let projected = (camera.projection_matrix() * camera.view_matrix(transform))
+            .transform_point(&Point3::new(x, y, 0.0));
+        (projected.x, projected.y)
+    }
+
+    fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-5 && (actual.1 - expected.1).abs() < 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn follow_translation(ecs: &Ecs, id: EntityIndex) -> (f32, f32) {
+        let (_, (transform,)) = ecs.query_one_by_id::<(R<Transform2D>,)>(id).unwrap();
+        transform.translation
+    }
+
+    #[test]
+    fn following_cameras_approach_their_target() {
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(DeltaTime(1.0 / 60.0));
+        let target = ecs.insert((Transform2D {
+            translation: (500.0, 400.0),
+            ..Default::default()
+        },));
+        let camera_follow = |lerp| CameraFollow {
+            target,
+            offset: (-400.0, -300.0),
+            lerp,
+        };
+        let smooth_camera = ecs.insert((camera_follow(0.5), Transform2D::default()));
+        let snapping_camera = ecs.insert((camera_follow(1.0), Transform2D::default()));
+
+        camera_follow_system(&mut ecs);
+        assert_close(follow_translation(&ecs, smooth_camera), (50.0, 50.0));
+        assert_eq!(follow_translation(&ecs, snapping_camera), (100.0, 100.0));
+
+        camera_follow_system(&mut ecs);
+        assert_close(follow_translation(&ecs, smooth_camera), (75.0, 75.0));
+        for _ in 0..20 {
+            camera_follow_system(&mut ecs);
+        }
+        let (x, y) = follow_translation(&ecs, smooth_camera);
+        assert!((x - 100.0).abs() < 0.01 && (y - 100.0).abs() < 0.01);
+        assert_eq!(follow_translation(&ecs, snapping_camera), (100.0, 100.0));
+    }
+
+    #[test]
+    fn following_cameras_move_as_far_whatever_the_frame_rate() {
+        let follow_for_a_second = |frame_rate: u32| {
+            let mut ecs = Ecs::new();
+            ecs.insert_shared_resource(DeltaTime(1.0 / f64::from(frame_rate)));
+            let target = ecs.insert((Transform2D {
+                translation: (100.0, 0.0),
+                ..Default::default()
+            },));
+            let camera = ecs.insert((
+                CameraFollow {
+                    target,
+                    offset: (0.0, 0.0),
+                    lerp: 0.05,
+                },
+                Transform2D::default(),
+            ));
+            for _ in 0..frame_rate {
+                camera_follow_system(&mut ecs);
+            }
+            follow_translation(&ecs, camera)
+        };
+
+        let (x, _) = follow_for_a_second(60);
+        assert!((x - 100.0 * (1.0 - 0.95f32.powi(60))).abs() < 0.01);
+        for &frame_rate in &[30, 144, 240] {
+            let (other_x, _) = follow_for_a_second(frame_rate);
+            assert!((other_x - x).abs() < 0.01, "{} != {}", other_x, x);
+        }
+    }
+
+    #[test]
+    fn stretched_cameras_cover_the_window_with_unchanged_bounds() {
+        let camera = camera_with_scaling_mode(ScalingMode::Stretch);
+
+        assert_eq!(camera.viewport((1000, 500)), (0, 0, 1000, 500));
+        let scaled_camera = camera.scaled_to_window((1000, 500));
+        assert_eq!(
+            (scaled_camera.left, scaled_camera.top),
+            (camera.left, camera.top)
+        );
+        assert_eq!(
+            (scaled_camera.right, scaled_camera.bottom),
+            (camera.right, camera.bottom)
+        );
+    }
+
+    #[test]
+    fn letterboxed_cameras_keep_their_aspect_ratio() {
+        let camera = camera_with_scaling_mode(ScalingMode::FitLetterbox);
+
+        assert_eq!(camera.viewport((1000, 500)), (166, 0, 667, 500));
+        assert_eq!(camera.viewport((800, 1000)), (0, 200, 800, 600));
+        assert_eq!(camera.viewport((1600, 1200)), (0, 0, 1600, 1200));
+        let scaled_camera = camera.scaled_to_window((1000, 500));
+        assert_eq!(
+            (scaled_camera.right, scaled_camera.bottom),
+            (camera.right, camera.bottom)
+        );
+    }
+
+    #[test]
+    fn cropped_cameras_show_the_center_of_their_bounds() {
+        let camera = camera_with_scaling_mode(ScalingMode::FillCrop);
+
+        assert_eq!(camera.viewport((1000, 500)), (0, 0, 1000, 500));
+        let scaled_camera = camera.scaled_to_window((1000, 500));
+        assert_close((scaled_camera.left, scaled_camera.top), (0.0, 100.0));
+        assert_close((scaled_camera.right, scaled_camera.bottom), (800.0, 500.0));
+
+        let scaled_camera = camera.scaled_to_window((300, 600));
+        assert_close((scaled_camera.left, scaled_camera.top), (250.0, 0.0));
+        assert_close((scaled_camera.right, scaled_camera.bottom), (550.0, 600.0));
+    }
+
+    #[test]
+    fn snapped_view_matrix_has_whole_pixel_translation() {
+        let transform = Transform2D {
+            translation: (10.3, 20.7),
+            ..Default::default()
+        };
+
+        for &zoom in &[1.0, 1.5] {
+            let view_matrix = camera(zoom).snapped_view_matrix(&transform);
+            assert_eq!(view_matrix[(0, 3)].fract(), 0.0);
+            assert_eq!(view_matrix[(1, 3)].fract(), 0.0);
+        }
+        assert_eq!(camera(1.0).snapped_view_matrix(&transform)[(0, 3)], -10.0);
+    }
+
+    #[test]
+    fn visible_world_bounds_follow_the_camera() {
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+
+        let (left, top, right, bottom) = camera(1.0).visible_world_bounds(&transform);
+        assert_close((left, top), (100.0, 50.0));
+        assert_close((right, bottom), (900.0, 650.0));
+
+        let (left, top, right, bottom) = camera(2.0).visible_world_bounds(&transform);
+        assert_close((left, top), (300.0, 200.0));
+        assert_close((right, bottom), (700.0, 500.0));
+    }
+
+    #[test]
+    fn screen_positions_are_converted_to_world_space() {
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+
+        assert_close(
+            camera(1.0).screen_to_world(&transform, (1600, 1200), (200.0, 100.0)),
+            (200.0, 100.0),
+        );
+        assert_close(
+            camera(2.0).screen_to_world(&transform, (800, 600), (400.0, 300.0)),
+            (500.0, 350.0),
+        );
+        assert_close(
+            camera(2.0).screen_to_world(&transform, (800, 600), (0.0, 0.0)),
+            (300.0, 200.0),
+        );
+
+        let letterboxed_camera = camera_with_scaling_mode(ScalingMode::FitLetterbox);
+        assert_close(
+            letterboxed_camera.screen_to_camera((1000, 500), (166.0, 250.0)),
+            (0.0, 300.0),
+        );
+    }
+
+    #[test]
+    fn zoom_scales_projected_coordinates_around_center() {
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+
+        assert_close(project(&camera(1.0), &transform, 500.0, 350.0), (0.0, 0.0));
+        assert_close(project(&camera(2.0), &transform, 500.0, 350.0), (0.0, 0.0));
+
+        let (x, y) = project(&camera(1.0), &transform, 600.0, 500.0);
+        assert_close((x, y), (0.25, -0.5));
+        assert_close(
+            project(&camera(2.0), &transform, 600.0, 500.0),
+            (2.0 * x, 2.0 * y),
+        );
+    }
+
+    #[test]
+    fn default_cameras_draw_every_layer() {
+        let layer_mask = OrthographicCamera::default().layer_mask;
+
+        assert!(CullingLayer(0).is_in(layer_mask));
+        assert!(CullingLayer(7).is_in(layer_mask));
+        assert!(CullingLayer(31).is_in(layer_mask));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::WindowSize;
+
+/// A fixed resolution the scene is rendered at before being scaled into the window
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FixedResolution {
+    /// Upscaled by the greatest whole factor fitting the window, and shrunk into windows smaller
+    /// than the design resolution
+    Design { design_size: WindowSize },
+    /// Scaled by the greatest whole factor fitting the window with nearest-neighbor sampling,
+    /// never less than 1 so that the pixels stay square
+    PixelPerfect { internal_size: WindowSize },
+}
+
+impl FixedResolution {
+    /// Returns the size of the target the scene is rendered to
+    pub fn size(&self) -> WindowSize {
+        match *self {
+            FixedResolution::Design { design_size } => design_size,
+            FixedResolution::PixelPerfect { internal_size } => internal_size,
+        }
+    }
+
+    /// Returns the scale and the (x, y, width, height) rectangle of the window the scene is
+    /// drawn to, in pixels
+    pub fn destination(&self, window_size: WindowSize) -> (f32, (i32, i32, u32, u32)) {
+        match *self {
+            FixedResolution::Design { design_size } => {
+                let (scale, (x, y, width, height)) =
+                    design_resolution_destination(design_size, window_size);
+                (scale, (x as i32, y as i32, width, height))
+            }
+            FixedResolution::PixelPerfect { internal_size } => {
+                let (scale, destination) = pixel_perfect_destination(internal_size, window_size);
+                (scale as f32, destination)
+            }
+        }
+    }
+
+    /// Returns the point of the scene shown at a position of the window, in pixels
+    pub fn window_to_scene(
+        &self,
+        window_size: WindowSize,
+        window_position: (f32, f32),
+    ) -> (f32, f32) {
+        let (scale, (x, y, _, _)) = self.destination(window_size);
+        (
+            (window_position.0 - x as f32) / scale,
+            (window_position.1 - y as f32) / scale,
+        )
+    }
+}
+
+/// Returns the scale and the (x, y, width, height) rectangle of the window a design
+/// resolution is drawn to, in pixels
+///
+/// The scale is the greatest whole factor fitting the window and the rectangle is centered,
+/// windows smaller than the design resolution shrink it to fit instead.
+pub fn design_resolution_destination(
+    design_size: WindowSize,
+    window_size: WindowSize,
+) -> (f32, (u32, u32, u32, u32)) {
+    let horizontal_scale = window_size.0 as f32 / design_size.0 as f32;
+    let vertical_scale = window_size.1 as f32 / design_size.1 as f32;
+    let fitting_scale = horizontal_scale.min(vertical_scale);
+    let scale = if fitting_scale >= 1.0 {
+        fitting_scale.floor()
+    } else {
+        fitting_scale
+    };
+
+    let width = ((design_size.0 as f32 * scale).round() as u32).min(window_size.0);
+    let height = ((design_size.1 as f32 * scale).round() as u32).min(window_size.1);
+    (
+        scale,
+        (
+            (window_size.0 - width) / 2,
+            (window_size.1 - height) / 2,
+            width,
+            height,
+        ),
+    )
+}
+
+/// Returns the whole scale and the (x, y, width, height) rectangle of the window a pixel
+/// perfect internal resolution is drawn to, in pixels
+///
+/// The scale is the greatest whole factor fitting the window, and never less than 1: the
+/// rectangle is centered and goes past the edges of windows smaller than the internal
+/// resolution, which crop it.
+pub fn pixel_perfect_destination(
+    internal_size: WindowSize,
+    window_size: WindowSize,
+) -> (u32, (i32, i32, u32, u32)) {
+    let horizontal_scale = window_size.0 / internal_size.0;
+    let vertical_scale = window_size.1 / internal_size.1;
+    let scale = horizontal_scale.min(vertical_scale).max(1);
+
+    let width = internal_size.0 * scale;
+    let height = internal_size.1 * scale;
+    (
+        scale,
+        (
+            (window_size.0 as i32 - width as i32) / 2,
+            (window_size.1 as i32 - height as i32) / 2,
+            width,
+            height,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn design_resolution_is_upscaled_by_whole_factors() {
+        assert_eq!(
+            design_resolution_destination((320, 180), (1920, 1080)),
+            (6.0, (0, 0, 1920, 1080))
+        );
+        assert_eq!(
+            design_resolution_destination((320, 180), (1280, 1024)),
+            (4.0, (0, 152, 1280, 720))
+        );
+        assert_eq!(
+            design_resolution_destination((320, 180), (1000, 700)),
+            (3.0, (20, 80, 960, 540))
+        );
+    }
+
+    #[test]
+    fn design_resolution_shrinks_into_smaller_windows() {
+        assert_eq!(
+            design_resolution_destination((320, 180), (160, 120)),
+            (0.5, (0, 15, 160, 90))
+        );
+    }
+
+    #[test]
+    fn pixel_perfect_resolution_is_scaled_by_whole_factors() {
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (1920, 1080)),
+            (6, (0, 0, 1920, 1080))
+        );
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (1280, 1024)),
+            (4, (0, 152, 1280, 720))
+        );
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (1000, 700)),
+            (3, (20, 80, 960, 540))
+        );
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (320, 180)),
+            (1, (0, 0, 320, 180))
+        );
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (639, 400)),
+            (1, (159, 110, 320, 180))
+        );
+    }
+
+    #[test]
+    fn pixel_perfect_resolution_is_cropped_by_smaller_windows() {
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (300, 200)),
+            (1, (-10, 10, 320, 180))
+        );
+        assert_eq!(
+            pixel_perfect_destination((320, 180), (160, 120)),
+            (1, (-80, -30, 320, 180))
+        );
+    }
+
+    #[test]
+    fn window_positions_are_scaled_down_to_the_fixed_resolution() {
+        let design = FixedResolution::Design {
+            design_size: (320, 180),
+        };
+        let pixel_perfect = FixedResolution::PixelPerfect {
+            internal_size: (320, 180),
+        };
+
+        for fixed_resolution in &[design, pixel_perfect] {
+            assert_eq!(
+                fixed_resolution.window_to_scene((1280, 1024), (0.0, 152.0)),
+                (0.0, 0.0)
+            );
+            assert_eq!(
+                fixed_resolution.window_to_scene((1280, 1024), (640.0, 512.0)),
+                (160.0, 90.0)
+            );
+            assert_eq!(
+                fixed_resolution.window_to_scene((1000, 700), (980.0, 620.0)),
+                (320.0, 180.0)
+            );
+        }
+        assert_eq!(design.window_to_scene((160, 120), (0.0, 15.0)), (0.0, 0.0));
+        assert_eq!(
+            pixel_perfect.window_to_scene((160, 120), (0.0, 0.0)),
+            (80.0, 30.0)
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// The delay during which the events of a file are merged into one
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(100);
+
+/// Watches the files of the loaded textures
+pub(crate) struct TextureWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<DebouncedEvent>,
+    watched_textures: HashMap<PathBuf, String>,
+}
+
+impl TextureWatcher {
+    pub fn new() -> notify::Result<Self> {
+        let (sender, events) = channel();
+        Ok(Self {
+            watcher: watcher(sender, DEBOUNCE_DELAY)?,
+            events,
+            watched_textures: HashMap::new(),
+        })
+    }
+
+    pub fn watch(&mut self, texture: &str) {
+        let path = match Path::new(texture).canonicalize() {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+
+        if self.watched_textures.contains_key(&path) {
+            return;
+        }
+
+        if let Err(error) = self.watcher.watch(&path, RecursiveMode::NonRecursive) {
+            eprintln!("Cannot watch texture {}: {}", texture, error);
+            return;
+        }
+
+        self.watched_textures.insert(path, texture.to_owned());
+    }
+
+    /// Returns the textures whose file changed since the last call
+    pub fn modified_textures(&self) -> Vec<String> {
+        let mut modified_textures = vec![];
+        for event in self.events.try_iter() {
+            let path = match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => path,
+                _ => continue,
+            };
+
+            if let Some(texture) = self.watched_textures.get(&path) {
+                if !modified_textures.contains(texture) {
+                    modified_textures.push(texture.clone());
+                }
+            }
+        }
+
+        modified_textures
+    }
+}
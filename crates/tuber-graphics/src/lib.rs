@@ -1,20 +1,29 @@
 use crate::bitmap_font::BitmapFont;
-use crate::camera::{Active, OrthographicCamera};
+use crate::camera::{camera_follow_system, Active, CullingLayer, OrthographicCamera};
 use crate::low_level::*;
-use crate::shape::RectangleShape;
-use crate::sprite::{sprite_animation_step_system, AnimatedSprite, Sprite};
-use crate::texture::{TextureAtlas, TextureData, TextureMetadata, TextureRegion, TextureSource};
-use crate::tilemap::TilemapRender;
-use crate::ui::{Frame, Image, NoViewTransform, Text};
+use crate::mesh::Mesh2D;
+use crate::shape::{CircleShape, RectangleShape};
+use crate::sprite::{
+    animation_switch_system, sprite_animation_step_system, AnimatedSprite, Sprite,
+};
+use crate::texture::{
+    TextureAddressMode, TextureAtlas, TextureData, TextureFilter, TextureMetadata, TextureRegion,
+    TextureSource,
+};
+use crate::texture_loader::TextureLoader;
+use crate::tilemap::{tile_animation_step_system, TilemapRender};
+use crate::ui::{ClipRect, Frame, Image, NinePatch, NoViewTransform, Text};
 use image::ImageError;
+use nalgebra::Point3;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::BufReader;
 use tuber_common::tilemap::Tilemap;
-use tuber_common::transform::Transform2D;
+use tuber_common::transform::{IntoMatrix4, Transform2D};
 use tuber_ecs::ecs::Ecs;
 use tuber_ecs::query::accessors::{R, W};
+use tuber_ecs::query::Query;
 use tuber_ecs::system::SystemBundle;
 use tuber_ecs::EntityIndex;
 
@@ -25,18 +34,145 @@ pub enum GraphicsError {
     ImageDecodeError(ImageError),
     SerdeError(serde_json::error::Error),
     BitmapFontFileReadError(std::io::Error),
+    /// A texture atlas has no region of that name, given as (atlas identifier, region name)
+    AtlasRegionNotFound(String, String),
+    /// A background texture loader panicked while decoding a texture file, given with the panic message
+    TextureLoaderPanic(String),
+    #[cfg(feature = "hot-reload")]
+    TextureWatcherError(notify::Error),
+}
+
+impl std::fmt::Display for GraphicsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphicsError::TextureFileOpenError(error) => {
+                write!(f, "couldn't open texture file: {}", error)
+            }
+            GraphicsError::AtlasDescriptionFileOpenError(error) => {
+                write!(f, "couldn't open atlas description file: {}", error)
+            }
+            GraphicsError::ImageDecodeError(error) => write!(f, "couldn't decode image: {}", error),
+            GraphicsError::SerdeError(error) => write!(f, "couldn't parse description: {}", error),
+            GraphicsError::BitmapFontFileReadError(error) => {
+                write!(f, "couldn't read bitmap font file: {}", error)
+            }
+            GraphicsError::AtlasRegionNotFound(texture_atlas, region_name) => write!(
+                f,
+                "couldn't find region {} in texture atlas {}",
+                region_name, texture_atlas
+            ),
+            GraphicsError::TextureLoaderPanic(message) => {
+                write!(f, "texture loader panicked: {}", message)
+            }
+            #[cfg(feature = "hot-reload")]
+            GraphicsError::TextureWatcherError(error) => {
+                write!(f, "couldn't watch texture files: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphicsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GraphicsError::TextureFileOpenError(error)
+            | GraphicsError::AtlasDescriptionFileOpenError(error)
+            | GraphicsError::BitmapFontFileReadError(error) => Some(error),
+            GraphicsError::ImageDecodeError(error) => Some(error),
+            GraphicsError::SerdeError(error) => Some(error),
+            GraphicsError::AtlasRegionNotFound(..) | GraphicsError::TextureLoaderPanic(_) => None,
+            #[cfg(feature = "hot-reload")]
+            GraphicsError::TextureWatcherError(error) => Some(error),
+        }
+    }
 }
 
 pub mod bitmap_font;
 pub mod camera;
+pub mod design_resolution;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
 pub mod low_level;
+pub mod mesh;
 pub mod shape;
 pub mod sprite;
 pub mod texture;
+mod texture_loader;
 pub mod tilemap;
 pub mod ui;
 
-pub type Color = (f32, f32, f32);
+/// An RGBA color in sRGB space, as picked in an image editor
+///
+/// Colors are converted to linear space by the renderers, which render to an sRGB surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    /// The opacity, from 0 (transparent) to 1 (opaque)
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+
+    /// Creates an opaque color
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl From<(f32, f32, f32)> for Color {
+    fn from((r, g, b): (f32, f32, f32)) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<(f32, f32, f32, f32)> for Color {
+    fn from((r, g, b, a): (f32, f32, f32, f32)) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+/// The order in which an element is drawn among the elements of the same kind,
+/// lower orders are drawn first
+///
+/// Elements without a draw order are at 0, elements with the same order are drawn by entity id.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DrawOrder(pub i32);
+
+/// Hides an element without deleting it, elements without it are visible
+pub struct Hidden;
+
+/// A category of elements drawn in a fixed order, from `Background` to `UI`
+///
+/// Elements without a render layer are in `UI` if they have a `NoViewTransform`, in `World` otherwise.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderLayer {
+    Background,
+    World,
+    Foreground,
+    UI,
+}
+
+impl RenderLayer {
+    /// Every render layer, in drawing order
+    pub const ALL: [RenderLayer; 4] = [
+        RenderLayer::Background,
+        RenderLayer::World,
+        RenderLayer::Foreground,
+        RenderLayer::UI,
+    ];
+}
+
+/// The identifier drawn in place of textures that failed to load or are still loading, rendered
+/// with the default texture
+const MISSING_TEXTURE_IDENTIFIER: &str = "missing_texture";
 
 pub type WindowSize = (u32, u32);
 pub struct Window<'a>(pub Box<&'a dyn HasRawWindowHandle>);
@@ -52,6 +188,20 @@ pub struct Graphics {
     texture_atlases: HashMap<String, TextureAtlas>,
     fonts: HashMap<String, BitmapFont>,
     bounding_box_rendering: bool,
+    texture_filter: TextureFilter,
+    texture_address_modes: HashMap<String, TextureAddressMode>,
+    texture_loader: TextureLoader,
+    /// The textures whose file couldn't be loaded, which aren't requested again until reloaded
+    failed_textures: HashSet<String>,
+    missing_camera_warned: bool,
+    /// The active camera and its transform, as of the last render
+    camera: Option<(OrthographicCamera, Transform2D)>,
+    window_size: WindowSize,
+    /// The stack of clip rectangles, each one intersected with the previous ones
+    clip_rects: Vec<ClipRect>,
+    reported_errors: HashSet<String>,
+    #[cfg(feature = "hot-reload")]
+    texture_watcher: Option<hot_reload::TextureWatcher>,
 }
 
 impl Graphics {
@@ -62,14 +212,98 @@ impl Graphics {
             texture_atlases: HashMap::new(),
             fonts: Default::default(),
             bounding_box_rendering: false,
+            texture_filter: TextureFilter::default(),
+            texture_address_modes: HashMap::new(),
+            texture_loader: TextureLoader::new(),
+            failed_textures: HashSet::new(),
+            missing_camera_warned: false,
+            camera: None,
+            window_size: (0, 0),
+            clip_rects: vec![],
+            reported_errors: HashSet::new(),
+            #[cfg(feature = "hot-reload")]
+            texture_watcher: None,
         }
     }
     pub fn initialize(&mut self, window: Window, window_size: (u32, u32)) {
+        self.window_size = window_size;
         self.graphics_impl.initialize(window, window_size);
     }
 
     fn render(&mut self) {
         self.graphics_impl.render();
+        self.clip_rects.clear();
+    }
+
+    /// Returns the (left, top, right, bottom) world-space rectangle shown by the active camera
+    /// during the last render, `None` if there was no active camera
+    pub fn visible_world_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.camera
+            .as_ref()
+            .map(|(camera, transform)| camera.visible_world_bounds(transform))
+    }
+
+    /// Returns the size of the target the scene is rendered to and the point of this target
+    /// shown at a position of the window, `None` if the window has no size yet
+    ///
+    /// The target is the fixed resolution of the renderer if it has one, the window otherwise.
+    fn scene_position(&self, screen_position: (f32, f32)) -> Option<(WindowSize, (f32, f32))> {
+        if self.window_size.0 == 0 || self.window_size.1 == 0 {
+            return None;
+        }
+
+        Some(match self.graphics_impl.fixed_resolution() {
+            Some(fixed_resolution) => (
+                fixed_resolution.size(),
+                fixed_resolution.window_to_scene(self.window_size, screen_position),
+            ),
+            None => (self.window_size, screen_position),
+        })
+    }
+
+    /// Returns the world-space point shown at a position of the window, in pixels, by the active
+    /// camera during the last render, `None` if there was no active camera
+    pub fn screen_to_world(&self, screen_position: (f32, f32)) -> Option<(f32, f32)> {
+        let (scene_size, scene_position) = self.scene_position(screen_position)?;
+        self.camera.as_ref().map(|(camera, transform)| {
+            camera.screen_to_world(transform, scene_size, scene_position)
+        })
+    }
+
+    /// Returns the topmost entity whose rectangle, circle, sprite or animated sprite quad contains
+    /// a position of the window, in pixels, as drawn by the active camera during the last render
+    ///
+    /// The quads of the last drawn `RenderLayer` win, then the ones of greatest z in that layer,
+    /// then the ones drawn last.
+    pub fn pick_entity(&self, ecs: &Ecs, screen_position: (f32, f32)) -> Option<EntityIndex> {
+        let (scene_size, scene_position) = self.scene_position(screen_position)?;
+        let (camera, camera_transform) = self.camera.as_ref()?;
+        let world_position = camera.screen_to_world(camera_transform, scene_size, scene_position);
+        let camera_position = camera.screen_to_camera(scene_size, scene_position);
+
+        let mut picked_entity = None;
+        for &layer in &RenderLayer::ALL {
+            // A layer is drawn over the previous ones, whatever their z
+            let mut picked_z = f32::MIN;
+            for (id, width, height, transform) in drawn_quads(ecs, layer, camera.layer_mask) {
+                let position = if applies_view_transform(ecs, id) {
+                    world_position
+                } else {
+                    camera_position
+                };
+                if transform.z >= picked_z && quad_contains(width, height, &transform, position) {
+                    picked_entity = Some(id);
+                    picked_z = transform.z;
+                }
+            }
+        }
+
+        picked_entity
+    }
+
+    /// Returns the draw calls, quads and vertices submitted during the last rendered frame
+    pub fn last_frame_stats(&self) -> RenderStats {
+        self.graphics_impl.last_frame_stats()
     }
 
     pub fn prepare_rectangle(
@@ -84,13 +318,95 @@ impl Graphics {
                 height: rectangle.height,
                 color: rectangle.color,
                 texture: None,
+                corner_radius: rectangle.corner_radius,
+                outline_thickness: 0.0,
+                blend_mode: BlendMode::Alpha,
             },
-            transform,
+            &anchored_transform(
+                transform,
+                rectangle.anchor,
+                rectangle.width,
+                rectangle.height,
+            ),
+            apply_view_transform,
+            self.bounding_box_rendering,
+        );
+    }
+
+    pub fn prepare_circle(
+        &mut self,
+        circle: &CircleShape,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) {
+        let diameter = circle.radius * 2.0;
+        self.graphics_impl.prepare_quad(
+            &QuadDescription {
+                width: diameter,
+                height: diameter,
+                color: circle.color,
+                texture: None,
+                corner_radius: circle.radius,
+                outline_thickness: circle.thickness.unwrap_or(0.0),
+                blend_mode: BlendMode::Alpha,
+            },
+            &anchored_transform(transform, (0.5, 0.5), diameter, diameter),
             apply_view_transform,
             self.bounding_box_rendering,
         );
     }
 
+    /// Restricts the following draws of the frame to a rectangle in screen coordinates,
+    /// within the current clip rectangle if there is one
+    pub fn push_clip_rect(&mut self, clip_rect: ClipRect) {
+        let clip_rect = match self.clip_rects.last() {
+            Some(current_clip_rect) => current_clip_rect.intersection(&clip_rect),
+            None => clip_rect,
+        };
+
+        self.clip_rects.push(clip_rect);
+        self.graphics_impl.set_clip_rect(Some(clip_rect));
+    }
+
+    /// Restores the clip rectangle in use before the last `push_clip_rect`
+    pub fn pop_clip_rect(&mut self) {
+        self.clip_rects.pop();
+        self.graphics_impl
+            .set_clip_rect(self.clip_rects.last().copied());
+    }
+
+    /// Draws a line in world coordinates during the current frame
+    pub fn draw_line(&mut self, start: (f32, f32), end: (f32, f32), color: Color) {
+        self.graphics_impl.prepare_line(start, end, color);
+    }
+
+    /// Draws lines joining the given points in world coordinates during the current frame
+    pub fn draw_polyline(&mut self, points: &[(f32, f32)], color: Color) {
+        for segment in points.windows(2) {
+            self.draw_line(segment[0], segment[1], color);
+        }
+    }
+
+    /// Draws a world-space grid over the given (left, top, right, bottom) bounds
+    /// during the current frame, with lines snapped to multiples of `cell_size`
+    ///
+    /// Only the part of the grid visible by the camera is drawn.
+    pub fn draw_grid(&mut self, cell_size: f32, color: Color, bounds: (f32, f32, f32, f32)) {
+        let bounds = match self.visible_world_bounds() {
+            Some((left, top, right, bottom)) => (
+                bounds.0.max(left),
+                bounds.1.max(top),
+                bounds.2.min(right),
+                bounds.3.min(bottom),
+            ),
+            None => bounds,
+        };
+
+        for (start, end) in grid_lines(cell_size, bounds) {
+            self.draw_line(start, end, color);
+        }
+    }
+
     fn load_texture_atlas(&mut self, texture_atlas_path: &str) -> Result<(), GraphicsError> {
         let atlas_description_file = File::open(texture_atlas_path)
             .map_err(|e| GraphicsError::AtlasDescriptionFileOpenError(e))?;
@@ -98,32 +414,154 @@ impl Graphics {
         let texture_atlas: TextureAtlas =
             serde_json::from_reader(reader).map_err(|e| GraphicsError::SerdeError(e))?;
 
-        if !self
-            .graphics_impl
-            .is_texture_in_memory(&texture_atlas.texture_identifier)
+        self.register_texture_atlas(texture_atlas_path, texture_atlas);
+        Ok(())
+    }
+
+    /// Adds a texture atlas built in code, replacing any atlas with the same identifier
+    ///
+    /// Elements refer to it by its identifier, as they would to an atlas description file.
+    pub fn register_texture_atlas(&mut self, identifier: &str, texture_atlas: TextureAtlas) {
+        self.request_texture(&texture_atlas.texture_identifier);
+        self.texture_atlases
+            .insert(identifier.to_owned(), texture_atlas);
+    }
+
+    /// Drops a cached texture atlas, along with its texture if no other atlas uses it
+    ///
+    /// The atlas is loaded from disk again the next time it is used.
+    pub fn unload_atlas(&mut self, texture_atlas_path: &str) {
+        let texture_atlas = match self.texture_atlases.remove(texture_atlas_path) {
+            Some(texture_atlas) => texture_atlas,
+            None => return,
+        };
+
+        let texture_identifier = texture_atlas.texture_identifier;
+        if self
+            .texture_atlases
+            .values()
+            .all(|texture_atlas| texture_atlas.texture_identifier != texture_identifier)
         {
-            self.load_texture(&texture_atlas.texture_identifier);
+            self.texture_metadata.remove(&texture_identifier);
+            self.graphics_impl.unload_texture(&texture_identifier);
         }
+    }
 
-        self.texture_atlases
-            .insert(texture_atlas_path.to_owned(), texture_atlas);
+    /// Reloads a texture from its file, replacing the one in memory under the same identifier
+    pub fn reload_texture(&mut self, texture: &str) {
+        self.failed_textures.remove(texture);
+        self.load_texture(texture);
+    }
+
+    /// Watches the files of the loaded textures and reloads them when they change
+    #[cfg(feature = "hot-reload")]
+    pub fn enable_texture_hot_reload(&mut self) -> Result<(), GraphicsError> {
+        let mut texture_watcher =
+            hot_reload::TextureWatcher::new().map_err(GraphicsError::TextureWatcherError)?;
+        for texture in self.texture_metadata.keys() {
+            texture_watcher.watch(texture);
+        }
+
+        self.texture_watcher = Some(texture_watcher);
+        Ok(())
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn reload_modified_textures(&mut self) {
+        let modified_textures = match &self.texture_watcher {
+            Some(texture_watcher) => texture_watcher.modified_textures(),
+            None => return,
+        };
+
+        for texture in modified_textures {
+            self.reload_texture(&texture);
+        }
+    }
+
+    /// Starts loading textures in the background so that they are ready when first drawn
+    pub fn preload_textures(&mut self, textures: &[&str]) {
+        for texture in textures {
+            self.request_texture(texture);
+        }
+    }
+
+    /// Starts loading a texture in the background if it isn't in memory yet
+    fn request_texture(&mut self, texture: &str) {
+        if self.graphics_impl.is_texture_in_memory(texture)
+            || self.failed_textures.contains(texture)
+        {
+            return;
+        }
+
+        self.texture_loader.load(texture);
+    }
+
+    /// Uploads the textures decoded in the background since the last call
+    fn upload_loaded_textures(&mut self) {
+        for (texture, texture_data) in self.texture_loader.loaded_textures() {
+            match texture_data {
+                Ok(texture_data) => self.upload_texture(texture_data),
+                Err(error) => {
+                    self.failed_textures.insert(texture);
+                    self.report_error(error);
+                }
+            }
+        }
+    }
+
+    /// Loads a texture from the bytes of an encoded image, such as one embedded with `include_bytes!`
+    ///
+    /// Elements refer to the texture by its identifier, as they would to a texture file.
+    pub fn load_texture_from_bytes(
+        &mut self,
+        identifier: &str,
+        bytes: &[u8],
+    ) -> Result<(), GraphicsError> {
+        let texture_data = TextureData::from_bytes(identifier, bytes)?;
+        self.upload_texture(texture_data);
         Ok(())
     }
 
     fn load_texture(&mut self, texture: &str) {
         if let Ok(texture_data) = TextureData::from_file(&texture) {
-            self.texture_metadata.insert(
-                texture.to_owned(),
-                TextureMetadata {
-                    width: texture_data.size.0,
-                    height: texture_data.size.1,
-                },
-            );
-            self.graphics_impl.load_texture(texture_data);
+            self.upload_texture(texture_data);
+        }
+    }
+
+    fn upload_texture(&mut self, texture_data: TextureData) {
+        let texture = texture_data.identifier.clone();
+        #[cfg(feature = "hot-reload")]
+        if let Some(texture_watcher) = &mut self.texture_watcher {
+            texture_watcher.watch(&texture);
         }
+
+        self.texture_metadata.insert(
+            texture.clone(),
+            TextureMetadata {
+                width: texture_data.size.0,
+                height: texture_data.size.1,
+            },
+        );
+        let address_mode = self
+            .texture_address_modes
+            .get(&texture)
+            .cloned()
+            .unwrap_or_default();
+        self.graphics_impl.load_texture(
+            texture_data
+                .with_filter(self.texture_filter)
+                .with_address_mode(address_mode),
+        );
+    }
+
+    /// Returns the size of a loaded texture, `None` if it hasn't been loaded yet
+    pub fn texture_size(&self, texture_identifier: &str) -> Option<(u32, u32)> {
+        self.texture_metadata
+            .get(texture_identifier)
+            .map(|metadata| (metadata.width, metadata.height))
     }
 
-    fn prepare_animated_sprite(
+    pub fn prepare_animated_sprite(
         &mut self,
         animated_sprite: &AnimatedSprite,
         transform: &Transform2D,
@@ -135,21 +573,34 @@ impl Graphics {
             }
         }
 
+        let animation_state = &animated_sprite.animation_state;
+        // An animation without keyframes draws nothing
+        let current_keyframe = match animation_state
+            .keyframes
+            .get(animation_state.current_keyframe)
+        {
+            Some(&keyframe) => keyframe,
+            None => return Ok(()),
+        };
+
         let texture = animated_sprite
             .texture
             .texture_identifier(&self.texture_atlases);
-        if !self.graphics_impl.is_texture_in_memory(&texture) {
-            self.load_texture(&texture);
-        }
+        self.request_texture(&texture);
 
-        let (texture_width, texture_height) = match self.texture_metadata.get(&texture) {
-            Some(metadata) => (metadata.width, metadata.height),
-            None => (32, 32),
+        let (texture_width, texture_height) = match self.texture_size(&texture) {
+            Some(size) => size,
+            None => {
+                self.prepare_missing_texture(
+                    animated_sprite.width,
+                    animated_sprite.height,
+                    transform,
+                    apply_view_transform,
+                );
+                return Ok(());
+            }
         };
 
-        let current_keyframe = animated_sprite.animation_state.keyframes
-            [animated_sprite.animation_state.current_keyframe];
-
         let mut normalized_texture_region = TextureRegion::new(
             current_keyframe.x,
             current_keyframe.y,
@@ -166,11 +617,15 @@ impl Graphics {
             &QuadDescription {
                 width: animated_sprite.width,
                 height: animated_sprite.height,
-                color: (1.0, 1.0, 1.0),
+                color: Color::WHITE,
                 texture: Some(TextureDescription {
                     identifier: texture,
                     texture_region: normalized_texture_region,
+                    address_mode: None,
                 }),
+                corner_radius: 0.0,
+                outline_thickness: 0.0,
+                blend_mode: BlendMode::Alpha,
             },
             transform,
             apply_view_transform,
@@ -193,82 +648,322 @@ impl Graphics {
         }
 
         let texture = sprite.texture.texture_identifier(&self.texture_atlases);
-        if !self.graphics_impl.is_texture_in_memory(&texture) {
-            self.load_texture(&texture);
-        }
+        self.request_texture(&texture);
 
-        let (texture_width, texture_height) = match self.texture_metadata.get(&texture) {
-            Some(metadata) => (metadata.width, metadata.height),
-            None => (32, 32),
+        let anchored_transform =
+            anchored_transform(transform, sprite.anchor, sprite.width, sprite.height);
+        let (texture_width, texture_height) = match self.texture_size(&texture) {
+            Some(size) => size,
+            None => {
+                self.prepare_missing_texture(
+                    sprite.width,
+                    sprite.height,
+                    &anchored_transform,
+                    apply_view_transform,
+                );
+                return Ok(());
+            }
         };
+        let texture_region = sprite.texture.normalized_texture_region(
+            texture_width,
+            texture_height,
+            &self.texture_atlases,
+        )?;
         self.graphics_impl.prepare_quad(
             &QuadDescription {
                 width: sprite.width,
                 height: sprite.height,
-                color: (1.0, 1.0, 1.0),
+                color: Color::WHITE,
                 texture: Some(TextureDescription {
                     identifier: texture,
-                    texture_region: sprite.texture.normalized_texture_region(
-                        texture_width,
-                        texture_height,
-                        &self.texture_atlases,
-                    ),
+                    texture_region,
+                    address_mode: sprite.address_mode,
                 }),
+                corner_radius: 0.0,
+                outline_thickness: 0.0,
+                blend_mode: sprite.blend_mode,
             },
-            transform,
+            &anchored_transform,
+            apply_view_transform,
+            self.bounding_box_rendering,
+        );
+        Ok(())
+    }
+
+    /// Prepares the render of a region of a texture, in pixels, stretched to the given size
+    ///
+    /// The missing texture is drawn instead until the texture is loaded, or if it failed to load.
+    pub fn draw_texture(
+        &mut self,
+        identifier: &str,
+        source: TextureRegion,
+        destination_transform: &Transform2D,
+        destination_size: (f32, f32),
+        apply_view_transform: bool,
+    ) {
+        self.request_texture(identifier);
+        let (texture_width, texture_height) = match self.texture_size(identifier) {
+            Some(size) => size,
+            None => {
+                self.prepare_missing_texture(
+                    destination_size.0,
+                    destination_size.1,
+                    destination_transform,
+                    apply_view_transform,
+                );
+                return;
+            }
+        };
+        self.graphics_impl.prepare_quad(
+            &QuadDescription {
+                width: destination_size.0,
+                height: destination_size.1,
+                color: Color::WHITE,
+                texture: Some(TextureDescription {
+                    identifier: identifier.into(),
+                    texture_region: source.normalize(texture_width, texture_height),
+                    address_mode: None,
+                }),
+                corner_radius: 0.0,
+                outline_thickness: 0.0,
+                blend_mode: BlendMode::Alpha,
+            },
+            destination_transform,
             apply_view_transform,
             self.bounding_box_rendering,
         );
+    }
+
+    /// Prepares the render of a mesh, skipped until its texture is loaded
+    pub fn prepare_mesh(
+        &mut self,
+        mesh_description: &MeshDescription,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) {
+        self.request_texture(&mesh_description.texture.identifier);
+        if self
+            .texture_size(&mesh_description.texture.identifier)
+            .is_none()
+        {
+            return;
+        }
+
+        self.graphics_impl
+            .prepare_mesh(mesh_description, transform, apply_view_transform);
+    }
+
+    pub fn prepare_mesh_2d(
+        &mut self,
+        mesh: &Mesh2D,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) -> Result<(), GraphicsError> {
+        if let TextureSource::TextureAtlas(texture_atlas_identifier, _) = &mesh.texture {
+            if !self.texture_atlases.contains_key(texture_atlas_identifier) {
+                self.load_texture_atlas(texture_atlas_identifier)?;
+            }
+        }
+
+        let texture = mesh.texture.texture_identifier(&self.texture_atlases);
+        self.request_texture(&texture);
+        let (texture_width, texture_height) = match self.texture_size(&texture) {
+            Some(size) => size,
+            None => {
+                self.prepare_missing_mesh_texture(mesh, transform, apply_view_transform);
+                return Ok(());
+            }
+        };
+        let texture_region = mesh.texture.normalized_texture_region(
+            texture_width,
+            texture_height,
+            &self.texture_atlases,
+        )?;
+
+        self.prepare_mesh(
+            &MeshDescription {
+                vertices: mesh.vertices.clone(),
+                texture: TextureDescription {
+                    identifier: texture,
+                    texture_region,
+                    address_mode: None,
+                },
+            },
+            transform,
+            apply_view_transform,
+        );
+        Ok(())
+    }
+
+    pub fn prepare_nine_patch(
+        &mut self,
+        nine_patch: &NinePatch,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) -> Result<(), GraphicsError> {
+        if let TextureSource::TextureAtlas(texture_atlas_identifier, _) = &nine_patch.texture {
+            if !self.texture_atlases.contains_key(texture_atlas_identifier) {
+                self.load_texture_atlas(texture_atlas_identifier)?;
+            }
+        }
+
+        let texture = nine_patch.texture.texture_identifier(&self.texture_atlases);
+        self.request_texture(&texture);
+
+        let (texture_width, texture_height) = match self.texture_size(&texture) {
+            Some(size) => size,
+            None => {
+                self.prepare_missing_texture(
+                    nine_patch.width,
+                    nine_patch.height,
+                    transform,
+                    apply_view_transform,
+                );
+                return Ok(());
+            }
+        };
+        let source_region = nine_patch.texture.normalized_texture_region(
+            texture_width,
+            texture_height,
+            &self.texture_atlases,
+        )?;
+
+        for slice in nine_patch.slices(source_region, texture_width, texture_height) {
+            let slice_transform = offset_transform(transform, slice.offset);
+            self.graphics_impl.prepare_quad(
+                &QuadDescription {
+                    width: slice.width,
+                    height: slice.height,
+                    color: Color::WHITE,
+                    texture: Some(TextureDescription {
+                        identifier: texture.clone(),
+                        texture_region: slice.texture_region,
+                        address_mode: None,
+                    }),
+                    corner_radius: 0.0,
+                    outline_thickness: 0.0,
+                    blend_mode: BlendMode::Alpha,
+                },
+                &slice_transform,
+                apply_view_transform,
+                self.bounding_box_rendering,
+            );
+        }
+
         Ok(())
     }
 
+    /// Draws a quad with the default texture in place of an element whose texture failed to load
+    /// or is still loading
+    fn prepare_missing_texture(
+        &mut self,
+        width: f32,
+        height: f32,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) {
+        self.graphics_impl.prepare_quad(
+            &QuadDescription {
+                width,
+                height,
+                color: Color::WHITE,
+                texture: Some(TextureDescription {
+                    identifier: MISSING_TEXTURE_IDENTIFIER.into(),
+                    texture_region: TextureRegion::new(0.0, 0.0, 1.0, 1.0),
+                    address_mode: None,
+                }),
+                corner_radius: 0.0,
+                outline_thickness: 0.0,
+                blend_mode: BlendMode::Alpha,
+            },
+            transform,
+            apply_view_transform,
+            self.bounding_box_rendering,
+        );
+    }
+
+    /// Draws the missing texture over the bounds of a mesh whose texture failed to load
+    fn prepare_missing_mesh_texture(
+        &mut self,
+        mesh: &Mesh2D,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    ) {
+        if let Some((left, top, right, bottom)) = mesh.bounds() {
+            self.prepare_missing_texture(
+                right - left,
+                bottom - top,
+                &offset_transform(transform, (left, top)),
+                apply_view_transform,
+            );
+        }
+    }
+
+    /// Logs an error raised while rendering, only once so that it isn't repeated every frame
+    fn report_error(&mut self, error: GraphicsError) {
+        let message = error.to_string();
+        if !self.reported_errors.contains(&message) {
+            eprintln!("Rendering error: {}", message);
+            self.reported_errors.insert(message);
+        }
+    }
+
     fn prepare_tilemap(
         &mut self,
         tilemap: &Tilemap,
         tilemap_render: &TilemapRender,
         transform: &Transform2D,
-    ) {
+    ) -> Result<(), GraphicsError> {
         if !self
             .texture_atlases
             .contains_key(&tilemap_render.texture_atlas_identifier)
         {
-            self.load_texture_atlas(&tilemap_render.texture_atlas_identifier)
-                .unwrap();
+            self.load_texture_atlas(&tilemap_render.texture_atlas_identifier)?;
         }
 
-        self.graphics_impl.prepare_tilemap(
-            tilemap,
-            tilemap_render,
-            self.texture_atlases
-                .get(&tilemap_render.texture_atlas_identifier)
-                .unwrap(),
-            transform,
-        );
+        let texture_atlas = &self.texture_atlases[&tilemap_render.texture_atlas_identifier];
+        if self
+            .texture_size(texture_atlas.texture_identifier())
+            .is_none()
+        {
+            return Ok(());
+        }
+
+        self.graphics_impl
+            .prepare_tilemap(tilemap, tilemap_render, texture_atlas, transform);
+        Ok(())
     }
 
+    /// Prepares the glyphs of a text, laid out from the translation of the transform
+    ///
+    /// If the font region is missing from its atlas, the glyphs are drawn with the missing texture
+    /// and an error is returned.
     pub fn prepare_text(
         &mut self,
         text: &str,
         font_path: &str,
+        color: Color,
         transform: &Transform2D,
         apply_view_transform: bool,
-    ) {
+    ) -> Result<(), GraphicsError> {
         if !self.fonts.contains_key(font_path) {
-            self.load_font(font_path).expect("Font not found");
+            self.load_font(font_path)?;
         }
         let font_atlas_path = self.fonts[font_path].font_atlas_path().to_owned();
         if !self.texture_atlases.contains_key(&font_atlas_path) {
-            self.load_texture_atlas(&font_atlas_path).unwrap();
+            self.load_texture_atlas(&font_atlas_path)?;
         }
 
         let font = &self.fonts[font_path];
         let texture_atlas = &self.texture_atlases[font.font_atlas_path()];
 
         let texture_identifier = texture_atlas.texture_identifier();
-        let texture = &self.texture_metadata[texture_identifier];
-        let font_region = texture_atlas
-            .texture_region(font_path)
-            .expect("Font region not found");
+        let (texture_width, texture_height) = match self.texture_size(texture_identifier) {
+            Some(size) => size,
+            None => return Ok(()),
+        };
+        // The glyphs are still laid out with the missing texture if the font region is missing
+        let font_region = texture_atlas.texture_region(font_path);
 
         let mut offset_x = transform.translation.0;
         let mut offset_y = transform.translation.1;
@@ -279,15 +974,9 @@ impl Graphics {
                 continue;
             }
 
-            let glyph_data = if font.ignore_case() {
-                if let Some(glyph) = font.glyph(character.to_ascii_uppercase()) {
-                    glyph
-                } else {
-                    font.glyph(character.to_ascii_lowercase())
-                        .expect("Glyph not found")
-                }
-            } else {
-                font.glyph(character).expect("Glyph not found")
+            let glyph_data = match font.glyph_or_fallback(character) {
+                Some(glyph_data) => glyph_data,
+                None => continue,
             };
 
             let glyph_region = glyph_data.region();
@@ -300,16 +989,27 @@ impl Graphics {
                 &QuadDescription {
                     width: glyph_region.width,
                     height: glyph_region.height,
-                    color: (0.0, 0.0, 0.0),
-                    texture: Some(TextureDescription {
-                        identifier: texture_identifier.into(),
-                        texture_region: TextureRegion {
-                            x: (font_region.x + glyph_region.x) / texture.width as f32,
-                            y: (font_region.y + glyph_region.y) / texture.height as f32,
-                            width: glyph_region.width / texture.width as f32,
-                            height: glyph_region.height / texture.height as f32,
+                    color,
+                    texture: Some(match font_region {
+                        Some(font_region) => TextureDescription {
+                            identifier: texture_identifier.into(),
+                            texture_region: TextureRegion {
+                                x: (font_region.x + glyph_region.x) / texture_width as f32,
+                                y: (font_region.y + glyph_region.y) / texture_height as f32,
+                                width: glyph_region.width / texture_width as f32,
+                                height: glyph_region.height / texture_height as f32,
+                            },
+                            address_mode: None,
+                        },
+                        None => TextureDescription {
+                            identifier: MISSING_TEXTURE_IDENTIFIER.into(),
+                            texture_region: TextureRegion::new(0.0, 0.0, 1.0, 1.0),
+                            address_mode: None,
                         },
                     }),
+                    corner_radius: 0.0,
+                    outline_thickness: 0.0,
+                    blend_mode: BlendMode::Alpha,
                 },
                 &glyph_transform,
                 apply_view_transform,
@@ -318,6 +1018,14 @@ impl Graphics {
 
             offset_x += glyph_region.width + font.letter_spacing() as f32;
         }
+
+        match font_region {
+            Some(_) => Ok(()),
+            None => Err(GraphicsError::AtlasRegionNotFound(
+                font.font_atlas_path().into(),
+                font_path.into(),
+            )),
+        }
     }
 
     fn load_font(&mut self, font_path: &str) -> Result<(), GraphicsError> {
@@ -326,9 +1034,17 @@ impl Graphics {
         Ok(())
     }
 
+    /// Drops a cached font, its atlas has to be unloaded with [`Graphics::unload_atlas`]
+    pub fn unload_font(&mut self, font_path: &str) {
+        self.fonts.remove(font_path);
+    }
+
     pub fn default_system_bundle() -> SystemBundle {
         let mut system_bundle = SystemBundle::new();
+        system_bundle.add_system(animation_switch_system);
         system_bundle.add_system(sprite_animation_step_system);
+        system_bundle.add_system(tile_animation_step_system);
+        system_bundle.add_system(camera_follow_system);
         system_bundle
     }
 
@@ -336,75 +1052,2307 @@ impl Graphics {
         self.graphics_impl.set_clear_color(clear_color);
     }
 
+    /// Sets the filter used by the textures loaded from now on
+    pub fn set_texture_filter(&mut self, texture_filter: TextureFilter) {
+        self.texture_filter = texture_filter;
+    }
+
+    /// Sets how a texture is sampled outside of its bounds, must be called before the texture is loaded
+    ///
+    /// Combined with a sprite [`TextureSource::TextureRegion`] larger than the texture,
+    /// [`TextureAddressMode::Repeat`] tiles the texture over the sprite. Sprites can override it
+    /// with their own `address_mode`.
+    pub fn set_texture_address_mode(
+        &mut self,
+        texture_identifier: &str,
+        address_mode: TextureAddressMode,
+    ) {
+        self.texture_address_modes
+            .insert(texture_identifier.into(), address_mode);
+    }
+
     pub fn set_bounding_box_rendering(&mut self, enabled: bool) {
         self.bounding_box_rendering = enabled;
     }
 
+    /// Draws quads and tilemaps as wireframes to visualize geometry and overdraw
+    pub fn set_wireframe_rendering(&mut self, enabled: bool) {
+        self.graphics_impl.set_wireframe_rendering(enabled);
+    }
+
+    /// Rounds the camera translation to whole pixels, see `OrthographicCamera::snapped_view_matrix`
+    pub fn set_pixel_snapping(&mut self, enabled: bool) {
+        self.graphics_impl.set_pixel_snapping(enabled);
+    }
+
+    /// Sets the color of the bounding boxes, magenta by default
+    pub fn set_bounding_box_color(&mut self, color: Color) {
+        self.graphics_impl.set_bounding_box_color(color);
+    }
+
     pub fn on_window_resized(&mut self, width: u32, height: u32) {
+        self.window_size = (width, height);
         self.graphics_impl.on_window_resized((width, height));
     }
 }
 
+/// Returns the transform of a quad drawn at `offset` in the local space of `transform`
+fn offset_transform(transform: &Transform2D, offset: (f32, f32)) -> Transform2D {
+    let mut offset_transform = *transform;
+    offset_transform.translation.0 += offset.0;
+    offset_transform.translation.1 += offset.1;
+    offset_transform.rotation_center.0 -= offset.0;
+    offset_transform.rotation_center.1 -= offset.1;
+    offset_transform
+}
+
+/// Returns whether a quad placed at `transform` overlaps the (left, top, right, bottom) bounds,
+/// using the bounding box of the quad when it is rotated
+fn is_quad_visible(
+    width: f32,
+    height: f32,
+    transform: &Transform2D,
+    visible_bounds: (f32, f32, f32, f32),
+) -> bool {
+    let matrix = transform.into_matrix4();
+    let (left, top, right, bottom) = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+        .iter()
+        .map(|&(x, y)| matrix.transform_point(&Point3::new(x, y, 0.0)))
+        .fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(left, top, right, bottom), corner| {
+                (
+                    left.min(corner.x),
+                    top.min(corner.y),
+                    right.max(corner.x),
+                    bottom.max(corner.y),
+                )
+            },
+        );
+    let (visible_left, visible_top, visible_right, visible_bottom) = visible_bounds;
+
+    left <= visible_right && right >= visible_left && top <= visible_bottom && bottom >= visible_top
+}
+
+/// Returns whether a quad placed at `transform` contains a point
+fn quad_contains(width: f32, height: f32, transform: &Transform2D, point: (f32, f32)) -> bool {
+    let local_point = match transform.into_matrix4().try_inverse() {
+        Some(inverse_matrix) => inverse_matrix.transform_point(&Point3::new(point.0, point.1, 0.0)),
+        None => return false,
+    };
+
+    (0.0..=width).contains(&local_point.x) && (0.0..=height).contains(&local_point.y)
+}
+
+/// Returns the grid lines on multiples of `cell_size` within the (left, top, right, bottom) bounds
+fn grid_lines(cell_size: f32, bounds: (f32, f32, f32, f32)) -> Vec<((f32, f32), (f32, f32))> {
+    let (left, top, right, bottom) = bounds;
+    let mut lines = vec![];
+    if cell_size <= 0.0 {
+        return lines;
+    }
+
+    let mut x = (left / cell_size).ceil() * cell_size;
+    while x <= right {
+        lines.push(((x, top), (x, bottom)));
+        x += cell_size;
+    }
+
+    let mut y = (top / cell_size).ceil() * cell_size;
+    while y <= bottom {
+        lines.push(((left, y), (right, y)));
+        y += cell_size;
+    }
+
+    lines
+}
+
+fn anchored_transform(
+    transform: &Transform2D,
+    anchor: (f32, f32),
+    width: f32,
+    height: f32,
+) -> Transform2D {
+    offset_transform(transform, (-anchor.0 * width, -anchor.1 * height))
+}
+
+fn is_hidden(ecs: &Ecs, id: EntityIndex) -> bool {
+    ecs.query_one_by_id::<(R<Hidden>,)>(id).is_some()
+}
+
+/// Returns whether an element is drawn in world space, elements with a `NoViewTransform` are
+/// drawn in screen space
+fn applies_view_transform(ecs: &Ecs, id: EntityIndex) -> bool {
+    ecs.query_one_by_id::<(R<NoViewTransform>,)>(id).is_none()
+}
+
+fn render_layer(ecs: &Ecs, id: EntityIndex) -> RenderLayer {
+    if let Some((_, (render_layer,))) = ecs.query_one_by_id::<(R<RenderLayer>,)>(id) {
+        return *render_layer;
+    }
+
+    if !applies_view_transform(ecs, id) {
+        RenderLayer::UI
+    } else {
+        RenderLayer::World
+    }
+}
+
+/// Returns whether an element is drawn in a render layer by a camera with the given layer mask
+fn is_drawn(ecs: &Ecs, id: EntityIndex, layer: RenderLayer, layer_mask: u32) -> bool {
+    let culling_layer = ecs
+        .query_one_by_id::<(R<CullingLayer>,)>(id)
+        .map(|(_, (culling_layer,))| *culling_layer)
+        .unwrap_or_default();
+    !is_hidden(ecs, id) && render_layer(ecs, id) == layer && culling_layer.is_in(layer_mask)
+}
+
+/// Returns the entities of a render layer matching a query that are drawn by a camera with the
+/// given layer mask, sorted by `DrawOrder` then by entity id
+fn draw_ordered<'a, Q: Query<'a>>(
+    ecs: &Ecs,
+    layer: RenderLayer,
+    layer_mask: u32,
+) -> Vec<EntityIndex> {
+    let mut ids = ecs.matching_entities::<Q>();
+    ids.retain(|&id| is_drawn(ecs, id, layer, layer_mask));
+    ids.sort_by_key(|&id| {
+        ecs.query_one_by_id::<(R<DrawOrder>,)>(id)
+            .map(|(_, (draw_order,))| *draw_order)
+            .unwrap_or_default()
+    });
+    ids
+}
+
+/// Returns the (entity, width, height, anchored transform) of the rectangle, circle, sprite and
+/// animated sprite quads of a render layer drawn by a camera with the given layer mask, in draw
+/// order
+fn drawn_quads(
+    ecs: &Ecs,
+    layer: RenderLayer,
+    layer_mask: u32,
+) -> Vec<(EntityIndex, f32, f32, Transform2D)> {
+    let mut quads = vec![];
+    for id in draw_ordered::<(R<RectangleShape>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (rectangle_shape, transform)) = ecs
+            .query_one_by_id::<(R<RectangleShape>, R<Transform2D>)>(id)
+            .unwrap();
+        let (width, height) = (rectangle_shape.width, rectangle_shape.height);
+        let transform = anchored_transform(&transform, rectangle_shape.anchor, width, height);
+        quads.push((id, width, height, transform));
+    }
+    for id in draw_ordered::<(R<CircleShape>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (circle_shape, transform)) = ecs
+            .query_one_by_id::<(R<CircleShape>, R<Transform2D>)>(id)
+            .unwrap();
+        let diameter = circle_shape.radius * 2.0;
+        let transform = anchored_transform(&transform, (0.5, 0.5), diameter, diameter);
+        quads.push((id, diameter, diameter, transform));
+    }
+    for id in draw_ordered::<(R<Sprite>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (sprite, transform)) = ecs
+            .query_one_by_id::<(R<Sprite>, R<Transform2D>)>(id)
+            .unwrap();
+        let transform = anchored_transform(&transform, sprite.anchor, sprite.width, sprite.height);
+        quads.push((id, sprite.width, sprite.height, transform));
+    }
+    for id in draw_ordered::<(R<AnimatedSprite>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (animated_sprite, transform)) = ecs
+            .query_one_by_id::<(R<AnimatedSprite>, R<Transform2D>)>(id)
+            .unwrap();
+        quads.push((
+            id,
+            animated_sprite.width,
+            animated_sprite.height,
+            *transform,
+        ));
+    }
+
+    quads
+}
+
+fn clip_rect(ecs: &Ecs, id: EntityIndex) -> Option<ClipRect> {
+    ecs.query_one_by_id::<(R<ClipRect>,)>(id)
+        .map(|(_, (clip_rect,))| *clip_rect)
+}
+
+/// Renders the frame seen by the active camera
+///
+/// This is `begin_frame` immediately followed by `end_frame`.
 pub fn render(ecs: &mut Ecs) {
+    begin_frame(ecs);
+    end_frame(ecs);
+}
+
+/// Prepares the elements of the Ecs seen by the active camera, without rendering them
+///
+/// Every `RenderLayer` is drawn in turn. In a layer, background tilemaps, rectangles, circles,
+/// sprites, animated sprites, meshes, foreground tilemaps and the UI elements are drawn in that
+/// order, each kind sorted by `DrawOrder` then entity id. `Hidden` elements and the ones whose
+/// `CullingLayer` isn't in the layer mask of the camera are skipped.
+///
+/// The z of the transforms only orders the world elements of a layer between themselves: a
+/// layer is drawn over the previous ones and its UI elements over its world elements.
+///
+/// Anything prepared on the `Graphics` resource until `end_frame` is drawn over these elements,
+/// whatever its z.
+pub fn begin_frame(ecs: &mut Ecs) {
     let mut graphics = ecs.shared_resource_mut::<Graphics>().unwrap();
+    #[cfg(feature = "hot-reload")]
+    graphics.reload_modified_textures();
+    graphics.upload_loaded_textures();
 
-    let (camera_id, (camera, _, camera_transform)) = ecs
-        .query_one::<(R<OrthographicCamera>, R<Active>, R<Transform2D>)>()
-        .expect("There is no camera");
+    let (camera_id, (camera, _, camera_transform)) =
+        match ecs.query_one::<(R<OrthographicCamera>, R<Active>, R<Transform2D>)>() {
+            Some(camera) => camera,
+            None => {
+                if !graphics.missing_camera_warned {
+                    eprintln!("There is no active camera, only the clear color is rendered");
+                    graphics.missing_camera_warned = true;
+                }
+                graphics.camera = None;
+                return;
+            }
+        };
+    graphics.missing_camera_warned = false;
+    graphics.camera = Some((*camera, *camera_transform));
+    let visible_bounds = camera.visible_world_bounds(&camera_transform);
     graphics
         .graphics_impl
         .update_camera(camera_id, &camera, &camera_transform);
 
-    for (_, (tilemap, tilemap_render, transform)) in
-        ecs.query::<(R<Tilemap>, R<TilemapRender>, R<Transform2D>)>()
-    {
-        graphics.prepare_tilemap(&tilemap, &tilemap_render, &transform);
+    for &layer in &RenderLayer::ALL {
+        graphics.graphics_impl.clear_depth();
+        prepare_world_elements(ecs, &mut graphics, layer, camera.layer_mask, visible_bounds);
+        graphics.graphics_impl.clear_depth();
+        prepare_ui_elements(ecs, &mut graphics, layer, camera.layer_mask);
+    }
+    graphics.graphics_impl.clear_depth();
+
+    for (_, (mut tilemap_render,)) in ecs.query::<(W<TilemapRender>,)>() {
+        tilemap_render.dirty = false;
+        tilemap_render.dirty_tiles.clear();
     }
+}
+
+/// Renders everything prepared since the last frame
+pub fn end_frame(ecs: &mut Ecs) {
+    ecs.shared_resource_mut::<Graphics>().unwrap().render();
+}
 
-    for (_, (rectangle_shape, transform)) in ecs.query::<(R<RectangleShape>, R<Transform2D>)>() {
-        graphics.prepare_rectangle(&rectangle_shape, &transform, true);
+/// Prepares the tilemaps, shapes, sprites and meshes of a render layer
+fn prepare_world_elements(
+    ecs: &Ecs,
+    graphics: &mut Graphics,
+    layer: RenderLayer,
+    layer_mask: u32,
+    visible_bounds: (f32, f32, f32, f32),
+) {
+    let mut tilemap_layers: Vec<(EntityIndex, i32)> = ecs
+        .query::<(R<Tilemap>, R<TilemapRender>, R<Transform2D>)>()
+        .filter(|(id, _)| is_drawn(ecs, *id, layer, layer_mask))
+        .map(|(id, (_, tilemap_render, _))| (id, tilemap_render.layer))
+        .collect();
+    tilemap_layers.sort_by_key(|&(id, layer)| (layer, id));
+    let (background_layers, foreground_layers): (Vec<_>, Vec<_>) = tilemap_layers
+        .into_iter()
+        .partition(|&(_, layer)| layer <= 0);
+
+    for (id, _) in background_layers {
+        let (_, (tilemap, tilemap_render, transform)) = ecs
+            .query_one_by_id::<(R<Tilemap>, R<TilemapRender>, R<Transform2D>)>(id)
+            .unwrap();
+        if let Err(error) = graphics.prepare_tilemap(&tilemap, &tilemap_render, &transform) {
+            graphics.report_error(error);
+        }
     }
-    for (_, (sprite, transform)) in ecs.query::<(R<Sprite>, R<Transform2D>)>() {
-        graphics.prepare_sprite(&sprite, &transform, true).unwrap();
+
+    for id in draw_ordered::<(R<RectangleShape>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (rectangle_shape, transform)) = ecs
+            .query_one_by_id::<(R<RectangleShape>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        let (width, height) = (rectangle_shape.width, rectangle_shape.height);
+        let anchored_transform =
+            anchored_transform(&transform, rectangle_shape.anchor, width, height);
+        if !apply_view_transform
+            || is_quad_visible(width, height, &anchored_transform, visible_bounds)
+        {
+            graphics.prepare_rectangle(&rectangle_shape, &transform, apply_view_transform);
+        }
     }
-    for (_, (animated_sprite, transform)) in ecs.query::<(R<AnimatedSprite>, R<Transform2D>)>() {
-        graphics
-            .prepare_animated_sprite(&animated_sprite, &transform, true)
+    for id in draw_ordered::<(R<CircleShape>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (circle_shape, transform)) = ecs
+            .query_one_by_id::<(R<CircleShape>, R<Transform2D>)>(id)
             .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        let diameter = circle_shape.radius * 2.0;
+        let anchored_transform = anchored_transform(&transform, (0.5, 0.5), diameter, diameter);
+        if !apply_view_transform
+            || is_quad_visible(diameter, diameter, &anchored_transform, visible_bounds)
+        {
+            graphics.prepare_circle(&circle_shape, &transform, apply_view_transform);
+        }
     }
+    for id in draw_ordered::<(R<Sprite>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (sprite, transform)) = ecs
+            .query_one_by_id::<(R<Sprite>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        let anchored_transform =
+            anchored_transform(&transform, sprite.anchor, sprite.width, sprite.height);
+        if apply_view_transform
+            && !is_quad_visible(
+                sprite.width,
+                sprite.height,
+                &anchored_transform,
+                visible_bounds,
+            )
+        {
+            continue;
+        }
 
-    for (_, (mut tilemap_render,)) in ecs.query::<(W<TilemapRender>,)>() {
-        tilemap_render.dirty = false;
+        if let Err(error) = graphics.prepare_sprite(&sprite, &transform, apply_view_transform) {
+            graphics.report_error(error);
+            graphics.prepare_missing_texture(
+                sprite.width,
+                sprite.height,
+                &anchored_transform,
+                apply_view_transform,
+            );
+        }
+    }
+    for id in draw_ordered::<(R<AnimatedSprite>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (animated_sprite, transform)) = ecs
+            .query_one_by_id::<(R<AnimatedSprite>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        if apply_view_transform
+            && !is_quad_visible(
+                animated_sprite.width,
+                animated_sprite.height,
+                &transform,
+                visible_bounds,
+            )
+        {
+            continue;
+        }
+
+        if let Err(error) =
+            graphics.prepare_animated_sprite(&animated_sprite, &transform, apply_view_transform)
+        {
+            graphics.report_error(error);
+            graphics.prepare_missing_texture(
+                animated_sprite.width,
+                animated_sprite.height,
+                &transform,
+                apply_view_transform,
+            );
+        }
+    }
+
+    for id in draw_ordered::<(R<Mesh2D>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (mesh, transform)) = ecs
+            .query_one_by_id::<(R<Mesh2D>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        if let Err(error) = graphics.prepare_mesh_2d(&mesh, &transform, apply_view_transform) {
+            graphics.report_error(error);
+            graphics.prepare_missing_mesh_texture(&mesh, &transform, apply_view_transform);
+        }
+    }
+
+    for (id, _) in foreground_layers {
+        let (_, (tilemap, tilemap_render, transform)) = ecs
+            .query_one_by_id::<(R<Tilemap>, R<TilemapRender>, R<Transform2D>)>(id)
+            .unwrap();
+        if let Err(error) = graphics.prepare_tilemap(&tilemap, &tilemap_render, &transform) {
+            graphics.report_error(error);
+        }
     }
+}
 
-    for (id, (frame, transform)) in ecs.query::<(R<Frame>, R<Transform2D>)>() {
-        let apply_view_transform = !ecs.query_one_by_id::<(R<NoViewTransform>,)>(id).is_some();
+/// Prepares the frames, nine patches, texts and images of a render layer
+fn prepare_ui_elements(ecs: &Ecs, graphics: &mut Graphics, layer: RenderLayer, layer_mask: u32) {
+    for id in draw_ordered::<(R<Frame>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (frame, transform)) = ecs
+            .query_one_by_id::<(R<Frame>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        let clip_rect = clip_rect(ecs, id);
+        if let Some(clip_rect) = clip_rect {
+            graphics.push_clip_rect(clip_rect);
+        }
         graphics.prepare_rectangle(
             &RectangleShape {
                 width: frame.width,
                 height: frame.height,
+                anchor: (0.0, 0.0),
                 color: frame.color,
+                corner_radius: 0.0,
             },
             &transform,
             apply_view_transform,
         );
+        if clip_rect.is_some() {
+            graphics.pop_clip_rect();
+        }
+    }
+
+    for id in draw_ordered::<(R<NinePatch>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (nine_patch, transform)) = ecs
+            .query_one_by_id::<(R<NinePatch>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        let clip_rect = clip_rect(ecs, id);
+        if let Some(clip_rect) = clip_rect {
+            graphics.push_clip_rect(clip_rect);
+        }
+        if let Err(error) =
+            graphics.prepare_nine_patch(&nine_patch, &transform, apply_view_transform)
+        {
+            graphics.report_error(error);
+            graphics.prepare_missing_texture(
+                nine_patch.width,
+                nine_patch.height,
+                &transform,
+                apply_view_transform,
+            );
+        }
+        if clip_rect.is_some() {
+            graphics.pop_clip_rect();
+        }
     }
 
-    for (id, (text, transform)) in ecs.query::<(R<Text>, R<Transform2D>)>() {
-        let apply_view_transform = !ecs.query_one_by_id::<(R<NoViewTransform>,)>(id).is_some();
-        graphics.prepare_text(text.text(), text.font(), &transform, apply_view_transform);
+    for id in draw_ordered::<(R<Text>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (text, transform)) = ecs
+            .query_one_by_id::<(R<Text>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
+        let clip_rect = clip_rect(ecs, id);
+        if let Some(clip_rect) = clip_rect {
+            graphics.push_clip_rect(clip_rect);
+        }
+        if let Err(error) = graphics.prepare_text(
+            text.text(),
+            text.font(),
+            text.color(),
+            &transform,
+            apply_view_transform,
+        ) {
+            graphics.report_error(error);
+        }
+        if clip_rect.is_some() {
+            graphics.pop_clip_rect();
+        }
     }
 
-    for (id, (image, transform)) in ecs.query::<(R<Image>, R<Transform2D>)>() {
-        let apply_view_transform = !ecs.query_one_by_id::<(R<NoViewTransform>,)>(id).is_some();
+    for id in draw_ordered::<(R<Image>, R<Transform2D>)>(ecs, layer, layer_mask) {
+        let (_, (image, transform)) = ecs
+            .query_one_by_id::<(R<Image>, R<Transform2D>)>(id)
+            .unwrap();
+        let apply_view_transform = applies_view_transform(ecs, id);
         let sprite = Sprite {
             width: image.width,
             height: image.height,
+            anchor: (0.0, 0.0),
             texture: image.texture.clone(),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
+        };
+        let clip_rect = clip_rect(ecs, id);
+        if let Some(clip_rect) = clip_rect {
+            graphics.push_clip_rect(clip_rect);
+        }
+
+        if let Err(error) = graphics.prepare_sprite(&sprite, &transform, apply_view_transform) {
+            graphics.report_error(error);
+            graphics.prepare_missing_texture(
+                image.width,
+                image.height,
+                &transform,
+                apply_view_transform,
+            );
+        }
+        if clip_rect.is_some() {
+            graphics.pop_clip_rect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design_resolution::FixedResolution;
+    use crate::tilemap::TilemapProjection;
+
+    struct PreparedQuad {
+        width: f32,
+        height: f32,
+        transform: Transform2D,
+        texture: Option<String>,
+        texture_region: Option<TextureRegion>,
+        address_mode: Option<TextureAddressMode>,
+        color: Color,
+        corner_radius: f32,
+        outline_thickness: f32,
+        blend_mode: BlendMode,
+        apply_view_transform: bool,
+    }
+
+    type PreparedLine = ((f32, f32), (f32, f32), Color);
+
+    #[derive(Default)]
+    struct MockGraphicsAPI {
+        prepared_quads: std::rc::Rc<std::cell::RefCell<Vec<PreparedQuad>>>,
+        prepared_lines: std::rc::Rc<std::cell::RefCell<Vec<PreparedLine>>>,
+        /// The vertex count of every prepared mesh
+        prepared_meshes: std::rc::Rc<std::cell::RefCell<Vec<usize>>>,
+        draw_order: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+        /// The length of the draw order at every depth clear
+        depth_clears: std::rc::Rc<std::cell::RefCell<Vec<usize>>>,
+        render_count: std::rc::Rc<std::cell::Cell<usize>>,
+        unloaded_textures: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+        loaded_textures: std::rc::Rc<std::cell::RefCell<Vec<TextureData>>>,
+        clip_rects: std::rc::Rc<std::cell::RefCell<Vec<Option<ClipRect>>>>,
+        fixed_resolution: Option<FixedResolution>,
+    }
+
+    impl LowLevelGraphicsAPI for MockGraphicsAPI {
+        fn initialize(&mut self, _window: Window, _window_size: WindowSize) {}
+        fn render(&mut self) {
+            self.render_count.set(self.render_count.get() + 1);
+        }
+        fn prepare_quad(
+            &mut self,
+            quad_description: &QuadDescription,
+            transform: &Transform2D,
+            apply_view_transform: bool,
+            _bounding_box_rendering: bool,
+        ) {
+            self.draw_order.borrow_mut().push("quad".into());
+            self.prepared_quads.borrow_mut().push(PreparedQuad {
+                width: quad_description.width,
+                height: quad_description.height,
+                transform: *transform,
+                blend_mode: quad_description.blend_mode,
+                texture: quad_description
+                    .texture
+                    .as_ref()
+                    .map(|texture| texture.identifier.clone()),
+                texture_region: quad_description
+                    .texture
+                    .as_ref()
+                    .map(|texture| texture.texture_region),
+                address_mode: quad_description
+                    .texture
+                    .as_ref()
+                    .and_then(|texture| texture.address_mode),
+                color: quad_description.color,
+                corner_radius: quad_description.corner_radius,
+                outline_thickness: quad_description.outline_thickness,
+                apply_view_transform,
+            });
+        }
+        fn prepare_line(&mut self, start: (f32, f32), end: (f32, f32), color: Color) {
+            self.prepared_lines.borrow_mut().push((start, end, color));
+        }
+        fn prepare_mesh(
+            &mut self,
+            mesh_description: &MeshDescription,
+            _transform: &Transform2D,
+            _apply_view_transform: bool,
+        ) {
+            self.draw_order.borrow_mut().push("mesh".into());
+            self.prepared_meshes
+                .borrow_mut()
+                .push(mesh_description.vertices.len());
+        }
+        fn prepare_tilemap(
+            &mut self,
+            _tilemap: &Tilemap,
+            tilemap_render: &TilemapRender,
+            _texture_atlas: &TextureAtlas,
+            _transform: &Transform2D,
+        ) {
+            self.draw_order
+                .borrow_mut()
+                .push(tilemap_render.identifier.clone());
+        }
+        fn is_texture_in_memory(&self, texture_identifier: &str) -> bool {
+            self.loaded_textures
+                .borrow()
+                .iter()
+                .any(|texture_data| texture_data.identifier == texture_identifier)
+        }
+        fn load_texture(&mut self, texture_data: TextureData) {
+            self.loaded_textures.borrow_mut().push(texture_data);
+        }
+        fn unload_texture(&mut self, texture_identifier: &str) {
+            self.unloaded_textures
+                .borrow_mut()
+                .push(texture_identifier.into());
+        }
+        fn update_camera(
+            &mut self,
+            _camera_id: EntityIndex,
+            _camera: &OrthographicCamera,
+            _transform: &Transform2D,
+        ) {
+        }
+        fn set_clip_rect(&mut self, clip_rect: Option<ClipRect>) {
+            self.draw_order.borrow_mut().push(
+                if clip_rect.is_some() {
+                    "clip"
+                } else {
+                    "unclip"
+                }
+                .into(),
+            );
+            self.clip_rects.borrow_mut().push(clip_rect);
+        }
+        fn clear_depth(&mut self) {
+            self.depth_clears
+                .borrow_mut()
+                .push(self.draw_order.borrow().len());
+        }
+        fn set_clear_color(&mut self, _color: Color) {}
+        fn set_bounding_box_color(&mut self, _color: Color) {}
+        fn set_wireframe_rendering(&mut self, _enabled: bool) {}
+        fn set_pixel_snapping(&mut self, _enabled: bool) {}
+        fn on_window_resized(&mut self, _size: WindowSize) {}
+        fn last_frame_stats(&self) -> RenderStats {
+            RenderStats::default()
+        }
+        fn fixed_resolution(&self) -> Option<FixedResolution> {
+            self.fixed_resolution
+        }
+    }
+
+    fn quad_bounds(quad: &PreparedQuad) -> (f32, f32, f32, f32) {
+        let matrix = quad.transform.into_matrix4();
+        let top_left = matrix.transform_point(&Point3::new(0.0, 0.0, 0.0));
+        let bottom_right = matrix.transform_point(&Point3::new(quad.width, quad.height, 0.0));
+        (top_left.x, top_left.y, bottom_right.x, bottom_right.y)
+    }
+
+    /// Records the metadata of a texture as if it had been loaded from disk
+    fn register_texture(graphics: &mut Graphics, texture_identifier: &str, size: (u32, u32)) {
+        graphics.texture_metadata.insert(
+            texture_identifier.into(),
+            TextureMetadata {
+                width: size.0,
+                height: size.1,
+            },
+        );
+    }
+
+    /// Returns a camera showing the 800x600 pixels from its transform
+    fn insert_active_camera(ecs: &mut Ecs) {
+        insert_active_camera_at(ecs, (0.0, 0.0));
+    }
+
+    fn insert_active_camera_at(ecs: &mut Ecs, translation: (f32, f32)) {
+        ecs.insert((
+            OrthographicCamera::default(),
+            Active,
+            Transform2D {
+                translation,
+                ..Default::default()
+            },
+        ));
+    }
+
+    fn rectangle(width: f32, height: f32) -> RectangleShape {
+        RectangleShape {
+            width,
+            height,
+            anchor: (0.0, 0.0),
+            color: Color::WHITE,
+            corner_radius: 0.0,
+        }
+    }
+
+    fn sprite(width: f32, height: f32) -> Sprite {
+        Sprite {
+            width,
+            height,
+            anchor: (0.0, 0.0),
+            texture: "texture".into(),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
+        }
+    }
+
+    /// Calls `poll` until it returns true, failing the test if that takes more than a few seconds
+    pub(crate) fn poll_until(mut poll: impl FnMut() -> bool) {
+        let start = std::time::Instant::now();
+        while !poll() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn graphics_errors_have_a_message() {
+        let io_error = || std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
+        let errors = vec![
+            GraphicsError::TextureFileOpenError(io_error()),
+            GraphicsError::AtlasDescriptionFileOpenError(io_error()),
+            GraphicsError::ImageDecodeError(ImageError::IoError(io_error())),
+            GraphicsError::SerdeError(serde_json::from_str::<u32>("atlas").unwrap_err()),
+            GraphicsError::BitmapFontFileReadError(io_error()),
+        ];
+
+        for error in errors {
+            assert!(!error.to_string().is_empty());
+            assert!(std::error::Error::source(&error).is_some());
+        }
+        assert_eq!(
+            GraphicsError::AtlasRegionNotFound("atlas.json".into(), "tree".into()).to_string(),
+            "couldn't find region tree in texture atlas atlas.json"
+        );
+    }
+
+    #[test]
+    fn colors_are_opaque_unless_given_an_alpha() {
+        let opaque: Color = (1.0, 0.5, 0.0).into();
+        assert_eq!(opaque, Color::rgb(1.0, 0.5, 0.0));
+        assert_eq!(opaque.a, 1.0);
+
+        let translucent: Color = (1.0, 0.5, 0.0, 0.25).into();
+        assert_eq!(translucent, Color::rgba(1.0, 0.5, 0.0, 0.25));
+        assert_eq!(translucent.a, 0.25);
+    }
+
+    #[test]
+    fn centered_anchor_is_symmetric_about_translation() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+
+        graphics
+            .prepare_sprite(
+                &Sprite {
+                    anchor: (0.5, 0.5),
+                    ..sprite(20.0, 10.0)
+                },
+                &transform,
+                true,
+            )
+            .unwrap();
+        graphics.prepare_rectangle(
+            &RectangleShape {
+                anchor: (0.5, 0.5),
+                ..rectangle(20.0, 10.0)
+            },
+            &transform,
+            true,
+        );
+        graphics.prepare_rectangle(&rectangle(20.0, 10.0), &transform, true);
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(quad_bounds(&prepared_quads[0]), (90.0, 45.0, 110.0, 55.0));
+        assert_eq!(quad_bounds(&prepared_quads[1]), (90.0, 45.0, 110.0, 55.0));
+        assert_eq!(quad_bounds(&prepared_quads[2]), (100.0, 50.0, 120.0, 60.0));
+    }
+
+    #[test]
+    fn rounded_rectangles_are_prepared_with_their_corner_radius() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+
+        graphics.prepare_rectangle(
+            &RectangleShape {
+                corner_radius: 8.0,
+                ..rectangle(40.0, 20.0)
+            },
+            &Transform2D::default(),
+            true,
+        );
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(quad_bounds(&prepared_quads[0]), (0.0, 0.0, 40.0, 20.0));
+        assert_eq!(prepared_quads[0].texture, None);
+        assert_eq!(prepared_quads[0].corner_radius, 8.0);
+        assert_eq!(prepared_quads[0].outline_thickness, 0.0);
+    }
+
+    #[test]
+    fn circles_are_prepared_as_untextured_rounded_quads() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+        let color = Color::rgb(1.0, 0.0, 0.0);
+
+        graphics.prepare_circle(
+            &CircleShape {
+                radius: 16.0,
+                color,
+                thickness: None,
+            },
+            &transform,
+            true,
+        );
+        graphics.prepare_circle(
+            &CircleShape {
+                radius: 16.0,
+                color,
+                thickness: Some(2.0),
+            },
+            &transform,
+            true,
+        );
+
+        let prepared_quads = prepared_quads.borrow();
+        for prepared_quad in prepared_quads.iter() {
+            assert_eq!(quad_bounds(prepared_quad), (84.0, 34.0, 116.0, 66.0));
+            assert_eq!(prepared_quad.texture, None);
+            assert_eq!(prepared_quad.color, color);
+            assert_eq!(prepared_quad.corner_radius, 16.0);
+        }
+        assert_eq!(prepared_quads[0].outline_thickness, 0.0);
+        assert_eq!(prepared_quads[1].outline_thickness, 2.0);
+    }
+
+    #[test]
+    fn render_without_camera_only_clears() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let render_count = graphics_api.render_count.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        ecs.insert((rectangle(20.0, 10.0), Transform2D::default()));
+
+        render(&mut ecs);
+        render(&mut ecs);
+
+        assert_eq!(render_count.get(), 2);
+        assert!(prepared_quads.borrow().is_empty());
+    }
+
+    #[test]
+    fn immediate_quads_are_drawn_after_the_ecs_elements() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let depth_clears = graphics_api.depth_clears.clone();
+        let render_count = graphics_api.render_count.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        insert_active_camera(&mut ecs);
+        // The last element prepared by begin_frame, with a greater z than the immediate quad
+        ecs.insert((
+            Frame {
+                width: 20.0,
+                height: 10.0,
+                color: Color::WHITE,
+            },
+            Transform2D {
+                z: 5.0,
+                ..Default::default()
+            },
+            NoViewTransform,
+        ));
+
+        begin_frame(&mut ecs);
+        ecs.shared_resource_mut::<Graphics>()
+            .unwrap()
+            .prepare_rectangle(&rectangle(30.0, 10.0), &Transform2D::default(), false);
+        assert_eq!(render_count.get(), 0);
+        end_frame(&mut ecs);
+
+        let prepared_widths: Vec<f32> = prepared_quads
+            .borrow()
+            .iter()
+            .map(|quad| quad.width)
+            .collect();
+        assert_eq!(prepared_widths, vec![20.0, 30.0]);
+        assert_eq!(depth_clears.borrow().last(), Some(&1));
+        assert_eq!(render_count.get(), 1);
+    }
+
+    #[test]
+    fn unloaded_atlas_is_reloaded_from_disk() {
+        let graphics_api = MockGraphicsAPI::default();
+        let unloaded_textures = graphics_api.unloaded_textures.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let atlas_path = std::env::temp_dir().join("tuber_graphics_unloaded_atlas.json");
+        let atlas_path = atlas_path.to_str().unwrap();
+        let write_atlas = |texture_identifier: &str| {
+            std::fs::write(
+                atlas_path,
+                format!(
+                    r#"{{"texture_identifier": "{}", "textures": {{"sprite": {{"x": 0.0, "y": 0.0, "width": 16.0, "height": 16.0}}}}}}"#,
+                    texture_identifier
+                ),
+            )
+            .unwrap();
+        };
+        let sprite = Sprite {
+            texture: TextureSource::TextureAtlas(atlas_path.into(), "sprite".into()),
+            ..sprite(16.0, 16.0)
         };
 
-        graphics.prepare_sprite(&sprite, &transform, apply_view_transform);
+        write_atlas("first.png");
+        graphics
+            .prepare_sprite(&sprite, &Transform2D::default(), true)
+            .unwrap();
+        write_atlas("second.png");
+        graphics
+            .prepare_sprite(&sprite, &Transform2D::default(), true)
+            .unwrap();
+        assert_eq!(
+            graphics.texture_atlases[atlas_path].texture_identifier,
+            "first.png"
+        );
+
+        graphics.unload_atlas(atlas_path);
+        assert!(!graphics.texture_atlases.contains_key(atlas_path));
+        assert_eq!(*unloaded_textures.borrow(), vec!["first.png".to_string()]);
+
+        graphics
+            .prepare_sprite(&sprite, &Transform2D::default(), true)
+            .unwrap();
+        assert_eq!(
+            graphics.texture_atlases[atlas_path].texture_identifier,
+            "second.png"
+        );
+
+        std::fs::remove_file(atlas_path).unwrap();
     }
 
-    graphics.render();
+    #[test]
+    fn atlas_texture_shared_with_another_atlas_is_kept() {
+        let graphics_api = MockGraphicsAPI::default();
+        let unloaded_textures = graphics_api.unloaded_textures.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        for atlas in &["first_atlas", "second_atlas"] {
+            graphics.texture_atlases.insert(
+                atlas.to_string(),
+                TextureAtlas {
+                    texture_identifier: "texture".into(),
+                    textures: HashMap::new(),
+                },
+            );
+        }
+
+        graphics.unload_atlas("first_atlas");
+        assert!(unloaded_textures.borrow().is_empty());
+
+        graphics.unload_atlas("second_atlas");
+        assert_eq!(*unloaded_textures.borrow(), vec!["texture".to_string()]);
+    }
+
+    #[test]
+    fn reloaded_texture_replaces_the_loaded_one() {
+        let graphics_api = MockGraphicsAPI::default();
+        let loaded_textures = graphics_api.loaded_textures.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let texture_path = std::env::temp_dir().join("tuber_graphics_reloaded_texture.png");
+        let texture = texture_path.to_str().unwrap();
+
+        image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 255]))
+            .save(texture)
+            .unwrap();
+        graphics.load_texture(texture);
+        image::RgbaImage::from_pixel(2, 1, image::Rgba([0, 0, 255, 255]))
+            .save(texture)
+            .unwrap();
+        graphics.reload_texture(texture);
+
+        let loaded_textures = loaded_textures.borrow();
+        assert_eq!(loaded_textures.len(), 2);
+        assert_eq!(loaded_textures[0].identifier, loaded_textures[1].identifier);
+        assert_eq!(loaded_textures[0].bytes, vec![255, 0, 0, 255]);
+        assert_eq!(
+            loaded_textures[1].bytes,
+            vec![0, 0, 255, 255, 0, 0, 255, 255]
+        );
+        assert_eq!(graphics.texture_metadata[texture].width, 2);
+
+        std::fs::remove_file(texture).unwrap();
+    }
+
+    #[test]
+    fn preloaded_texture_is_uploaded_once_decoded() {
+        let graphics_api = MockGraphicsAPI::default();
+        let loaded_textures = graphics_api.loaded_textures.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let texture_path = std::env::temp_dir().join("tuber_graphics_preloaded_texture.png");
+        let texture = texture_path.to_str().unwrap();
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255]))
+            .save(texture)
+            .unwrap();
+
+        graphics.preload_textures(&[texture]);
+        graphics.preload_textures(&[texture]);
+        poll_until(|| {
+            graphics.upload_loaded_textures();
+            graphics.graphics_impl.is_texture_in_memory(texture)
+        });
+
+        assert_eq!(graphics.texture_size(texture), Some((3, 2)));
+        assert_eq!(loaded_textures.borrow().len(), 1);
+        std::fs::remove_file(texture).unwrap();
+    }
+
+    #[test]
+    fn failed_background_load_is_reported() {
+        let graphics_api = MockGraphicsAPI::default();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+
+        graphics.preload_textures(&["nonexistent_texture.png"]);
+        poll_until(|| {
+            graphics.upload_loaded_textures();
+            graphics.failed_textures.contains("nonexistent_texture.png")
+        });
+
+        assert_eq!(graphics.texture_size("nonexistent_texture.png"), None);
+        assert_eq!(graphics.reported_errors.len(), 1);
+    }
+
+    #[test]
+    fn sprites_can_use_a_texture_loaded_from_bytes() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let mut bytes = vec![];
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(16, 8))
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        graphics
+            .load_texture_from_bytes("embedded_texture", &bytes)
+            .unwrap();
+        assert!(graphics
+            .load_texture_from_bytes("invalid_texture", &[0, 1, 2])
+            .is_err());
+
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            Sprite {
+                texture: TextureSource::TextureRegion(
+                    "embedded_texture".into(),
+                    TextureRegion::new(0.0, 0.0, 8.0, 8.0),
+                ),
+                ..sprite(20.0, 10.0)
+            },
+            Transform2D::default(),
+        ));
+        render(&mut ecs);
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 1);
+        assert_eq!(
+            prepared_quads[0].texture.as_deref(),
+            Some("embedded_texture")
+        );
+        assert_eq!(
+            prepared_quads[0].texture_region,
+            Some(TextureRegion::new(0.0, 0.0, 0.5, 1.0))
+        );
+    }
+
+    #[test]
+    fn sprites_can_use_an_atlas_registered_in_code() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "atlas_texture", (32, 32));
+        let mut textures = HashMap::new();
+        textures.insert("tree".to_string(), TextureRegion::new(8.0, 0.0, 8.0, 16.0));
+        graphics.register_texture_atlas(
+            "generated_atlas",
+            TextureAtlas {
+                texture_identifier: "atlas_texture".into(),
+                textures,
+            },
+        );
+
+        graphics
+            .prepare_sprite(
+                &Sprite {
+                    texture: TextureSource::TextureAtlas("generated_atlas".into(), "tree".into()),
+                    ..sprite(20.0, 10.0)
+                },
+                &Transform2D::default(),
+                true,
+            )
+            .unwrap();
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 1);
+        assert_eq!(prepared_quads[0].texture.as_deref(), Some("atlas_texture"));
+        assert_eq!(
+            prepared_quads[0].texture_region,
+            Some(TextureRegion::new(0.25, 0.0, 0.25, 0.5))
+        );
+        assert!(graphics.reported_errors.is_empty());
+    }
+
+    #[test]
+    fn meshes_submit_their_vertices_after_the_sprites() {
+        let graphics_api = MockGraphicsAPI::default();
+        let draw_order = graphics_api.draw_order.clone();
+        let prepared_meshes = graphics_api.prepared_meshes.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        let vertex = |x, y| VertexDescription {
+            position: (x, y, 0.0),
+            color: (1.0, 1.0, 1.0),
+            texture_coordinates: (x / 10.0, y / 10.0),
+        };
+        ecs.insert((
+            Mesh2D {
+                vertices: vec![vertex(0.0, 0.0), vertex(0.0, 10.0), vertex(10.0, 10.0)],
+                texture: "texture".into(),
+            },
+            Transform2D::default(),
+        ));
+        ecs.insert((
+            Mesh2D {
+                vertices: vec![vertex(0.0, 0.0), vertex(0.0, 10.0), vertex(10.0, 10.0)],
+                texture: "pending_texture".into(),
+            },
+            Transform2D::default(),
+        ));
+        ecs.insert((sprite(10.0, 10.0), Transform2D::default()));
+
+        render(&mut ecs);
+
+        // The pending mesh is replaced by a missing texture quad
+        assert_eq!(*draw_order.borrow(), vec!["quad", "mesh", "quad"]);
+        assert_eq!(*prepared_meshes.borrow(), vec![3]);
+    }
+
+    #[test]
+    fn draw_lines() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_lines = graphics_api.prepared_lines.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+
+        let red = Color::rgb(1.0, 0.0, 0.0);
+        let green = Color::rgb(0.0, 1.0, 0.0);
+        graphics.draw_polyline(&[(0.0, 0.0), (10.0, 0.0), (5.0, 10.0), (0.0, 0.0)], red);
+        graphics.draw_line((5.0, 5.0), (8.0, 2.0), green);
+
+        assert_eq!(
+            *prepared_lines.borrow(),
+            vec![
+                ((0.0, 0.0), (10.0, 0.0), red),
+                ((10.0, 0.0), (5.0, 10.0), red),
+                ((5.0, 10.0), (0.0, 0.0), red),
+                ((5.0, 5.0), (8.0, 2.0), green),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_lines_are_snapped_to_the_cell_size() {
+        let lines = grid_lines(32.0, (-10.0, 0.0, 100.0, 64.0));
+
+        let vertical_lines: Vec<_> = lines
+            .iter()
+            .filter(|(start, end)| start.0 == end.0)
+            .collect();
+        assert_eq!(vertical_lines.len(), 4);
+        assert_eq!(*vertical_lines[0], ((0.0, 0.0), (0.0, 64.0)));
+        assert_eq!(lines.len() - vertical_lines.len(), 3);
+        assert!(grid_lines(0.0, (0.0, 0.0, 100.0, 100.0)).is_empty());
+    }
+
+    #[test]
+    fn draw_grid_prepares_its_lines() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_lines = graphics_api.prepared_lines.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+
+        let gray = Color::rgb(0.5, 0.5, 0.5);
+        graphics.draw_grid(10.0, gray, (0.0, 0.0, 20.0, 10.0));
+
+        assert_eq!(
+            *prepared_lines.borrow(),
+            vec![
+                ((0.0, 0.0), (0.0, 10.0), gray),
+                ((10.0, 0.0), (10.0, 10.0), gray),
+                ((20.0, 0.0), (20.0, 10.0), gray),
+                ((0.0, 0.0), (20.0, 0.0), gray),
+                ((0.0, 10.0), (20.0, 10.0), gray),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_clip_rects_are_intersected() {
+        let graphics_api = MockGraphicsAPI::default();
+        let clip_rects = graphics_api.clip_rects.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let panel = ClipRect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        };
+
+        graphics.push_clip_rect(panel);
+        graphics.push_clip_rect(ClipRect {
+            x: 50.0,
+            y: 80.0,
+            width: 100.0,
+            height: 100.0,
+        });
+        graphics.pop_clip_rect();
+        graphics.pop_clip_rect();
+
+        assert_eq!(
+            *clip_rects.borrow(),
+            vec![
+                Some(panel),
+                Some(ClipRect {
+                    x: 50.0,
+                    y: 80.0,
+                    width: 50.0,
+                    height: 20.0,
+                }),
+                Some(panel),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn clipped_ui_elements_are_drawn_within_their_clip_rect() {
+        let graphics_api = MockGraphicsAPI::default();
+        let draw_order = graphics_api.draw_order.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            Frame {
+                width: 200.0,
+                height: 100.0,
+                color: Color::WHITE,
+            },
+            ClipRect {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+            },
+            Transform2D::default(),
+            NoViewTransform,
+        ));
+
+        render(&mut ecs);
+
+        assert_eq!(*draw_order.borrow(), vec!["clip", "quad", "unclip"]);
+    }
+
+    #[test]
+    fn sprites_are_drawn_by_draw_order_then_entity_id() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+
+        ecs.insert((sprite(10.0, 10.0), Transform2D::default()));
+        ecs.insert((sprite(20.0, 10.0), Transform2D::default()));
+        ecs.insert((sprite(30.0, 10.0), Transform2D::default(), DrawOrder(-1)));
+        ecs.insert((sprite(40.0, 10.0), Transform2D::default(), DrawOrder(0)));
+        render(&mut ecs);
+
+        let prepared_widths: Vec<f32> = prepared_quads
+            .borrow()
+            .iter()
+            .map(|quad| quad.width)
+            .collect();
+        assert_eq!(prepared_widths, vec![30.0, 10.0, 20.0, 40.0]);
+    }
+
+    #[test]
+    fn animated_sprites_without_keyframes_are_not_drawn() {
+        use crate::sprite::AnimationState;
+
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 16));
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        let mut animation_state = AnimationState::from_grid(16.0, 16.0, 2, 1, 2).unwrap();
+        animation_state.keyframes.clear();
+        ecs.insert((
+            AnimatedSprite {
+                width: 16.0,
+                height: 16.0,
+                texture: "texture".into(),
+                animation_state,
+            },
+            Transform2D::default(),
+        ));
+
+        render(&mut ecs);
+
+        assert!(prepared_quads.borrow().is_empty());
+    }
+
+    #[test]
+    fn switching_animation_changes_the_drawn_keyframe() {
+        use crate::sprite::{AnimationState, Animations};
+
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 16));
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        let mut run = AnimationState::from_grid(16.0, 16.0, 2, 1, 2).unwrap();
+        run.keyframes.reverse();
+        let animations = vec![
+            (
+                "idle".to_string(),
+                AnimationState::from_grid(16.0, 16.0, 2, 1, 1).unwrap(),
+            ),
+            ("run".to_string(), run),
+        ]
+        .into_iter()
+        .collect();
+        let character = ecs.insert((
+            AnimatedSprite {
+                width: 16.0,
+                height: 16.0,
+                texture: "texture".into(),
+                animation_state: AnimationState::from_grid(16.0, 16.0, 2, 1, 1).unwrap(),
+            },
+            Animations::new(animations, "idle"),
+            Transform2D::default(),
+        ));
+        let drawn_keyframe = |ecs: &mut Ecs| {
+            animation_switch_system(ecs);
+            prepared_quads.borrow_mut().clear();
+            render(ecs);
+            prepared_quads.borrow()[0].texture_region.unwrap()
+        };
+
+        assert_eq!(
+            drawn_keyframe(&mut ecs),
+            TextureRegion::new(0.0, 0.0, 0.5, 1.0)
+        );
+        {
+            let (_, (mut animations,)) =
+                ecs.query_one_by_id::<(W<Animations>,)>(character).unwrap();
+            animations.current = "run".into();
+        }
+        assert_eq!(
+            drawn_keyframe(&mut ecs),
+            TextureRegion::new(0.5, 0.0, 0.5, 1.0)
+        );
+        {
+            let (_, (mut animations,)) =
+                ecs.query_one_by_id::<(W<Animations>,)>(character).unwrap();
+            animations.current = "idle".into();
+        }
+        assert_eq!(
+            drawn_keyframe(&mut ecs),
+            TextureRegion::new(0.0, 0.0, 0.5, 1.0)
+        );
+    }
+
+    #[test]
+    fn cameras_draw_only_the_layers_of_their_mask() {
+        use crate::camera::CullingLayer;
+
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+        ecs.insert_shared_resource(graphics);
+        let main_camera = ecs.insert((
+            OrthographicCamera {
+                layer_mask: 0b01,
+                ..Default::default()
+            },
+            Active,
+            Transform2D::default(),
+        ));
+        let minimap_camera = ecs.insert((
+            OrthographicCamera {
+                layer_mask: 0b10,
+                ..Default::default()
+            },
+            Transform2D::default(),
+        ));
+        ecs.insert((sprite(10.0, 10.0), Transform2D::default()));
+        ecs.insert((sprite(20.0, 10.0), Transform2D::default(), CullingLayer(1)));
+        ecs.insert((sprite(30.0, 10.0), Transform2D::default(), CullingLayer(0)));
+        ecs.insert((sprite(40.0, 10.0), Transform2D::default(), CullingLayer(1)));
+        ecs.insert((sprite(50.0, 10.0), Transform2D::default(), CullingLayer(2)));
+        let prepared_widths = |ecs: &mut Ecs| {
+            prepared_quads.borrow_mut().clear();
+            render(ecs);
+            prepared_quads
+                .borrow()
+                .iter()
+                .map(|quad| quad.width)
+                .collect::<Vec<f32>>()
+        };
+
+        assert_eq!(prepared_widths(&mut ecs), vec![10.0, 30.0]);
+
+        ecs.remove_component::<Active>(main_camera);
+        ecs.add_component(Active, minimap_camera);
+        assert_eq!(prepared_widths(&mut ecs), vec![20.0, 40.0]);
+    }
+
+    #[test]
+    fn hidden_elements_are_not_drawn() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+
+        ecs.insert((sprite(10.0, 10.0), Transform2D::default()));
+        let hidden_sprite = ecs.insert((sprite(20.0, 10.0), Transform2D::default(), Hidden));
+        ecs.insert((rectangle(30.0, 10.0), Transform2D::default(), Hidden));
+        render(&mut ecs);
+
+        let prepared_widths: Vec<f32> = prepared_quads
+            .borrow()
+            .iter()
+            .map(|quad| quad.width)
+            .collect();
+        assert_eq!(prepared_widths, vec![10.0]);
+
+        ecs.remove_component::<Hidden>(hidden_sprite);
+        prepared_quads.borrow_mut().clear();
+        render(&mut ecs);
+        assert_eq!(prepared_quads.borrow().len(), 2);
+    }
+
+    #[test]
+    fn elements_are_drawn_by_render_layer_then_draw_order() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+
+        ecs.insert((
+            sprite(10.0, 10.0),
+            Transform2D::default(),
+            RenderLayer::Foreground,
+            DrawOrder(-1),
+        ));
+        ecs.insert((sprite(20.0, 10.0), Transform2D::default(), NoViewTransform));
+        ecs.insert((sprite(30.0, 10.0), Transform2D::default(), DrawOrder(5)));
+        ecs.insert((sprite(40.0, 10.0), Transform2D::default()));
+        ecs.insert((
+            sprite(50.0, 10.0),
+            Transform2D::default(),
+            RenderLayer::Background,
+            DrawOrder(10),
+        ));
+        ecs.insert((
+            rectangle(60.0, 10.0),
+            Transform2D::default(),
+            RenderLayer::Foreground,
+        ));
+        render(&mut ecs);
+
+        let prepared_widths: Vec<f32> = prepared_quads
+            .borrow()
+            .iter()
+            .map(|quad| quad.width)
+            .collect();
+        assert_eq!(prepared_widths, vec![50.0, 40.0, 30.0, 60.0, 10.0, 20.0]);
+    }
+
+    #[test]
+    fn sprites_without_view_transform_are_drawn_in_screen_space() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (32, 32));
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera_at(&mut ecs, (5000.0, 5000.0));
+        let transform = Transform2D {
+            translation: (10.0, 20.0),
+            ..Default::default()
+        };
+
+        ecs.insert((sprite(10.0, 10.0), transform));
+        ecs.insert((sprite(20.0, 10.0), transform, NoViewTransform));
+        render(&mut ecs);
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 1);
+        assert_eq!(prepared_quads[0].width, 20.0);
+        assert_eq!(prepared_quads[0].transform.translation, (10.0, 20.0));
+        assert!(!prepared_quads[0].apply_view_transform);
+    }
+
+    #[test]
+    fn the_topmost_entity_under_the_cursor_is_picked() {
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(MockGraphicsAPI::default()));
+        register_texture(&mut graphics, "texture", (32, 32));
+        graphics.on_window_resized(800, 600);
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera_at(&mut ecs, (100.0, 0.0));
+        let rectangle = |position, size| RectangleShape::bundle(Color::WHITE, position, size);
+
+        let (transform, rectangle_shape) = rectangle((200.0, 100.0), (100.0, 100.0));
+        let bottom = ecs.insert((transform, rectangle_shape, DrawOrder(0)));
+        let (transform, rectangle_shape) = rectangle((220.0, 120.0), (50.0, 50.0));
+        let top = ecs.insert((transform, rectangle_shape, DrawOrder(1)));
+        let (transform, rectangle_shape) = rectangle((200.0, 100.0), (100.0, 100.0));
+        ecs.insert((transform, rectangle_shape, DrawOrder(2), Hidden));
+        let (mut transform, sprite) = Sprite::bundle("texture", (200.0, 100.0), (100.0, 100.0));
+        transform.z = -1.0;
+        ecs.insert((transform, sprite));
+        let (transform, rectangle_shape) = rectangle((0.0, 0.0), (50.0, 50.0));
+        let ui = ecs.insert((transform, rectangle_shape, NoViewTransform));
+        render(&mut ecs);
+
+        let graphics = ecs.shared_resource::<Graphics>().unwrap();
+        assert_eq!(graphics.pick_entity(&ecs, (130.0, 130.0)), Some(top));
+        assert_eq!(graphics.pick_entity(&ecs, (110.0, 110.0)), Some(bottom));
+        assert_eq!(graphics.pick_entity(&ecs, (10.0, 10.0)), Some(ui));
+        assert_eq!(graphics.pick_entity(&ecs, (500.0, 500.0)), None);
+    }
+
+    #[test]
+    fn entities_of_later_layers_are_picked_whatever_their_z() {
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(MockGraphicsAPI::default()));
+        graphics.on_window_resized(800, 600);
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+
+        let foreground = ecs.insert((
+            rectangle(100.0, 100.0),
+            Transform2D {
+                z: -10.0,
+                ..Default::default()
+            },
+            RenderLayer::Foreground,
+        ));
+        ecs.insert((
+            rectangle(100.0, 100.0),
+            Transform2D {
+                z: 10.0,
+                ..Default::default()
+            },
+        ));
+        let background = ecs.insert((
+            rectangle(200.0, 200.0),
+            Transform2D {
+                z: 20.0,
+                ..Default::default()
+            },
+            RenderLayer::Background,
+        ));
+        render(&mut ecs);
+
+        let graphics = ecs.shared_resource::<Graphics>().unwrap();
+        assert_eq!(graphics.pick_entity(&ecs, (50.0, 50.0)), Some(foreground));
+        assert_eq!(graphics.pick_entity(&ecs, (150.0, 150.0)), Some(background));
+    }
+
+    #[test]
+    fn screen_positions_are_scaled_down_to_the_design_resolution() {
+        let mut ecs = Ecs::new();
+        let mut graphics = Graphics::new(Box::new(MockGraphicsAPI {
+            fixed_resolution: Some(FixedResolution::Design {
+                design_size: (320, 180),
+            }),
+            ..Default::default()
+        }));
+        graphics.on_window_resized(1280, 1024);
+        ecs.insert_shared_resource(graphics);
+        ecs.insert((
+            OrthographicCamera {
+                right: 320.0,
+                bottom: 180.0,
+                ..Default::default()
+            },
+            Active,
+            Transform2D {
+                translation: (100.0, 0.0),
+                ..Default::default()
+            },
+        ));
+        let (transform, rectangle_shape) =
+            RectangleShape::bundle(Color::WHITE, (250.0, 80.0), (20.0, 20.0));
+        let rectangle = ecs.insert((transform, rectangle_shape));
+        render(&mut ecs);
+
+        let graphics = ecs.shared_resource::<Graphics>().unwrap();
+        // The design resolution is upscaled 4 times, between bars 152 pixels high
+        assert_eq!(graphics.screen_to_world((0.0, 152.0)), Some((100.0, 0.0)));
+        assert_eq!(
+            graphics.screen_to_world((640.0, 512.0)),
+            Some((260.0, 90.0))
+        );
+        assert_eq!(graphics.pick_entity(&ecs, (640.0, 512.0)), Some(rectangle));
+        assert_eq!(graphics.pick_entity(&ecs, (640.0, 600.0)), None);
+    }
+
+    #[test]
+    fn sprites_are_drawn_with_their_blend_mode() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "light", (16, 16));
+
+        for &blend_mode in &BlendMode::ALL {
+            let sprite = Sprite {
+                texture: "light".into(),
+                blend_mode,
+                ..sprite(16.0, 16.0)
+            };
+            graphics
+                .prepare_sprite(&sprite, &Transform2D::default(), true)
+                .unwrap();
+        }
+
+        let blend_modes: Vec<BlendMode> = prepared_quads
+            .borrow()
+            .iter()
+            .map(|quad| quad.blend_mode)
+            .collect();
+        assert_eq!(blend_modes, BlendMode::ALL.to_vec());
+    }
+
+    #[test]
+    fn sprites_are_drawn_with_their_address_mode() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        register_texture(&mut graphics, "texture", (16, 16));
+
+        graphics
+            .prepare_sprite(
+                &Sprite {
+                    texture: TextureSource::TextureRegion(
+                        "texture".into(),
+                        TextureRegion::new(0.0, 0.0, 64.0, 32.0),
+                    ),
+                    address_mode: Some(TextureAddressMode::Repeat),
+                    ..sprite(64.0, 32.0)
+                },
+                &Transform2D::default(),
+                true,
+            )
+            .unwrap();
+        graphics
+            .prepare_sprite(&sprite(16.0, 16.0), &Transform2D::default(), true)
+            .unwrap();
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(
+            prepared_quads[0].address_mode,
+            Some(TextureAddressMode::Repeat)
+        );
+        assert_eq!(
+            prepared_quads[0].texture_region,
+            Some(TextureRegion::new(0.0, 0.0, 4.0, 2.0))
+        );
+        assert_eq!(prepared_quads[1].address_mode, None);
+    }
+
+    #[test]
+    fn sprites_use_the_missing_texture_until_their_texture_is_loaded() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let sprite = Sprite {
+            texture: TextureSource::TextureRegion(
+                "pending_texture".into(),
+                TextureRegion::new(0.0, 0.0, 32.0, 16.0),
+            ),
+            ..sprite(20.0, 10.0)
+        };
+
+        assert_eq!(graphics.texture_size("pending_texture"), None);
+        graphics
+            .prepare_sprite(&sprite, &Transform2D::default(), true)
+            .unwrap();
+        assert_eq!(
+            prepared_quads.borrow()[0].texture.as_deref(),
+            Some(MISSING_TEXTURE_IDENTIFIER)
+        );
+
+        register_texture(&mut graphics, "pending_texture", (64, 64));
+        assert_eq!(graphics.texture_size("pending_texture"), Some((64, 64)));
+        graphics
+            .prepare_sprite(&sprite, &Transform2D::default(), true)
+            .unwrap();
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 2);
+        assert_eq!(
+            prepared_quads[1].texture.as_deref(),
+            Some("pending_texture")
+        );
+        assert_eq!(
+            prepared_quads[1].texture_region,
+            Some(TextureRegion::new(0.0, 0.0, 0.5, 0.25))
+        );
+        assert_eq!(quad_bounds(&prepared_quads[1]), (0.0, 0.0, 20.0, 10.0));
+    }
+
+    /// Prepares a sprite, an animated sprite, a nine patch, a mesh and a texture region, all drawn
+    /// with the given texture
+    fn prepare_textured_elements(graphics: &mut Graphics, texture: &str) {
+        use crate::sprite::AnimationState;
+
+        let transform = Transform2D::default();
+        graphics
+            .prepare_sprite(
+                &Sprite {
+                    texture: texture.into(),
+                    ..sprite(20.0, 10.0)
+                },
+                &transform,
+                true,
+            )
+            .unwrap();
+        graphics
+            .prepare_animated_sprite(
+                &AnimatedSprite {
+                    width: 20.0,
+                    height: 10.0,
+                    texture: texture.into(),
+                    animation_state: AnimationState::from_grid(16.0, 16.0, 2, 1, 2).unwrap(),
+                },
+                &transform,
+                true,
+            )
+            .unwrap();
+        graphics
+            .prepare_nine_patch(
+                &NinePatch {
+                    width: 20.0,
+                    height: 10.0,
+                    texture: texture.into(),
+                    left: 2.0,
+                    right: 2.0,
+                    top: 2.0,
+                    bottom: 2.0,
+                },
+                &transform,
+                true,
+            )
+            .unwrap();
+        let vertex = |x, y| VertexDescription {
+            position: (x, y, 0.0),
+            color: (1.0, 1.0, 1.0),
+            texture_coordinates: (0.0, 0.0),
+        };
+        graphics
+            .prepare_mesh_2d(
+                &Mesh2D {
+                    vertices: vec![vertex(5.0, 0.0), vertex(5.0, 10.0), vertex(25.0, 10.0)],
+                    texture: texture.into(),
+                },
+                &transform,
+                true,
+            )
+            .unwrap();
+        graphics.draw_texture(
+            texture,
+            TextureRegion::new(0.0, 0.0, 16.0, 16.0),
+            &transform,
+            (20.0, 10.0),
+            true,
+        );
+    }
+
+    #[test]
+    fn elements_use_the_missing_texture_while_their_texture_is_loading() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let prepared_meshes = graphics_api.prepared_meshes.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+
+        prepare_textured_elements(&mut graphics, "loading_texture.png");
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 5);
+        for quad in prepared_quads.iter() {
+            assert_eq!(quad.texture.as_deref(), Some(MISSING_TEXTURE_IDENTIFIER));
+        }
+        assert_eq!(quad_bounds(&prepared_quads[3]), (5.0, 0.0, 25.0, 10.0));
+        assert!(prepared_meshes.borrow().is_empty());
+    }
+
+    #[test]
+    fn elements_use_the_missing_texture_once_their_texture_failed_to_load() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        graphics.preload_textures(&["nonexistent_texture.png"]);
+        poll_until(|| {
+            graphics.upload_loaded_textures();
+            graphics.failed_textures.contains("nonexistent_texture.png")
+        });
+
+        prepare_textured_elements(&mut graphics, "nonexistent_texture.png");
+        prepare_textured_elements(&mut graphics, "nonexistent_texture.png");
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 10);
+        for quad in prepared_quads.iter() {
+            assert_eq!(quad.texture.as_deref(), Some(MISSING_TEXTURE_IDENTIFIER));
+        }
+        assert_eq!(graphics.reported_errors.len(), 1);
+    }
+
+    #[test]
+    fn draw_texture_maps_a_source_region_to_the_destination() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let source = TextureRegion::new(32.0, 16.0, 64.0, 32.0);
+        let transform = Transform2D {
+            translation: (100.0, 50.0),
+            ..Default::default()
+        };
+
+        graphics.draw_texture("ui_atlas", source, &transform, (40.0, 20.0), false);
+        assert_eq!(
+            prepared_quads.borrow()[0].texture.as_deref(),
+            Some(MISSING_TEXTURE_IDENTIFIER)
+        );
+
+        register_texture(&mut graphics, "ui_atlas", (128, 64));
+        graphics.draw_texture("ui_atlas", source, &transform, (40.0, 20.0), false);
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 2);
+        assert_eq!(prepared_quads[1].texture.as_deref(), Some("ui_atlas"));
+        assert_eq!(
+            prepared_quads[1].texture_region,
+            Some(TextureRegion::new(0.25, 0.25, 0.5, 0.5))
+        );
+        assert_eq!(quad_bounds(&prepared_quads[1]), (100.0, 50.0, 140.0, 70.0));
+    }
+
+    #[test]
+    fn texture_region_sprites_are_drawn_once_their_texture_file_is_loaded() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let texture_path = std::env::temp_dir().join("tuber_graphics_region_texture.png");
+        let texture = texture_path.to_str().unwrap().to_owned();
+        image::RgbaImage::new(64, 32).save(&texture).unwrap();
+
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            Sprite {
+                texture: TextureSource::TextureRegion(
+                    texture.clone(),
+                    TextureRegion::new(16.0, 8.0, 16.0, 16.0),
+                ),
+                ..sprite(20.0, 10.0)
+            },
+            Transform2D::default(),
+        ));
+
+        poll_until(|| {
+            render(&mut ecs);
+            prepared_quads.borrow().last().unwrap().texture.as_deref()
+                != Some(MISSING_TEXTURE_IDENTIFIER)
+        });
+
+        let prepared_quads = prepared_quads.borrow();
+        let prepared_quad = prepared_quads.last().unwrap();
+        assert_eq!(prepared_quad.texture.as_deref(), Some(texture.as_str()));
+        assert_eq!(
+            prepared_quad.texture_region,
+            Some(TextureRegion::new(0.25, 0.25, 0.25, 0.5))
+        );
+        std::fs::remove_file(texture).unwrap();
+    }
+
+    #[test]
+    fn off_screen_quads_are_not_prepared() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        insert_active_camera(&mut ecs);
+        let at = |x, y| Transform2D {
+            translation: (x, y),
+            ..Default::default()
+        };
+
+        ecs.insert((rectangle(20.0, 10.0), at(100.0, 100.0)));
+        ecs.insert((rectangle(20.0, 10.0), at(790.0, 595.0)));
+        ecs.insert((rectangle(20.0, 10.0), at(900.0, 100.0)));
+        ecs.insert((rectangle(20.0, 10.0), at(100.0, -20.0)));
+        ecs.insert((
+            rectangle(200.0, 10.0),
+            Transform2D {
+                translation: (-195.0, 300.0),
+                angle: 90.0,
+                rotation_center: (100.0, 5.0),
+                ..Default::default()
+            },
+        ));
+        ecs.insert((sprite(20.0, 10.0), at(-500.0, -500.0)));
+
+        render(&mut ecs);
+
+        let mut prepared_lefts: Vec<f32> = prepared_quads
+            .borrow()
+            .iter()
+            .map(|quad| quad_bounds(quad).0)
+            .collect();
+        prepared_lefts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(prepared_lefts, vec![100.0, 790.0]);
+    }
+
+    #[test]
+    fn visible_world_bounds_shift_with_the_camera() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_lines = graphics_api.prepared_lines.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        assert_eq!(
+            ecs.shared_resource::<Graphics>()
+                .unwrap()
+                .visible_world_bounds(),
+            None
+        );
+
+        insert_active_camera_at(&mut ecs, (200.0, -100.0));
+        render(&mut ecs);
+
+        let mut graphics = ecs.shared_resource_mut::<Graphics>().unwrap();
+        assert_eq!(
+            graphics.visible_world_bounds(),
+            Some((200.0, -100.0, 1000.0, 500.0))
+        );
+
+        graphics.draw_grid(100.0, Color::WHITE, (0.0, 0.0, 400.0, 400.0));
+        let vertical_lines = 3;
+        let horizontal_lines = 5;
+        assert_eq!(
+            prepared_lines.borrow().len(),
+            vertical_lines + horizontal_lines
+        );
+    }
+
+    #[test]
+    fn tilemap_layers_are_drawn_in_order_around_shapes() {
+        let graphics_api = MockGraphicsAPI::default();
+        let draw_order = graphics_api.draw_order.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        graphics.texture_atlases.insert(
+            "atlas".into(),
+            TextureAtlas {
+                texture_identifier: "texture".into(),
+                textures: HashMap::new(),
+            },
+        );
+        register_texture(&mut graphics, "texture", (32, 32));
+
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        for (identifier, layer) in &[("foreground", 1), ("background", -1), ("ground", 0)] {
+            ecs.insert((
+                Tilemap::new(2, 2, 16, 16, &[]),
+                TilemapRender {
+                    identifier: identifier.to_string(),
+                    texture_atlas_identifier: "atlas".into(),
+                    tile_texture_function: Box::new(|_| None),
+                    layer: *layer,
+                    dirty: true,
+                    dirty_tiles: Default::default(),
+                    animations: Default::default(),
+                    animation_time: 0.0,
+                    texel_inset: false,
+                    tint: Color::WHITE,
+                    tile_tint_function: None,
+                    projection: TilemapProjection::Orthogonal,
+                },
+                Transform2D::default(),
+            ));
+        }
+        ecs.insert((rectangle(10.0, 10.0), Transform2D::default()));
+
+        render(&mut ecs);
+
+        assert_eq!(
+            *draw_order.borrow(),
+            vec!["background", "ground", "quad", "foreground"]
+        );
+        for (_, (tilemap_render,)) in ecs.query::<(R<TilemapRender>,)>() {
+            assert!(!tilemap_render.dirty);
+        }
+    }
+
+    #[test]
+    fn tilemaps_are_submitted_once_their_atlas_texture_is_loaded() {
+        let graphics_api = MockGraphicsAPI::default();
+        let draw_order = graphics_api.draw_order.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        graphics.texture_atlases.insert(
+            "atlas".into(),
+            TextureAtlas {
+                texture_identifier: "texture".into(),
+                textures: HashMap::new(),
+            },
+        );
+
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            Tilemap::new(2, 2, 16, 16, &[]),
+            TilemapRender {
+                identifier: "tilemap".into(),
+                texture_atlas_identifier: "atlas".into(),
+                tile_texture_function: Box::new(|_| None),
+                layer: 0,
+                dirty: true,
+                dirty_tiles: Default::default(),
+                animations: Default::default(),
+                animation_time: 0.0,
+                texel_inset: false,
+                tint: Color::WHITE,
+                tile_tint_function: None,
+                projection: TilemapProjection::Orthogonal,
+            },
+            Transform2D::default(),
+        ));
+
+        render(&mut ecs);
+        assert!(draw_order.borrow().is_empty());
+
+        register_texture(
+            &mut ecs.shared_resource_mut::<Graphics>().unwrap(),
+            "texture",
+            (32, 32),
+        );
+        render(&mut ecs);
+        assert_eq!(*draw_order.borrow(), vec!["tilemap"]);
+    }
+
+    #[test]
+    fn sprites_with_a_missing_atlas_use_the_missing_texture() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            Sprite {
+                anchor: (0.5, 0.5),
+                texture: TextureSource::TextureAtlas(
+                    "nonexistent_atlas.json".into(),
+                    "sprite".into(),
+                ),
+                ..sprite(20.0, 10.0)
+            },
+            Transform2D {
+                translation: (100.0, 50.0),
+                ..Default::default()
+            },
+        ));
+
+        render(&mut ecs);
+        render(&mut ecs);
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 2);
+        assert_eq!(
+            prepared_quads[0].texture.as_deref(),
+            Some(MISSING_TEXTURE_IDENTIFIER)
+        );
+        assert_eq!(quad_bounds(&prepared_quads[0]), (90.0, 45.0, 110.0, 55.0));
+        assert_eq!(
+            ecs.shared_resource::<Graphics>()
+                .unwrap()
+                .reported_errors
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn text_with_missing_glyphs_skips_them() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let font: BitmapFont = r#"
+        {
+            "font_atlas_path": "font_atlas",
+            "font_atlas_region": { "x": 0, "y": 0, "width": 64, "height": 32 },
+            "line_height": 32,
+            "line_spacing": 4,
+            "ignore_case": false,
+            "letter_spacing": 2,
+            "glyphs": {
+                "A": { "region": { "x": 0, "y": 0, "width": 32, "height": 32 } }
+            }
+        }
+        "#
+        .parse()
+        .unwrap();
+        graphics.fonts.insert("font".into(), font);
+        graphics.texture_atlases.insert(
+            "font_atlas".into(),
+            TextureAtlas {
+                texture_identifier: "font_texture".into(),
+                textures: vec![("font".to_string(), TextureRegion::new(0.0, 0.0, 64.0, 32.0))]
+                    .into_iter()
+                    .collect(),
+            },
+        );
+        graphics.texture_metadata.insert(
+            "font_texture".into(),
+            TextureMetadata {
+                width: 64,
+                height: 32,
+            },
+        );
+
+        graphics
+            .prepare_text("AéA", "font", Color::WHITE, &Transform2D::default(), true)
+            .unwrap();
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 2);
+        assert_eq!(prepared_quads[1].transform.translation, (34.0, 0.0));
+    }
+
+    #[test]
+    fn missing_atlas_regions_are_reported_once_and_use_the_missing_texture() {
+        let graphics_api = MockGraphicsAPI::default();
+        let prepared_quads = graphics_api.prepared_quads.clone();
+        let mut graphics = Graphics::new(Box::new(graphics_api));
+        let font: BitmapFont = r#"
+        {
+            "font_atlas_path": "atlas",
+            "font_atlas_region": { "x": 0, "y": 0, "width": 64, "height": 32 },
+            "line_height": 32,
+            "line_spacing": 4,
+            "ignore_case": false,
+            "letter_spacing": 2,
+            "glyphs": {
+                "A": { "region": { "x": 0, "y": 0, "width": 32, "height": 32 } }
+            }
+        }
+        "#
+        .parse()
+        .unwrap();
+        graphics.fonts.insert("font".into(), font);
+        // Uploaded so that registering the atlas doesn't start loading a missing file, whose
+        // error would be reported if the loader finished before a render
+        graphics.upload_texture(TextureData {
+            identifier: "atlas_texture".into(),
+            size: (64, 32),
+            bytes: vec![255; 64 * 32 * 4],
+            filter: Default::default(),
+            address_mode: Default::default(),
+        });
+        graphics.register_texture_atlas(
+            "atlas",
+            TextureAtlas {
+                texture_identifier: "atlas_texture".into(),
+                textures: HashMap::new(),
+            },
+        );
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(graphics);
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            Sprite {
+                texture: TextureSource::TextureAtlas("atlas".into(), "sprite".into()),
+                ..sprite(20.0, 10.0)
+            },
+            Transform2D::default(),
+        ));
+        ecs.insert(Text::bundle("A", "font", (0.0, 0.0)));
+
+        render(&mut ecs);
+        render(&mut ecs);
+
+        let prepared_quads = prepared_quads.borrow();
+        assert_eq!(prepared_quads.len(), 4);
+        for quad in prepared_quads.iter() {
+            assert_eq!(quad.texture.as_deref(), Some(MISSING_TEXTURE_IDENTIFIER));
+        }
+        assert_eq!(quad_bounds(&prepared_quads[1]), (0.0, 0.0, 32.0, 32.0));
+        assert_eq!(
+            ecs.shared_resource::<Graphics>()
+                .unwrap()
+                .reported_errors
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn ui_elements_are_drawn_over_world_quads_of_greater_z() {
+        let graphics_api = MockGraphicsAPI::default();
+        let draw_order = graphics_api.draw_order.clone();
+        let depth_clears = graphics_api.depth_clears.clone();
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(Graphics::new(Box::new(graphics_api)));
+        insert_active_camera(&mut ecs);
+        ecs.insert((
+            rectangle(10.0, 10.0),
+            Transform2D {
+                z: 5.0,
+                ..Default::default()
+            },
+        ));
+        ecs.insert((
+            Frame {
+                width: 10.0,
+                height: 10.0,
+                color: Color::WHITE,
+            },
+            Transform2D::default(),
+            NoViewTransform,
+        ));
+
+        render(&mut ecs);
+
+        assert_eq!(*draw_order.borrow(), vec!["quad", "quad"]);
+        // The quad is drawn in the world pass of the World layer, the frame in the UI pass of the
+        // UI layer, with the depth cleared before every pass and before the draws made until end_frame
+        assert_eq!(*depth_clears.borrow(), vec![0, 0, 0, 1, 1, 1, 1, 1, 2]);
+    }
 }
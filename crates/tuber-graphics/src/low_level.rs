@@ -1,3 +1,4 @@
+use crate::design_resolution::FixedResolution;
 use crate::*;
 
 /// The low level API
@@ -15,6 +16,15 @@ pub trait LowLevelGraphicsAPI {
         apply_view_transform: bool,
         bounding_box_rendering: bool,
     );
+    /// Prepares the render of a line in world coordinates
+    fn prepare_line(&mut self, start: (f32, f32), end: (f32, f32), color: Color);
+    /// Prepares the render of a mesh, every three vertices forming a triangle
+    fn prepare_mesh(
+        &mut self,
+        mesh_description: &MeshDescription,
+        transform: &Transform2D,
+        apply_view_transform: bool,
+    );
     fn prepare_tilemap(
         &mut self,
         tilemap: &Tilemap,
@@ -25,6 +35,8 @@ pub trait LowLevelGraphicsAPI {
     fn is_texture_in_memory(&self, texture_identifier: &str) -> bool;
     /// Loads a texture in memory
     fn load_texture(&mut self, texture_data: TextureData);
+    /// Frees a texture from memory
+    fn unload_texture(&mut self, texture_identifier: &str);
     /// Updates the view/projection matrix
     fn update_camera(
         &mut self,
@@ -32,16 +44,52 @@ pub trait LowLevelGraphicsAPI {
         camera: &OrthographicCamera,
         transform: &Transform2D,
     );
+    /// Draws everything prepared next over what was prepared before, whatever their depth
+    fn clear_depth(&mut self);
+
+    /// Restricts the following draws to a rectangle in screen coordinates, `None` lifts the restriction
+    fn set_clip_rect(&mut self, clip_rect: Option<ClipRect>);
 
     fn set_clear_color(&mut self, color: Color);
+    /// Sets the color of the bounding boxes drawn around quads
+    fn set_bounding_box_color(&mut self, color: Color);
+    /// Draws quads and tilemaps as wireframes, if the device supports it
+    fn set_wireframe_rendering(&mut self, enabled: bool);
+    /// Rounds the camera translation to whole pixels to avoid seams between tiles
+    fn set_pixel_snapping(&mut self, enabled: bool);
     fn on_window_resized(&mut self, size: WindowSize);
+    /// Returns the fixed resolution the scene is rendered at before being scaled into the
+    /// window, `None` if it is rendered at the window resolution
+    fn fixed_resolution(&self) -> Option<FixedResolution>;
+    /// Returns the statistics of the last rendered frame
+    fn last_frame_stats(&self) -> RenderStats;
+}
+
+/// Counters of the work submitted to the GPU during a frame
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct RenderStats {
+    /// The number of draw calls issued
+    pub draw_calls: usize,
+    /// The number of quads drawn
+    pub quads: usize,
+    /// The number of vertices drawn
+    pub vertices: usize,
+}
+
+impl RenderStats {
+    /// Records a draw call of the given number of vertices and instances
+    pub fn record_draw(&mut self, vertex_count: usize, instance_count: usize) {
+        self.draw_calls += 1;
+        self.vertices += vertex_count * instance_count;
+    }
 }
 
 /// Describes a vertex for the low-level renderer
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct VertexDescription {
-    /// The position in Normalized Device Coordinates
+    /// The position, relative to the transform of the mesh
     pub position: (f32, f32, f32),
-    /// The color of the vertex
+    /// The color of the vertex, multiplied with the texture
     pub color: (f32, f32, f32),
     /// The normalized texture coordinates of the vertex
     pub texture_coordinates: (f32, f32),
@@ -52,6 +100,8 @@ pub struct TextureDescription {
     pub identifier: String,
     /// The region of the texture to use
     pub texture_region: TextureRegion,
+    /// How the texture is sampled outside of its bounds, the address mode of the texture if `None`
+    pub address_mode: Option<TextureAddressMode>,
 }
 
 /// Describes a quad for the low-level renderer
@@ -64,12 +114,43 @@ pub struct QuadDescription {
     pub color: Color,
     /// The texture of the quad
     pub texture: Option<TextureDescription>,
+    /// The radius of the rounded corners of an untextured quad
+    pub corner_radius: f32,
+    /// The thickness of the outline of an untextured quad with rounded corners, 0 for a filled quad
+    pub outline_thickness: f32,
+    /// How the quad is blended with what is drawn behind it
+    pub blend_mode: BlendMode,
 }
 
-/// Describes a mesh for the low-leven renderer
+/// How a draw is combined with the colors already in the framebuffer
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Blends using the alpha of the drawn color
+    #[default]
+    Alpha,
+    /// Adds the drawn color weighted by its alpha, for lights and particles
+    Additive,
+    /// Multiplies the colors, for shadows and tinting
+    Multiply,
+    /// Replaces the colors, ignoring the alpha
+    Opaque,
+}
+
+impl BlendMode {
+    /// Every blend mode
+    pub const ALL: [BlendMode; 4] = [
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Opaque,
+    ];
+}
+
+/// Describes a mesh for the low-level renderer
 pub struct MeshDescription {
-    /// The vertices of the mesh
+    /// The vertices of the mesh, every three of them forming a triangle
     pub vertices: Vec<VertexDescription>,
+    /// The texture of the mesh, the texture coordinates of the vertices being relative to its region
     pub texture: TextureDescription,
 }
 
@@ -79,3 +160,26 @@ pub struct TilemapDescription {
 }
 
 pub struct TileDescription;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_draws_accumulate_the_vertices_of_every_instance() {
+        let mut frame_stats = RenderStats::default();
+
+        frame_stats.record_draw(6, 1);
+        frame_stats.record_draw(4, 3);
+        frame_stats.record_draw(0, 1);
+
+        assert_eq!(
+            frame_stats,
+            RenderStats {
+                draw_calls: 3,
+                quads: 0,
+                vertices: 18,
+            }
+        );
+    }
+}
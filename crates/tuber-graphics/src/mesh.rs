@@ -0,0 +1,30 @@
+use crate::low_level::VertexDescription;
+use crate::texture::TextureSource;
+
+/// A textured mesh drawn relative to its transform, every three vertices forming a triangle
+pub struct Mesh2D {
+    pub vertices: Vec<VertexDescription>,
+    /// The texture coordinates of the vertices are relative to the region of this texture
+    pub texture: TextureSource,
+}
+
+impl Mesh2D {
+    /// Returns the (left, top, right, bottom) bounds of the vertices, `None` without vertices
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        if self.vertices.is_empty() {
+            return None;
+        }
+
+        Some(self.vertices.iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(left, top, right, bottom), vertex| {
+                (
+                    left.min(vertex.position.0),
+                    top.min(vertex.position.1),
+                    right.max(vertex.position.0),
+                    bottom.max(vertex.position.1),
+                )
+            },
+        ))
+    }
+}
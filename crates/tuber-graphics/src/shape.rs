@@ -1,7 +1,43 @@
 use crate::Color;
+use tuber_common::transform::Transform2D;
 
 pub struct RectangleShape {
     pub width: f32,
     pub height: f32,
+    /// The normalized point of the rectangle placed at its translation, (0, 0) is the top-left corner
+    pub anchor: (f32, f32),
     pub color: Color,
+    /// The radius of the rounded corners, 0 for sharp corners
+    pub corner_radius: f32,
+}
+
+impl RectangleShape {
+    /// Returns the components of a rectangle whose top-left corner is at the given position
+    pub fn bundle(
+        color: Color,
+        position: (f32, f32),
+        size: (f32, f32),
+    ) -> (Transform2D, RectangleShape) {
+        (
+            Transform2D {
+                translation: position,
+                ..Default::default()
+            },
+            RectangleShape {
+                width: size.0,
+                height: size.1,
+                anchor: (0.0, 0.0),
+                color,
+                corner_radius: 0.0,
+            },
+        )
+    }
+}
+
+/// A circle centered on its translation
+pub struct CircleShape {
+    pub radius: f32,
+    pub color: Color,
+    /// The thickness of the outline, the circle is filled if `None`
+    pub thickness: Option<f32>,
 }
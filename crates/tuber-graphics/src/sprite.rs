@@ -1,12 +1,48 @@
-use crate::texture::{TextureRegion, TextureSource};
-use std::time::Instant;
+use crate::low_level::BlendMode;
+use crate::texture::{TextureAddressMode, TextureAtlas, TextureRegion, TextureSource};
+use std::collections::HashMap;
+use tuber_common::time::DeltaTime;
+use tuber_common::transform::Transform2D;
 use tuber_ecs::ecs::Ecs;
 use tuber_ecs::query::accessors::W;
 
 pub struct Sprite {
     pub width: f32,
     pub height: f32,
+    /// The normalized point of the sprite placed at its translation, (0, 0) is the top-left corner
+    pub anchor: (f32, f32),
     pub texture: TextureSource,
+    pub blend_mode: BlendMode,
+    /// How the texture is sampled outside of its bounds, the address mode set with
+    /// [`crate::Graphics::set_texture_address_mode`] if `None`
+    ///
+    /// Combined with a [`TextureSource::TextureRegion`] larger than the texture,
+    /// [`TextureAddressMode::Repeat`] tiles the texture over the sprite.
+    pub address_mode: Option<TextureAddressMode>,
+}
+
+impl Sprite {
+    /// Returns the components of a sprite whose top-left corner is at the given position
+    pub fn bundle(
+        texture: impl Into<TextureSource>,
+        position: (f32, f32),
+        size: (f32, f32),
+    ) -> (Transform2D, Sprite) {
+        (
+            Transform2D {
+                translation: position,
+                ..Default::default()
+            },
+            Sprite {
+                width: size.0,
+                height: size.1,
+                anchor: (0.0, 0.0),
+                texture: texture.into(),
+                blend_mode: BlendMode::Alpha,
+                address_mode: None,
+            },
+        )
+    }
 }
 
 pub struct AnimatedSprite {
@@ -16,20 +52,466 @@ pub struct AnimatedSprite {
     pub animation_state: AnimationState,
 }
 
+#[derive(Clone)]
 pub struct AnimationState {
     pub keyframes: Vec<TextureRegion>,
     pub current_keyframe: usize,
-    pub start_instant: Instant,
+    /// Time elapsed since the start of the animation in milliseconds
+    pub elapsed: f64,
+    /// Duration of a keyframe in milliseconds
     pub frame_duration: u32,
+    /// Duration of each keyframe in milliseconds, replacing `frame_duration` when set
+    pub keyframe_durations: Option<Vec<u32>>,
+    /// Multiplier applied to the elapsed time, 2.0 plays the animation twice as fast
+    pub speed: f32,
     pub flip_x: bool,
+    /// Whether the animation starts over once its last keyframe is done
+    pub looping: bool,
+    /// Set once a non-looping animation has played its last keyframe
+    pub finished: bool,
+}
+
+impl AnimationState {
+    /// The keyframe duration of the generated animations in milliseconds
+    const DEFAULT_FRAME_DURATION: u32 = 100;
+
+    fn new(keyframes: Vec<TextureRegion>) -> Self {
+        Self {
+            keyframes,
+            current_keyframe: 0,
+            elapsed: 0.0,
+            frame_duration: Self::DEFAULT_FRAME_DURATION,
+            keyframe_durations: None,
+            speed: 1.0,
+            flip_x: false,
+            looping: true,
+            finished: false,
+        }
+    }
+
+    /// Creates a looping animation from the frames of a uniform sprite sheet, read row by row
+    ///
+    /// Returns `None` if the animation would have no frame.
+    pub fn from_grid(
+        frame_width: f32,
+        frame_height: f32,
+        columns: usize,
+        rows: usize,
+        frame_count: usize,
+    ) -> Option<Self> {
+        let keyframes: Vec<TextureRegion> = (0..frame_count.min(columns * rows))
+            .map(|frame| {
+                TextureRegion::new(
+                    (frame % columns) as f32 * frame_width,
+                    (frame / columns) as f32 * frame_height,
+                    frame_width,
+                    frame_height,
+                )
+            })
+            .collect();
+        if keyframes.is_empty() {
+            return None;
+        }
+
+        Some(Self::new(keyframes))
+    }
+
+    /// Creates a looping animation from named regions of an atlas
+    ///
+    /// Returns `None` if there is no region name or if a region is missing from the atlas.
+    pub fn from_atlas(texture_atlas: &TextureAtlas, region_names: &[&str]) -> Option<Self> {
+        if region_names.is_empty() {
+            return None;
+        }
+
+        let keyframes = region_names
+            .iter()
+            .map(|region_name| texture_atlas.texture_region(region_name))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self::new(keyframes))
+    }
+
+    /// Creates a looping animation from keyframes each lasting the given milliseconds
+    ///
+    /// Returns `None` if there is no keyframe.
+    pub fn from_timed_keyframes(keyframes: Vec<(TextureRegion, u32)>) -> Option<Self> {
+        if keyframes.is_empty() {
+            return None;
+        }
+
+        let (keyframes, keyframe_durations) = keyframes.into_iter().unzip();
+        Some(Self {
+            keyframe_durations: Some(keyframe_durations),
+            ..Self::new(keyframes)
+        })
+    }
+
+    /// Returns the number of keyframes fully played after the given elapsed milliseconds,
+    /// counting the ones of the previous loops
+    fn elapsed_keyframes(&self, elapsed: f64) -> usize {
+        let keyframe_durations = match &self.keyframe_durations {
+            Some(keyframe_durations) => keyframe_durations,
+            None => return (elapsed / self.frame_duration as f64) as usize,
+        };
+        let animation_duration: u32 = keyframe_durations.iter().sum();
+        if animation_duration == 0 {
+            return 0;
+        }
+
+        let loops = (elapsed / animation_duration as f64) as usize;
+        let mut remaining = elapsed - (loops * animation_duration as usize) as f64;
+        let mut elapsed_keyframes = loops * keyframe_durations.len();
+        for &keyframe_duration in keyframe_durations {
+            if remaining < keyframe_duration as f64 {
+                break;
+            }
+
+            remaining -= keyframe_duration as f64;
+            elapsed_keyframes += 1;
+        }
+
+        elapsed_keyframes
+    }
+}
+
+/// Named animations of an `AnimatedSprite`, of which the `current` one is played
+///
+/// The `AnimatedSprite` plays a copy of the current animation, `animations` keeping every
+/// animation in its initial state.
+pub struct Animations {
+    pub animations: HashMap<String, AnimationState>,
+    /// The name of the animation to play, it restarts from its first keyframe when switched to
+    pub current: String,
+    /// The name of the animation currently in the `AnimatedSprite`
+    playing: Option<String>,
+}
+
+impl Animations {
+    pub fn new(animations: HashMap<String, AnimationState>, current: &str) -> Self {
+        Self {
+            animations,
+            current: current.to_owned(),
+            playing: None,
+        }
+    }
+}
+
+/// Puts the current animation of the `Animations` in their `AnimatedSprite`
+///
+/// Switching to an unknown animation keeps the previous one playing.
+pub fn animation_switch_system(ecs: &mut Ecs) {
+    for (_, (mut animations, mut animated_sprite)) in
+        ecs.query::<(W<Animations>, W<AnimatedSprite>)>()
+    {
+        if animations.playing.as_ref() == Some(&animations.current) {
+            continue;
+        }
+
+        let current = animations.current.clone();
+        let mut animation_state = match animations.animations.get(&current) {
+            Some(animation_state) => animation_state.clone(),
+            None => continue,
+        };
+        animation_state.current_keyframe = 0;
+        animation_state.elapsed = 0.0;
+        animation_state.finished = false;
+
+        animated_sprite.animation_state = animation_state;
+        animations.playing = Some(current);
+    }
 }
 
 pub fn sprite_animation_step_system(ecs: &mut Ecs) {
+    let DeltaTime(delta_time) = *ecs
+        .shared_resource::<DeltaTime>()
+        .expect("DeltaTime resource not found");
+
     for (_, (mut animated_sprite,)) in ecs.query::<(W<AnimatedSprite>,)>() {
         let mut animation_state = &mut animated_sprite.animation_state;
-        animation_state.current_keyframe = ((animation_state.start_instant.elapsed().as_millis()
-            / animation_state.frame_duration as u128)
-            % animation_state.keyframes.len() as u128)
-            as usize
+        // An animation without keyframes has nothing to step through
+        if animation_state.finished || animation_state.keyframes.is_empty() {
+            continue;
+        }
+
+        animation_state.elapsed += delta_time * 1000.0 * animation_state.speed as f64;
+        let elapsed_frames = animation_state.elapsed_keyframes(animation_state.elapsed);
+        let keyframe_count = animation_state.keyframes.len();
+
+        if animation_state.looping {
+            animation_state.current_keyframe = elapsed_frames % keyframe_count;
+        } else if elapsed_frames >= keyframe_count {
+            animation_state.current_keyframe = keyframe_count - 1;
+            animation_state.finished = true;
+        } else {
+            animation_state.current_keyframe = elapsed_frames;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuber_ecs::query::accessors::R;
+
+    fn animated_sprite(looping: bool, speed: f32) -> AnimatedSprite {
+        AnimatedSprite {
+            width: 16.0,
+            height: 16.0,
+            texture: "texture".into(),
+            animation_state: AnimationState {
+                keyframes: vec![
+                    TextureRegion::new(0.0, 0.0, 16.0, 16.0),
+                    TextureRegion::new(16.0, 0.0, 16.0, 16.0),
+                    TextureRegion::new(32.0, 0.0, 16.0, 16.0),
+                ],
+                current_keyframe: 0,
+                elapsed: 0.0,
+                frame_duration: 100,
+                keyframe_durations: None,
+                speed,
+                flip_x: false,
+                looping,
+                finished: false,
+            },
+        }
+    }
+
+    fn step(ecs: &mut Ecs, delta_time: f64) {
+        ecs.insert_shared_resource(DeltaTime(delta_time));
+        sprite_animation_step_system(ecs);
+    }
+
+    fn current_keyframe(ecs: &Ecs) -> usize {
+        let (_, (animated_sprite,)) = ecs.query_one::<(R<AnimatedSprite>,)>().unwrap();
+        animated_sprite.animation_state.current_keyframe
+    }
+
+    #[test]
+    fn sprite_bundle_can_be_inserted() {
+        let mut ecs = Ecs::new();
+        let (transform, sprite) = Sprite::bundle("texture", (10.0, 20.0), (32.0, 16.0));
+        let id = ecs.insert((transform, sprite, crate::DrawOrder(2)));
+
+        let (_, (transform, sprite, _)) = ecs
+            .query_one_by_id::<(R<Transform2D>, R<Sprite>, R<crate::DrawOrder>)>(id)
+            .unwrap();
+        assert_eq!(transform.translation, (10.0, 20.0));
+        assert_eq!((sprite.width, sprite.height), (32.0, 16.0));
+        assert!(
+            matches!(&sprite.texture, TextureSource::WholeTexture(texture) if texture == "texture")
+        );
+    }
+
+    #[test]
+    fn switching_animation_keeps_every_animation() {
+        let mut ecs = Ecs::new();
+        let animations = vec![
+            (
+                "idle".to_string(),
+                animated_sprite(true, 1.0).animation_state,
+            ),
+            (
+                "run".to_string(),
+                animated_sprite(true, 2.0).animation_state,
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let id = ecs.insert((
+            animated_sprite(true, 1.0),
+            Animations::new(animations, "run"),
+        ));
+
+        animation_switch_system(&mut ecs);
+        step(&mut ecs, 0.1);
+
+        let (_, (animated_sprite, animations)) = ecs
+            .query_one_by_id::<(R<AnimatedSprite>, R<Animations>)>(id)
+            .unwrap();
+        assert_eq!(animated_sprite.animation_state.speed, 2.0);
+        assert_eq!(animated_sprite.animation_state.current_keyframe, 2);
+        assert_eq!(animations.animations.len(), 2);
+        assert_eq!(animations.animations["run"].current_keyframe, 0);
+        assert_eq!(animations.animations["run"].elapsed, 0.0);
+    }
+
+    #[test]
+    fn looping_animation_wraps_around() {
+        let mut ecs = Ecs::new();
+        ecs.insert((animated_sprite(true, 1.0),));
+
+        step(&mut ecs, 0.35);
+
+        let (_, (animated_sprite,)) = ecs.query_one::<(R<AnimatedSprite>,)>().unwrap();
+        assert_eq!(animated_sprite.animation_state.current_keyframe, 0);
+        assert!(!animated_sprite.animation_state.finished);
+    }
+
+    #[test]
+    fn fixed_steps_change_keyframes_at_exact_frame_boundaries() {
+        let mut ecs = Ecs::new();
+        ecs.insert((animated_sprite(true, 1.0),));
+
+        let keyframes: Vec<usize> = (0..35)
+            .map(|_| {
+                step(&mut ecs, 0.01);
+                current_keyframe(&ecs)
+            })
+            .collect();
+
+        let transitions: Vec<(usize, usize)> = keyframes
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] != pair[1])
+            .map(|(step_index, pair)| (step_index + 2, pair[1]))
+            .collect();
+        assert_eq!(transitions, vec![(10, 1), (20, 2), (30, 0)]);
+    }
+
+    #[test]
+    fn non_looping_animation_holds_last_keyframe() {
+        let mut ecs = Ecs::new();
+        ecs.insert((animated_sprite(false, 1.0),));
+
+        step(&mut ecs, 0.15);
+        {
+            let (_, (animated_sprite,)) = ecs.query_one::<(R<AnimatedSprite>,)>().unwrap();
+            assert_eq!(animated_sprite.animation_state.current_keyframe, 1);
+            assert!(!animated_sprite.animation_state.finished);
+        }
+
+        step(&mut ecs, 1.0);
+        step(&mut ecs, 1.0);
+
+        let (_, (animated_sprite,)) = ecs.query_one::<(R<AnimatedSprite>,)>().unwrap();
+        assert_eq!(animated_sprite.animation_state.current_keyframe, 2);
+        assert!(animated_sprite.animation_state.finished);
+    }
+
+    #[test]
+    fn animation_speed_scales_keyframe_advancement() {
+        let mut normal_speed_ecs = Ecs::new();
+        normal_speed_ecs.insert((animated_sprite(true, 1.0),));
+        let mut double_speed_ecs = Ecs::new();
+        double_speed_ecs.insert((animated_sprite(true, 2.0),));
+
+        step(&mut normal_speed_ecs, 0.1);
+        step(&mut double_speed_ecs, 0.1);
+        assert_eq!(current_keyframe(&normal_speed_ecs), 1);
+        assert_eq!(current_keyframe(&double_speed_ecs), 2);
+
+        let mut paused_ecs = Ecs::new();
+        paused_ecs.insert((animated_sprite(true, 0.0),));
+        step(&mut paused_ecs, 0.25);
+        assert_eq!(current_keyframe(&paused_ecs), 0);
+    }
+
+    #[test]
+    fn keyframes_last_their_own_duration() {
+        let mut ecs = Ecs::new();
+        ecs.insert((AnimatedSprite {
+            width: 16.0,
+            height: 16.0,
+            texture: "texture".into(),
+            animation_state: AnimationState::from_timed_keyframes(vec![
+                (TextureRegion::new(0.0, 0.0, 16.0, 16.0), 50),
+                (TextureRegion::new(16.0, 0.0, 16.0, 16.0), 200),
+                (TextureRegion::new(32.0, 0.0, 16.0, 16.0), 100),
+            ])
+            .unwrap(),
+        },));
+
+        let keyframes: Vec<usize> = [0.04, 0.02, 0.15, 0.04, 0.1, 0.05]
+            .iter()
+            .map(|&delta_time| {
+                step(&mut ecs, delta_time);
+                current_keyframe(&ecs)
+            })
+            .collect();
+
+        assert_eq!(keyframes, vec![0, 1, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn from_grid_reads_frames_row_by_row() {
+        let animation_state = AnimationState::from_grid(16.0, 8.0, 4, 2, 6).unwrap();
+
+        assert_eq!(
+            animation_state.keyframes,
+            vec![
+                TextureRegion::new(0.0, 0.0, 16.0, 8.0),
+                TextureRegion::new(16.0, 0.0, 16.0, 8.0),
+                TextureRegion::new(32.0, 0.0, 16.0, 8.0),
+                TextureRegion::new(48.0, 0.0, 16.0, 8.0),
+                TextureRegion::new(0.0, 8.0, 16.0, 8.0),
+                TextureRegion::new(16.0, 8.0, 16.0, 8.0),
+            ]
+        );
+        assert_eq!(
+            AnimationState::from_grid(16.0, 8.0, 4, 1, 6)
+                .unwrap()
+                .keyframes
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn from_atlas_uses_the_named_regions() {
+        let texture_atlas = TextureAtlas {
+            texture_identifier: "texture".into(),
+            textures: vec![
+                (
+                    "walk_1".to_string(),
+                    TextureRegion::new(0.0, 0.0, 32.0, 32.0),
+                ),
+                (
+                    "walk_2".to_string(),
+                    TextureRegion::new(32.0, 0.0, 32.0, 32.0),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let animation_state =
+            AnimationState::from_atlas(&texture_atlas, &["walk_2", "walk_1", "walk_2"]).unwrap();
+        assert_eq!(
+            animation_state.keyframes,
+            vec![
+                TextureRegion::new(32.0, 0.0, 32.0, 32.0),
+                TextureRegion::new(0.0, 0.0, 32.0, 32.0),
+                TextureRegion::new(32.0, 0.0, 32.0, 32.0),
+            ]
+        );
+        assert!(AnimationState::from_atlas(&texture_atlas, &["walk_3"]).is_none());
+    }
+
+    #[test]
+    fn animations_without_keyframes_are_rejected() {
+        let texture_atlas = TextureAtlas {
+            texture_identifier: "texture".into(),
+            textures: HashMap::new(),
+        };
+
+        assert!(AnimationState::from_grid(16.0, 16.0, 4, 2, 0).is_none());
+        assert!(AnimationState::from_grid(16.0, 16.0, 0, 2, 6).is_none());
+        assert!(AnimationState::from_atlas(&texture_atlas, &[]).is_none());
+        assert!(AnimationState::from_timed_keyframes(vec![]).is_none());
+    }
+
+    #[test]
+    fn animations_without_keyframes_are_not_stepped() {
+        let mut ecs = Ecs::new();
+        let mut empty_animated_sprite = animated_sprite(true, 1.0);
+        empty_animated_sprite.animation_state.keyframes.clear();
+        ecs.insert((empty_animated_sprite,));
+
+        step(&mut ecs, 0.35);
+
+        let (_, (animated_sprite,)) = ecs.query_one::<(R<AnimatedSprite>,)>().unwrap();
+        assert_eq!(animated_sprite.animation_state.current_keyframe, 0);
+        assert_eq!(animated_sprite.animation_state.elapsed, 0.0);
     }
 }
@@ -6,21 +6,48 @@ use std::collections::HashMap;
 
 pub type TextureSize = (u32, u32);
 
+/// The filtering used when sampling a texture
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum TextureFilter {
+    /// Picks the closest texel, keeps pixel art crisp
+    #[default]
+    Nearest,
+    /// Interpolates between neighboring texels
+    Linear,
+}
+
+/// How texture coordinates outside of the [0, 1] range are sampled
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum TextureAddressMode {
+    /// Uses the color of the closest edge texel
+    #[default]
+    ClampToEdge,
+    /// Tiles the texture
+    Repeat,
+    /// Tiles the texture, mirroring it on every repetition
+    MirrorRepeat,
+}
+
 pub struct TextureData {
     pub identifier: String,
     pub size: TextureSize,
     pub bytes: Vec<u8>,
+    pub filter: TextureFilter,
+    pub address_mode: TextureAddressMode,
 }
 
 impl TextureData {
     pub fn from_bytes(identifier: &str, bytes: &[u8]) -> Result<TextureData, GraphicsError> {
-        let image = image::load_from_memory(bytes).unwrap();
-        let image = image.as_rgba8().unwrap();
+        let image = image::load_from_memory(bytes)
+            .map_err(ImageDecodeError)?
+            .into_rgba8();
 
         Ok(TextureData {
             identifier: identifier.into(),
             size: image.dimensions(),
-            bytes: image.to_vec(),
+            bytes: image.into_raw(),
+            filter: TextureFilter::default(),
+            address_mode: TextureAddressMode::default(),
         })
     }
 
@@ -29,15 +56,27 @@ impl TextureData {
         let image = ImageReader::open(file_path)
             .map_err(|e| TextureFileOpenError(e))?
             .decode()
-            .map_err(|e| ImageDecodeError(e))?;
-        let image = image.as_rgba8().unwrap();
+            .map_err(|e| ImageDecodeError(e))?
+            .into_rgba8();
 
         Ok(TextureData {
             identifier: file_path.into(),
             size: image.dimensions(),
-            bytes: image.to_vec(),
+            bytes: image.into_raw(),
+            filter: TextureFilter::default(),
+            address_mode: TextureAddressMode::default(),
         })
     }
+
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_address_mode(mut self, address_mode: TextureAddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -70,8 +109,8 @@ impl TextureSource {
         texture_width: u32,
         texture_height: u32,
         texture_atlases: &HashMap<String, TextureAtlas>,
-    ) -> TextureRegion {
-        match self {
+    ) -> Result<TextureRegion, GraphicsError> {
+        Ok(match self {
             TextureSource::WholeTexture(_) => TextureRegion::new(0.0, 0.0, 1.0, 1.0),
             TextureSource::TextureRegion(_, region) => TextureRegion {
                 x: region.x / texture_width as f32,
@@ -83,9 +122,13 @@ impl TextureSource {
                 let region = texture_atlases
                     .get(texture_atlas)
                     .expect("Texture atlas not found")
-                    .textures
-                    .get(texture_name)
-                    .expect("Texture not found in atlas");
+                    .texture_region(texture_name)
+                    .ok_or_else(|| {
+                        GraphicsError::AtlasRegionNotFound(
+                            texture_atlas.clone(),
+                            texture_name.clone(),
+                        )
+                    })?;
 
                 TextureRegion {
                     x: region.x / texture_width as f32,
@@ -94,7 +137,7 @@ impl TextureSource {
                     height: region.height / texture_height as f32,
                 }
             }
-        }
+        })
     }
 }
 
@@ -107,7 +150,7 @@ where
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 pub struct TextureRegion {
     pub x: f32,
     pub y: f32,
@@ -136,6 +179,19 @@ impl TextureRegion {
         }
     }
 
+    /// Shrinks the region by `amount` on each side, in the same unit as the region
+    ///
+    /// Insetting a pixel region by half a texel stops linear filtering from sampling
+    /// the neighboring regions of an atlas.
+    pub fn inset(self, amount: f32) -> Self {
+        Self {
+            x: self.x + amount,
+            y: self.y + amount,
+            width: self.width - 2.0 * amount,
+            height: self.height - 2.0 * amount,
+        }
+    }
+
     pub fn flip_x(self) -> Self {
         Self {
             x: self.x + self.width,
@@ -172,3 +228,58 @@ impl TextureAtlas {
         &self.texture_identifier
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb_png() -> Vec<u8> {
+        let mut bytes = vec![];
+        image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 1, image::Rgb([255, 0, 0])))
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn rgb_images_are_loaded_from_bytes_as_rgba() {
+        let texture_data = TextureData::from_bytes("texture", &rgb_png()).unwrap();
+
+        assert_eq!(texture_data.size, (2, 1));
+        assert_eq!(texture_data.bytes, vec![255, 0, 0, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn rgb_images_are_loaded_from_files_as_rgba() {
+        let file_path = std::env::temp_dir().join("tuber_rgb_texture.png");
+        std::fs::write(&file_path, rgb_png()).unwrap();
+
+        let texture_data = TextureData::from_file(file_path.to_str().unwrap()).unwrap();
+
+        assert_eq!(texture_data.size, (2, 1));
+        assert_eq!(texture_data.bytes, vec![255, 0, 0, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn texture_region_larger_than_texture_normalizes_beyond_one() {
+        let texture_source =
+            TextureSource::TextureRegion("texture".into(), TextureRegion::new(0.0, 0.0, 48.0, 64.0));
+
+        let region = texture_source
+            .normalized_texture_region(32, 32, &HashMap::new())
+            .unwrap();
+        assert_eq!(region, TextureRegion::new(0.0, 0.0, 1.5, 2.0));
+    }
+
+    #[test]
+    fn inset_region_is_shrunk_on_each_side() {
+        let region = TextureRegion::new(32.0, 0.0, 32.0, 16.0)
+            .inset(0.5)
+            .normalize(128, 64);
+
+        assert_eq!(
+            region,
+            TextureRegion::new(32.5 / 128.0, 0.5 / 64.0, 31.0 / 128.0, 15.0 / 64.0)
+        );
+    }
+}
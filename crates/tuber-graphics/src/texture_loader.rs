@@ -0,0 +1,155 @@
+use crate::texture::TextureData;
+use crate::GraphicsError;
+use std::collections::HashSet;
+use std::panic;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type LoadedTexture = (String, Result<TextureData, GraphicsError>);
+type Decoder = fn(&str) -> Result<TextureData, GraphicsError>;
+
+/// The maximum number of threads decoding texture files at the same time
+const WORKER_COUNT: usize = 4;
+
+/// Decodes texture files on background threads so that the main thread doesn't stall
+///
+/// The files are decoded by a pool of at most `WORKER_COUNT` threads, started on demand.
+pub(crate) struct TextureLoader {
+    decode: Decoder,
+    jobs: Sender<String>,
+    job_receiver: Arc<Mutex<Receiver<String>>>,
+    sender: Sender<LoadedTexture>,
+    loaded_textures: Receiver<LoadedTexture>,
+    pending_textures: HashSet<String>,
+    worker_count: usize,
+}
+
+impl TextureLoader {
+    pub fn new() -> Self {
+        let (jobs, job_receiver) = channel();
+        let (sender, loaded_textures) = channel();
+        Self {
+            decode: TextureData::from_file,
+            jobs,
+            job_receiver: Arc::new(Mutex::new(job_receiver)),
+            sender,
+            loaded_textures,
+            pending_textures: HashSet::new(),
+            worker_count: 0,
+        }
+    }
+
+    /// Creates a loader decoding the texture files with the given function instead
+    #[cfg(test)]
+    pub fn with_decoder(decode: Decoder) -> Self {
+        Self {
+            decode,
+            ..Self::new()
+        }
+    }
+
+    /// Starts decoding a texture file, unless it is already being decoded
+    pub fn load(&mut self, texture: &str) {
+        if !self.pending_textures.insert(texture.to_owned()) {
+            return;
+        }
+
+        if self.worker_count < WORKER_COUNT.min(self.pending_textures.len()) {
+            self.spawn_worker();
+        }
+
+        let _ = self.jobs.send(texture.to_owned());
+    }
+
+    /// Returns the textures decoded since the last call
+    pub fn loaded_textures(&mut self) -> Vec<LoadedTexture> {
+        let loaded_textures: Vec<LoadedTexture> = self.loaded_textures.try_iter().collect();
+        for (texture, _) in &loaded_textures {
+            self.pending_textures.remove(texture);
+        }
+
+        loaded_textures
+    }
+
+    /// Starts a thread decoding the queued texture files until the loader is dropped
+    ///
+    /// A panic while decoding a file is reported as the result of that file.
+    fn spawn_worker(&mut self) {
+        let decode = self.decode;
+        let job_receiver = Arc::clone(&self.job_receiver);
+        let sender = self.sender.clone();
+        thread::spawn(move || loop {
+            let texture = job_receiver.lock().unwrap().recv();
+            let texture = match texture {
+                Ok(texture) => texture,
+                Err(_) => return,
+            };
+
+            let texture_data = panic::catch_unwind(|| decode(&texture)).unwrap_or_else(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                Err(GraphicsError::TextureLoaderPanic(message))
+            });
+            let _ = sender.send((texture, texture_data));
+        });
+        self.worker_count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::poll_until;
+
+    fn decode(texture: &str) -> Result<TextureData, GraphicsError> {
+        Ok(TextureData {
+            identifier: texture.into(),
+            size: (1, 1),
+            bytes: vec![255; 4],
+            filter: Default::default(),
+            address_mode: Default::default(),
+        })
+    }
+
+    #[test]
+    fn textures_are_decoded_by_a_bounded_pool_of_workers() {
+        let mut texture_loader = TextureLoader::with_decoder(decode);
+
+        for i in 0..3 * WORKER_COUNT {
+            texture_loader.load(&format!("texture_{}.png", i));
+        }
+        let mut loaded_textures = vec![];
+        poll_until(|| {
+            loaded_textures.extend(texture_loader.loaded_textures());
+            loaded_textures.len() == 3 * WORKER_COUNT
+        });
+
+        assert_eq!(texture_loader.worker_count, WORKER_COUNT);
+        assert!(loaded_textures
+            .iter()
+            .all(|(texture, texture_data)| texture_data.as_ref().unwrap().identifier == *texture));
+    }
+
+    #[test]
+    fn panicking_decoders_fail_the_texture() {
+        let mut texture_loader = TextureLoader::with_decoder(|_| panic!("corrupted texture"));
+
+        texture_loader.load("texture.png");
+        texture_loader.load("other_texture.png");
+        let mut loaded_textures = vec![];
+        poll_until(|| {
+            loaded_textures.extend(texture_loader.loaded_textures());
+            loaded_textures.len() == 2
+        });
+
+        assert!(loaded_textures.iter().all(|(_, texture_data)| matches!(
+            texture_data,
+            Err(GraphicsError::TextureLoaderPanic(message)) if message == "corrupted texture"
+        )));
+        assert!(texture_loader.pending_textures.is_empty());
+    }
+}
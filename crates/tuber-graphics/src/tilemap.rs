@@ -1,8 +1,317 @@
-use tuber_common::tilemap::Tile;
+use crate::Color;
+use std::collections::{HashMap, HashSet};
+use tuber_common::tilemap::{Tile, Tilemap};
+use tuber_common::time::DeltaTime;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::query::accessors::{R, W};
+
+pub type TileTintFunction = Box<dyn Fn(&Tile) -> Color>;
+
+/// How the tiles of a tilemap are laid out
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TilemapProjection {
+    /// A grid of rectangular tiles, drawn row by row
+    Orthogonal,
+    /// A diamond layout where the tile (i, j) is placed at
+    /// ((i - j) * tile_width / 2, (i + j) * tile_height / 2), drawn back to front
+    Isometric,
+}
+
+impl TilemapProjection {
+    /// Returns the top-left corner of the quad of a tile, relative to the tilemap
+    pub fn tile_position(&self, tilemap: &Tilemap, tile_index: usize) -> (f32, f32) {
+        let i = tile_index % tilemap.width;
+        let j = tile_index / tilemap.width;
+        let (tile_width, tile_height) = (tilemap.tile_width as f32, tilemap.tile_height as f32);
+        match self {
+            TilemapProjection::Orthogonal => (i as f32 * tile_width, j as f32 * tile_height),
+            TilemapProjection::Isometric => (
+                (i as f32 - j as f32) * tile_width / 2.0,
+                (i + j) as f32 * tile_height / 2.0,
+            ),
+        }
+    }
+
+    /// Returns the rank of a tile in the draw order of the tilemap
+    ///
+    /// Isometric tiles are drawn diagonal by diagonal from the back, so that the tiles in front
+    /// overlap the ones behind them.
+    pub fn draw_index(&self, tilemap: &Tilemap, tile_index: usize) -> usize {
+        if *self == TilemapProjection::Orthogonal {
+            return tile_index;
+        }
+
+        let (width, height) = (tilemap.width, tilemap.height);
+        let first_column = |diagonal: usize| diagonal.saturating_sub(height - 1);
+        let diagonal_length =
+            |diagonal: usize| diagonal.min(width - 1) + 1 - first_column(diagonal);
+        let i = tile_index % width;
+        let diagonal = i + tile_index / width;
+
+        (0..diagonal).map(diagonal_length).sum::<usize>() + i - first_column(diagonal)
+    }
+}
 
 pub struct TilemapRender {
     pub identifier: String,
     pub texture_atlas_identifier: String,
     pub tile_texture_function: Box<dyn Fn(&Tile) -> Option<&str>>,
+    /// The draw order of the tilemap, layers above 0 are drawn over the sprites and shapes
+    pub layer: i32,
+    /// Rebuilds the whole tilemap on the next render
     pub dirty: bool,
+    /// Indices of the tiles changed since the last render, only those are rebuilt
+    pub dirty_tiles: HashSet<usize>,
+    /// Animations played by the tiles, keyed by the region returned by `tile_texture_function`
+    pub animations: HashMap<String, TileAnimation>,
+    /// Time elapsed since the start of the tile animations in milliseconds
+    pub animation_time: f64,
+    /// Shrinks the tile regions by half a texel so that neighboring tiles of the atlas
+    /// don't bleed into each other with linear filtering
+    pub texel_inset: bool,
+    /// The color multiplied with the texture of every tile, its alpha is ignored
+    ///
+    /// The tilemap must be marked dirty for a change to be rendered.
+    pub tint: Color,
+    /// Returns a color multiplied with the tint for a tile, to tint some tiles differently
+    pub tile_tint_function: Option<TileTintFunction>,
+    /// How the tiles are laid out
+    ///
+    /// The tilemap must be marked dirty for a change to be rendered.
+    pub projection: TilemapProjection,
+}
+
+pub struct TileAnimation {
+    /// The texture atlas regions of the keyframes
+    pub frames: Vec<String>,
+    /// Duration of a keyframe in milliseconds
+    pub frame_duration: u32,
+}
+
+impl TileAnimation {
+    /// Returns the index of the frame shown at a time, `None` if there is no frame
+    fn current_frame(&self, animation_time: f64) -> Option<usize> {
+        if self.frames.is_empty() {
+            return None;
+        }
+
+        Some((animation_time / self.frame_duration as f64) as usize % self.frames.len())
+    }
+}
+
+impl TilemapRender {
+    /// Returns the texture atlas region of a tile at the current animation time
+    ///
+    /// The tiles of an animation without frames keep the region returned by
+    /// `tile_texture_function`.
+    pub fn texture_region_identifier<'a>(&'a self, tile: &'a Tile) -> Option<&'a str> {
+        let texture_region_identifier = (self.tile_texture_function)(tile)?;
+        let animation_frame =
+            self.animations
+                .get(texture_region_identifier)
+                .and_then(|animation| {
+                    let frame = animation.current_frame(self.animation_time)?;
+                    Some(animation.frames[frame].as_str())
+                });
+        Some(animation_frame.unwrap_or(texture_region_identifier))
+    }
+
+    /// Returns the color multiplied with the texture of a tile
+    pub fn tile_tint(&self, tile: &Tile) -> Color {
+        let tile_tint = match &self.tile_tint_function {
+            Some(tile_tint_function) => tile_tint_function(tile),
+            None => return self.tint,
+        };
+
+        Color::rgba(
+            self.tint.r * tile_tint.r,
+            self.tint.g * tile_tint.g,
+            self.tint.b * tile_tint.b,
+            self.tint.a * tile_tint.a,
+        )
+    }
+}
+
+pub fn tile_animation_step_system(ecs: &mut Ecs) {
+    let DeltaTime(delta_time) = *ecs
+        .shared_resource::<DeltaTime>()
+        .expect("DeltaTime resource not found");
+
+    for (_, (tilemap, mut tilemap_render)) in ecs.query::<(R<Tilemap>, W<TilemapRender>)>() {
+        if tilemap_render.animations.is_empty() {
+            continue;
+        }
+
+        let previous_time = tilemap_render.animation_time;
+        let current_time = previous_time + delta_time * 1000.0;
+        tilemap_render.animation_time = current_time;
+
+        let changed_animations: HashSet<&str> = tilemap_render
+            .animations
+            .iter()
+            .filter(|(_, animation)| {
+                animation.current_frame(previous_time) != animation.current_frame(current_time)
+            })
+            .map(|(identifier, _)| identifier.as_str())
+            .collect();
+        if changed_animations.is_empty() {
+            continue;
+        }
+
+        let animated_tiles: Vec<usize> = tilemap
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| {
+                (tilemap_render.tile_texture_function)(tile)
+                    .is_some_and(|identifier| changed_animations.contains(identifier))
+            })
+            .map(|(tile_index, _)| tile_index)
+            .collect();
+        tilemap_render.dirty_tiles.extend(animated_tiles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tilemap_render() -> TilemapRender {
+        let mut animations = HashMap::new();
+        animations.insert(
+            "water".to_owned(),
+            TileAnimation {
+                frames: vec!["water_0".into(), "water_1".into()],
+                frame_duration: 100,
+            },
+        );
+
+        TilemapRender {
+            identifier: "tilemap".into(),
+            texture_atlas_identifier: "atlas".into(),
+            tile_texture_function: Box::new(|tile: &Tile| {
+                if tile.tags.contains("water") {
+                    Some("water")
+                } else {
+                    Some("dirt")
+                }
+            }),
+            layer: 0,
+            dirty: false,
+            dirty_tiles: HashSet::new(),
+            animations,
+            animation_time: 0.0,
+            texel_inset: false,
+            tint: Color::WHITE,
+            tile_tint_function: None,
+            projection: TilemapProjection::Orthogonal,
+        }
+    }
+
+    fn step(ecs: &mut Ecs, delta_time: f64) {
+        ecs.insert_shared_resource(DeltaTime(delta_time));
+        tile_animation_step_system(ecs);
+    }
+
+    fn texture_regions(ecs: &Ecs) -> Vec<String> {
+        let (_, (tilemap, tilemap_render)) =
+            ecs.query_one::<(R<Tilemap>, R<TilemapRender>)>().unwrap();
+        tilemap
+            .tiles
+            .iter()
+            .map(|tile| {
+                tilemap_render
+                    .texture_region_identifier(tile)
+                    .unwrap()
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tile_tint_is_multiplied_with_the_tilemap_tint() {
+        let mut tilemap_render = tilemap_render();
+        tilemap_render.tint = Color::rgba(0.5, 1.0, 1.0, 1.0);
+        let dirt = Tile::with_tags(&["dirt".into()]);
+        assert_eq!(tilemap_render.tile_tint(&dirt), tilemap_render.tint);
+
+        tilemap_render.tile_tint_function = Some(Box::new(|_| Color::rgba(1.0, 0.5, 0.0, 0.5)));
+        assert_eq!(
+            tilemap_render.tile_tint(&dirt),
+            Color::rgba(0.5, 0.5, 0.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn isometric_tiles_are_placed_in_a_diamond() {
+        let tilemap = Tilemap::new(3, 2, 32, 16, &[]);
+        let projection = TilemapProjection::Isometric;
+
+        assert_eq!(projection.tile_position(&tilemap, 0), (0.0, 0.0));
+        assert_eq!(projection.tile_position(&tilemap, 1), (16.0, 8.0));
+        assert_eq!(projection.tile_position(&tilemap, 3), (-16.0, 8.0));
+        assert_eq!(projection.tile_position(&tilemap, 5), (16.0, 24.0));
+        assert_eq!(
+            TilemapProjection::Orthogonal.tile_position(&tilemap, 5),
+            (64.0, 16.0)
+        );
+    }
+
+    #[test]
+    fn isometric_tiles_are_drawn_back_to_front() {
+        let tilemap = Tilemap::new(3, 2, 32, 16, &[]);
+
+        let draw_indices: Vec<usize> = (0..6)
+            .map(|tile_index| TilemapProjection::Isometric.draw_index(&tilemap, tile_index))
+            .collect();
+        // The diagonals are the tiles 0, then 3 and 1, then 4 and 2, then 5
+        assert_eq!(draw_indices, vec![0, 2, 4, 1, 3, 5]);
+        let draw_indices: Vec<usize> = (0..6)
+            .map(|tile_index| TilemapProjection::Orthogonal.draw_index(&tilemap, tile_index))
+            .collect();
+        assert_eq!(draw_indices, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn animated_tiles_change_region_over_time() {
+        let mut ecs = Ecs::new();
+        let mut tilemap = Tilemap::new(2, 1, 16, 16, &["dirt".into()]);
+        tilemap.tiles[1] = Tile::with_tags(&["water".into()]);
+        ecs.insert((tilemap, tilemap_render()));
+
+        assert_eq!(texture_regions(&ecs), vec!["dirt", "water_0"]);
+
+        step(&mut ecs, 0.05);
+        assert_eq!(texture_regions(&ecs), vec!["dirt", "water_0"]);
+        {
+            let (_, (tilemap_render,)) = ecs.query_one::<(R<TilemapRender>,)>().unwrap();
+            assert!(tilemap_render.dirty_tiles.is_empty());
+        }
+
+        step(&mut ecs, 0.06);
+        assert_eq!(texture_regions(&ecs), vec!["dirt", "water_1"]);
+        let (_, (tilemap_render,)) = ecs.query_one::<(R<TilemapRender>,)>().unwrap();
+        assert_eq!(
+            tilemap_render.dirty_tiles,
+            vec![1].into_iter().collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn tile_animations_without_frames_show_the_tile_region() {
+        let mut ecs = Ecs::new();
+        let tilemap = Tilemap::new(1, 1, 16, 16, &["water".into()]);
+        let mut tilemap_render = tilemap_render();
+        tilemap_render
+            .animations
+            .get_mut("water")
+            .unwrap()
+            .frames
+            .clear();
+        ecs.insert((tilemap, tilemap_render));
+
+        step(&mut ecs, 0.15);
+
+        assert_eq!(texture_regions(&ecs), vec!["water"]);
+    }
 }
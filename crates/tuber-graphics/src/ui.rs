@@ -1,6 +1,6 @@
 use crate::shape::RectangleShape;
 use crate::sprite::Sprite;
-use crate::texture::TextureSource;
+use crate::texture::{TextureRegion, TextureSource};
 use crate::{Color, Graphics};
 use std::collections::HashSet;
 use tuber_common::transform::Transform2D;
@@ -23,6 +23,7 @@ pub struct Frame {
 pub struct Text {
     text: String,
     font: String,
+    color: Color,
 }
 
 impl Text {
@@ -30,9 +31,21 @@ impl Text {
         Self {
             text: text.into(),
             font: font.into(),
+            color: Color::WHITE,
         }
     }
 
+    /// Returns the components of a text whose top-left corner is at the given position
+    pub fn bundle(text: &str, font: &str, position: (f32, f32)) -> (Transform2D, Text) {
+        (
+            Transform2D {
+                translation: position,
+                ..Default::default()
+            },
+            Text::new(text, font),
+        )
+    }
+
     pub fn text(&self) -> &str {
         &self.text
     }
@@ -46,6 +59,191 @@ impl Text {
     pub fn set_font(&mut self, font: &str) {
         self.font = font.to_string();
     }
+
+    /// Returns the color multiplied with the glyphs of the font, white by default
+    pub fn color(&self) -> Color {
+        self.color
+    }
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
 }
 
 pub struct NoViewTransform;
+
+/// Restricts the rendering of a UI element to a rectangle in screen coordinates
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClipRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ClipRect {
+    /// Returns the part of the rectangle also covered by another one
+    pub fn intersection(&self, other: &ClipRect) -> ClipRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        ClipRect {
+            x,
+            y,
+            width: ((self.x + self.width).min(other.x + other.width) - x).max(0.0),
+            height: ((self.y + self.height).min(other.y + other.height) - y).max(0.0),
+        }
+    }
+}
+
+/// A frame drawn from a texture split in nine slices
+///
+/// The corners keep their size, the edges are stretched along one axis and
+/// the center is stretched to fill the remaining space.
+pub struct NinePatch {
+    pub width: f32,
+    pub height: f32,
+    pub texture: TextureSource,
+    /// Size of the left border in pixels
+    pub left: f32,
+    /// Size of the right border in pixels
+    pub right: f32,
+    /// Size of the top border in pixels
+    pub top: f32,
+    /// Size of the bottom border in pixels
+    pub bottom: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NinePatchSlice {
+    /// Position of the slice relative to the nine patch origin
+    pub offset: (f32, f32),
+    pub width: f32,
+    pub height: f32,
+    /// The normalized texture region of the slice
+    pub texture_region: TextureRegion,
+}
+
+impl NinePatch {
+    /// Computes the nine slices of the patch, row by row from the top-left corner
+    ///
+    /// `source_region` is the normalized region of the whole patch on its texture.
+    pub fn slices(
+        &self,
+        source_region: TextureRegion,
+        texture_width: u32,
+        texture_height: u32,
+    ) -> Vec<NinePatchSlice> {
+        let texture_width = texture_width as f32;
+        let texture_height = texture_height as f32;
+
+        let columns = [
+            (0.0, self.left),
+            (self.left, (self.width - self.left - self.right).max(0.0)),
+            ((self.width - self.right).max(self.left), self.right),
+        ];
+        let rows = [
+            (0.0, self.top),
+            (self.top, (self.height - self.top - self.bottom).max(0.0)),
+            ((self.height - self.bottom).max(self.top), self.bottom),
+        ];
+
+        let region_columns = [
+            (source_region.x, self.left / texture_width),
+            (
+                source_region.x + self.left / texture_width,
+                source_region.width - (self.left + self.right) / texture_width,
+            ),
+            (
+                source_region.x + source_region.width - self.right / texture_width,
+                self.right / texture_width,
+            ),
+        ];
+        let region_rows = [
+            (source_region.y, self.top / texture_height),
+            (
+                source_region.y + self.top / texture_height,
+                source_region.height - (self.top + self.bottom) / texture_height,
+            ),
+            (
+                source_region.y + source_region.height - self.bottom / texture_height,
+                self.bottom / texture_height,
+            ),
+        ];
+
+        let mut slices = Vec::with_capacity(9);
+        for (&(y, height), &(region_y, region_height)) in rows.iter().zip(region_rows.iter()) {
+            for (&(x, width), &(region_x, region_width)) in
+                columns.iter().zip(region_columns.iter())
+            {
+                slices.push(NinePatchSlice {
+                    offset: (x, y),
+                    width,
+                    height,
+                    texture_region: TextureRegion::new(
+                        region_x,
+                        region_y,
+                        region_width,
+                        region_height,
+                    ),
+                });
+            }
+        }
+
+        slices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nine_patch_slices() {
+        let nine_patch = NinePatch {
+            width: 100.0,
+            height: 60.0,
+            texture: "frame".into(),
+            left: 8.0,
+            right: 8.0,
+            top: 4.0,
+            bottom: 12.0,
+        };
+
+        let slices = nine_patch.slices(TextureRegion::new(0.0, 0.0, 1.0, 1.0), 32, 32);
+        assert_eq!(slices.len(), 9);
+
+        let expected_geometry = [
+            ((0.0, 0.0), 8.0, 4.0),
+            ((8.0, 0.0), 84.0, 4.0),
+            ((92.0, 0.0), 8.0, 4.0),
+            ((0.0, 4.0), 8.0, 44.0),
+            ((8.0, 4.0), 84.0, 44.0),
+            ((92.0, 4.0), 8.0, 44.0),
+            ((0.0, 48.0), 8.0, 12.0),
+            ((8.0, 48.0), 84.0, 12.0),
+            ((92.0, 48.0), 8.0, 12.0),
+        ];
+        for (slice, &(offset, width, height)) in slices.iter().zip(expected_geometry.iter()) {
+            assert_eq!(slice.offset, offset);
+            assert_eq!(slice.width, width);
+            assert_eq!(slice.height, height);
+        }
+
+        let expected_regions = [
+            (0.0, 0.0, 0.25, 0.125),
+            (0.25, 0.0, 0.5, 0.125),
+            (0.75, 0.0, 0.25, 0.125),
+            (0.0, 0.125, 0.25, 0.5),
+            (0.25, 0.125, 0.5, 0.5),
+            (0.75, 0.125, 0.25, 0.5),
+            (0.0, 0.625, 0.25, 0.375),
+            (0.25, 0.625, 0.5, 0.375),
+            (0.75, 0.625, 0.25, 0.375),
+        ];
+        for (slice, &(x, y, width, height)) in slices.iter().zip(expected_regions.iter()) {
+            assert_eq!(slice.texture_region.x, x);
+            assert_eq!(slice.texture_region.y, y);
+            assert_eq!(slice.texture_region.width, width);
+            assert_eq!(slice.texture_region.height, height);
+        }
+    }
+}
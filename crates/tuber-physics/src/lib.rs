@@ -1,4 +1,7 @@
 mod sat;
+mod spatial_hash_grid;
+
+pub use spatial_hash_grid::SpatialHashGrid;
 
 use nalgebra::{Point2, Point3};
 use std::collections::{HashMap, HashSet};
@@ -7,20 +10,78 @@ use tuber_core::DeltaTime;
 use tuber_ecs::ecs::Ecs;
 use tuber_ecs::query::accessors::{R, W};
 use tuber_ecs::system::SystemBundle;
+use tuber_ecs::EntityIndex;
+use tuber_graphics::{Color, Graphics};
 
 type Vector2 = nalgebra::Vector2<f32>;
 
+/// The color of the collision shape outlines drawn by the debug rendering
+const DEBUG_RENDERING_COLOR: Color = Color::rgb(0.0, 1.0, 0.0);
+
 pub struct Physics {
     gravity: Vector2,
+    /// The size of the cells of the broadphase grid
+    cell_size: f32,
+    last_collisions: Vec<CollisionInfo>,
+    debug_rendering: bool,
+    /// How many times the collisions are detected and resolved per step
+    solver_iterations: u32,
+}
+
+/// A collision detected during the last physics step
+///
+/// Each colliding pair is recorded once from the point of view of each of its entities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionInfo {
+    /// The entity being pushed out of the collision
+    pub entity: EntityIndex,
+    /// The entity it collided with
+    pub other: EntityIndex,
+    /// The world-space direction in which the entity is pushed out
+    pub normal: (f32, f32),
+    /// How deep the shapes overlap along the normal
+    pub penetration: f32,
+    /// The approximate point where the shapes touch, the center of their overlapping bounds
+    pub contact_point: (f32, f32),
 }
 
 impl Physics {
-    pub fn new(gravity: (f32, f32)) -> Self {
+    /// Creates the physics, the cell size should be about the size of the largest colliders
+    ///
+    /// Panics if the cell size isn't positive and finite.
+    pub fn new(gravity: (f32, f32), cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0 && cell_size.is_finite(),
+            "The cell size must be positive and finite, got {}",
+            cell_size
+        );
         Self {
             gravity: Vector2::new(gravity.0, gravity.1),
+            cell_size,
+            last_collisions: vec![],
+            debug_rendering: false,
+            solver_iterations: 1,
         }
     }
 
+    /// Sets how many times the collisions are detected and resolved per step, more iterations
+    /// let stacked bodies settle instead of jittering
+    pub fn set_solver_iterations(&mut self, iterations: u32) {
+        self.solver_iterations = iterations.max(1);
+    }
+
+    /// Enables drawing the outline of the collision shapes
+    pub fn set_debug_rendering(&mut self, enabled: bool) {
+        self.debug_rendering = enabled;
+    }
+
+    /// Returns the collisions detected during the last physics step
+    ///
+    /// A pair colliding during several solver iterations is recorded as detected in the first one.
+    pub fn last_collisions(&self) -> &[CollisionInfo] {
+        &self.last_collisions
+    }
+
     pub fn update_rigid_body_2d(
         &mut self,
         delta_time: f64,
@@ -28,9 +89,12 @@ impl Physics {
         rigid_body: &mut RigidBody2D,
     ) {
         if !rigid_body.grounded {
-            rigid_body.acceleration += self.gravity;
+            rigid_body.acceleration += self.gravity * rigid_body.gravity_scale;
         }
-        rigid_body.velocity = rigid_body.velocity + rigid_body.acceleration * delta_time as f32;
+        let acceleration = rigid_body.acceleration + rigid_body.force / rigid_body.mass;
+        rigid_body.force = Vector2::zeros();
+        rigid_body.velocity = rigid_body.velocity + acceleration * delta_time as f32;
+        rigid_body.clamp_velocity();
         transform.translation.0 += rigid_body.velocity.x;
         transform.translation.1 += rigid_body.velocity.y;
     }
@@ -40,6 +104,49 @@ impl Physics {
         system_bundle.add_system(physics_update_system);
         system_bundle
     }
+
+    /// Returns the systems drawing the debug rendering, to run once per frame
+    pub fn debug_system_bundle() -> SystemBundle {
+        let mut system_bundle = SystemBundle::new();
+        system_bundle.add_system(collision_shapes_debug_system);
+        system_bundle
+    }
+}
+
+/// Draws the outline of the collision shapes if the debug rendering is enabled
+pub fn collision_shapes_debug_system(ecs: &mut Ecs) {
+    let debug_rendering = ecs
+        .shared_resource::<Physics>()
+        .is_some_and(|physics| physics.debug_rendering);
+    if !debug_rendering {
+        return;
+    }
+
+    let mut graphics = ecs
+        .shared_resource_mut::<Graphics>()
+        .expect("No Graphics resource");
+    for (start, end) in collision_shape_outlines(ecs) {
+        graphics.draw_line(start, end, DEBUG_RENDERING_COLOR);
+    }
+}
+
+/// Returns the world-space edges of every collision shape
+fn collision_shape_outlines(ecs: &Ecs) -> Vec<((f32, f32), (f32, f32))> {
+    let mut outlines = vec![];
+    for (_, (transform, collidable)) in ecs.query::<(R<Transform2D>, R<Collidable>)>() {
+        for collision_shape in &collidable.shapes {
+            outlines.extend(
+                collision_shape
+                    .transform(&transform)
+                    .polygon
+                    .edges()
+                    .into_iter()
+                    .map(|(start, end)| ((start.x, start.y), (end.x, end.y))),
+            );
+        }
+    }
+
+    outlines
 }
 
 pub fn physics_update_system(ecs: &mut Ecs) {
@@ -54,41 +161,90 @@ pub fn physics_update_system(ecs: &mut Ecs) {
         physics.update_rigid_body_2d(delta_time, &mut transform, &mut rigid_body);
     }
 
+    let mut collided = HashSet::new();
+    let mut last_collisions: Vec<CollisionInfo> = vec![];
+    for _ in 0..physics.solver_iterations {
+        let collisions = resolve_collisions(ecs, physics.cell_size);
+        collided.extend(collisions.iter().map(|collision| collision.entity));
+
+        let recorded_pairs: HashSet<(EntityIndex, EntityIndex)> = last_collisions
+            .iter()
+            .map(|collision| (collision.entity, collision.other))
+            .collect();
+        last_collisions.extend(
+            collisions
+                .into_iter()
+                .filter(|collision| !recorded_pairs.contains(&(collision.entity, collision.other))),
+        );
+    }
+    physics.last_collisions = last_collisions;
+
+    for (id, (mut rigid_body,)) in ecs.query::<(W<RigidBody2D>,)>() {
+        if !collided.contains(&id) {
+            rigid_body.grounded = false;
+        }
+    }
+}
+
+/// Pushes the rigid bodies out of the shapes they overlap and returns the detected collisions
+fn resolve_collisions(ecs: &Ecs, cell_size: f32) -> Vec<CollisionInfo> {
+    let mut collisions = vec![];
     let mut displacements = HashMap::new();
     let mut collided = HashSet::new();
 
-    for (first, (transform, collidable)) in ecs.query::<(R<Transform2D>, R<Collidable>)>() {
-        for (second, (second_transform, second_collidable)) in
-            ecs.query::<(R<Transform2D>, R<Collidable>)>()
-        {
-            if first == second || (collidable.bit & second_collidable.mask == 0) {
+    let mut grid = SpatialHashGrid::new(cell_size);
+    let mut colliders = HashMap::new();
+    for (id, (transform, collidable)) in ecs.query::<(R<Transform2D>, R<Collidable>)>() {
+        let shapes: Vec<CollisionShape> = collidable
+            .shapes
+            .iter()
+            .map(|collision_shape| collision_shape.transform(&transform))
+            .collect();
+        if let Some(bounds) = shapes_bounds(&shapes) {
+            grid.insert(id, bounds);
+        }
+        colliders.insert(id, (shapes, collidable.bit, collidable.mask));
+    }
+
+    for (first_entity, second_entity) in grid.candidate_pairs() {
+        for &(first, second) in &[(first_entity, second_entity), (second_entity, first_entity)] {
+            let (shapes, bit, _) = &colliders[&first];
+            let (second_shapes, _, second_mask) = &colliders[&second];
+            if bit & second_mask == 0 {
                 continue;
             }
 
-            for collision_shape in &collidable.shapes {
-                for second_collision_shape in &second_collidable.shapes {
-                    let transformed_collision_box = collision_shape.transform(&transform);
-                    let transformed_second_collision_box =
-                        second_collision_shape.transform(&second_transform);
-
+            for transformed_collision_box in shapes {
+                for transformed_second_collision_box in second_shapes {
                     if let Some(collision_data) = sat::are_colliding(
-                        &transformed_collision_box,
-                        &transformed_second_collision_box,
+                        transformed_collision_box,
+                        transformed_second_collision_box,
                     ) {
-                        let displacement = Vector2::new(
-                            -collision_data.smallest_axis.x,
+                        let normal = (
+                            collision_data.smallest_axis.x,
                             collision_data.smallest_axis.y,
                         );
-
-                        let s = (displacement.x * displacement.x + displacement.y * displacement.y)
-                            .sqrt();
-
                         let displacement = (
-                            displacement.x * collision_data.overlap / s,
-                            displacement.y * collision_data.overlap / s,
+                            normal.0 * collision_data.overlap,
+                            normal.1 * collision_data.overlap,
                         );
 
-                        displacements.insert(first, displacement);
+                        collisions.push(CollisionInfo {
+                            entity: first,
+                            other: second,
+                            normal,
+                            penetration: collision_data.overlap,
+                            contact_point: transformed_collision_box
+                                .polygon
+                                .overlapping_bounds_center(
+                                    &transformed_second_collision_box.polygon,
+                                ),
+                        });
+
+                        let combined_displacement =
+                            displacements.entry(first).or_insert((0.0, 0.0));
+                        *combined_displacement =
+                            combine_displacements(*combined_displacement, displacement);
                         collided.insert(first);
                     }
                 }
@@ -96,12 +252,6 @@ pub fn physics_update_system(ecs: &mut Ecs) {
         }
     }
 
-    for (id, (mut rigid_body,)) in ecs.query::<(W<RigidBody2D>,)>() {
-        if !collided.contains(&id) {
-            rigid_body.grounded = false;
-        }
-    }
-
     for id in collided {
         let displacement = displacements[&id];
         if let Some((_, (mut transform, mut body))) =
@@ -126,6 +276,41 @@ pub fn physics_update_system(ecs: &mut Ecs) {
             }
         }
     }
+
+    collisions
+}
+
+/// Returns the bounding box of a set of shapes, `None` if there are none
+fn shapes_bounds(shapes: &[CollisionShape]) -> Option<(Point2<f32>, Point2<f32>)> {
+    shapes
+        .iter()
+        .map(|collision_shape| collision_shape.polygon.bounds())
+        .fold(None, |bounds, (minimum, maximum)| match bounds {
+            None => Some((minimum, maximum)),
+            Some((bounds_minimum, bounds_maximum)) => Some((
+                Point2::new(
+                    minimum.x.min(bounds_minimum.x),
+                    minimum.y.min(bounds_minimum.y),
+                ),
+                Point2::new(
+                    maximum.x.max(bounds_maximum.x),
+                    maximum.y.max(bounds_maximum.y),
+                ),
+            )),
+        })
+}
+
+/// Combines the push-outs of the shapes of an entity, keeping the largest one on each axis
+fn combine_displacements(first: (f32, f32), second: (f32, f32)) -> (f32, f32) {
+    let largest = |first: f32, second: f32| {
+        if second.abs() > first.abs() {
+            second
+        } else {
+            first
+        }
+    };
+
+    (largest(first.0, second.0), largest(first.1, second.1))
 }
 
 #[derive(Debug)]
@@ -133,6 +318,42 @@ pub struct RigidBody2D {
     pub velocity: Vector2,
     pub acceleration: Vector2,
     pub grounded: bool,
+    /// The mass dividing the applied forces and impulses
+    pub mass: f32,
+    /// The multiplier of the gravity applied to the body, 0.0 makes it ignore gravity
+    pub gravity_scale: f32,
+    /// The maximum absolute velocity on each axis
+    pub max_velocity: Option<(f32, f32)>,
+    /// The maximum length of the velocity
+    pub max_speed: Option<f32>,
+    /// The forces applied since the last step, cleared once integrated
+    pub force: Vector2,
+}
+
+impl RigidBody2D {
+    /// Changes the velocity instantly by the impulse divided by the mass
+    pub fn apply_impulse(&mut self, impulse: (f32, f32)) {
+        self.velocity += Vector2::new(impulse.0, impulse.1) / self.mass;
+    }
+
+    /// Applies a force during the next step only
+    pub fn apply_force(&mut self, force: (f32, f32)) {
+        self.force += Vector2::new(force.0, force.1);
+    }
+
+    fn clamp_velocity(&mut self) {
+        if let Some((max_x, max_y)) = self.max_velocity {
+            self.velocity.x = self.velocity.x.max(-max_x).min(max_x);
+            self.velocity.y = self.velocity.y.max(-max_y).min(max_y);
+        }
+
+        if let Some(max_speed) = self.max_speed {
+            let speed = self.velocity.norm();
+            if speed > max_speed {
+                self.velocity *= max_speed / speed;
+            }
+        }
+    }
 }
 
 impl Default for RigidBody2D {
@@ -141,6 +362,11 @@ impl Default for RigidBody2D {
             velocity: Vector2::new(0.0, 0.0),
             acceleration: Vector2::new(0.0, 0.0),
             grounded: false,
+            mass: 1.0,
+            gravity_scale: 1.0,
+            max_velocity: None,
+            max_speed: None,
+            force: Vector2::new(0.0, 0.0),
         }
     }
 }
@@ -187,6 +413,46 @@ impl Polygon {
         }
     }
 
+    /// Returns the edges of the polygon, the last one joining the last point to the first one
+    pub fn edges(&self) -> Vec<(Point2<f32>, Point2<f32>)> {
+        self.points
+            .iter()
+            .zip(self.points.iter().cycle().skip(1))
+            .map(|(&start, &end)| (start, end))
+            .collect()
+    }
+
+    /// Returns the average of the points of the polygon
+    pub fn center(&self) -> Point2<f32> {
+        let sum = self
+            .points
+            .iter()
+            .fold(Vector2::zeros(), |sum, point| sum + point.coords);
+        Point2::from(sum / self.points.len() as f32)
+    }
+
+    /// Returns the top-left and bottom-right corners of the bounding box of the polygon
+    pub fn bounds(&self) -> (Point2<f32>, Point2<f32>) {
+        self.points[1..].iter().fold(
+            (self.points[0], self.points[0]),
+            |(minimum, maximum), point| {
+                (
+                    Point2::new(minimum.x.min(point.x), minimum.y.min(point.y)),
+                    Point2::new(maximum.x.max(point.x), maximum.y.max(point.y)),
+                )
+            },
+        )
+    }
+
+    fn overlapping_bounds_center(&self, other: &Polygon) -> (f32, f32) {
+        let (minimum, maximum) = self.bounds();
+        let (other_minimum, other_maximum) = other.bounds();
+        (
+            (minimum.x.max(other_minimum.x) + maximum.x.min(other_maximum.x)) / 2.0,
+            (minimum.y.max(other_minimum.y) + maximum.y.min(other_maximum.y)) / 2.0,
+        )
+    }
+
     pub fn project(&self, axis: &Vector2) -> (f32, f32) {
         self.points[1..].iter().fold(
             (
@@ -262,3 +528,289 @@ impl CollisionShape {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "The cell size must be positive and finite, got NaN")]
+    fn nan_cell_size_is_rejected() {
+        Physics::new((0.0, 0.0), f32::NAN);
+    }
+
+    #[test]
+    fn impulse_changes_velocity_immediately() {
+        let mut rigid_body = RigidBody2D {
+            mass: 2.0,
+            ..Default::default()
+        };
+
+        rigid_body.apply_impulse((4.0, -2.0));
+
+        assert_eq!(rigid_body.velocity, Vector2::new(2.0, -1.0));
+    }
+
+    #[test]
+    fn force_is_scaled_by_mass_during_one_step() {
+        let mut physics = Physics::new((0.0, 0.0), 64.0);
+        let mut transform = Transform2D::default();
+        let mut rigid_body = RigidBody2D {
+            mass: 2.0,
+            ..Default::default()
+        };
+
+        rigid_body.apply_force((10.0, 0.0));
+        rigid_body.apply_force((10.0, 4.0));
+        physics.update_rigid_body_2d(0.5, &mut transform, &mut rigid_body);
+        assert_eq!(rigid_body.velocity, Vector2::new(5.0, 1.0));
+        assert_eq!(transform.translation, (5.0, 1.0));
+
+        physics.update_rigid_body_2d(0.5, &mut transform, &mut rigid_body);
+        assert_eq!(rigid_body.velocity, Vector2::new(5.0, 1.0));
+        assert_eq!(rigid_body.acceleration, Vector2::zeros());
+    }
+
+    fn fall_distance(gravity_scale: f32) -> f32 {
+        let mut physics = Physics::new((0.0, 1.0), 64.0);
+        let mut transform = Transform2D::default();
+        let mut rigid_body = RigidBody2D {
+            gravity_scale,
+            ..Default::default()
+        };
+
+        for _ in 0..4 {
+            physics.update_rigid_body_2d(1.0, &mut transform, &mut rigid_body);
+        }
+
+        transform.translation.1
+    }
+
+    #[test]
+    fn gravity_is_scaled_per_body() {
+        let baseline = fall_distance(1.0);
+
+        assert!(baseline > 0.0);
+        assert_eq!(fall_distance(0.0), 0.0);
+        assert_eq!(fall_distance(2.0), 2.0 * baseline);
+    }
+
+    #[test]
+    fn velocity_is_clamped_per_axis() {
+        let mut physics = Physics::new((0.0, 0.0), 64.0);
+        let mut transform = Transform2D::default();
+        let mut rigid_body = RigidBody2D {
+            max_velocity: Some((10.0, 2.0)),
+            ..Default::default()
+        };
+
+        rigid_body.apply_impulse((-30.0, 1.5));
+        physics.update_rigid_body_2d(1.0, &mut transform, &mut rigid_body);
+        assert_eq!(rigid_body.velocity, Vector2::new(-10.0, 1.5));
+
+        rigid_body.apply_impulse((5.0, 4.0));
+        physics.update_rigid_body_2d(1.0, &mut transform, &mut rigid_body);
+        assert_eq!(rigid_body.velocity, Vector2::new(-5.0, 2.0));
+    }
+
+    #[test]
+    fn velocity_is_clamped_by_magnitude() {
+        let mut physics = Physics::new((0.0, 0.0), 64.0);
+        let mut transform = Transform2D::default();
+        let mut rigid_body = RigidBody2D {
+            max_speed: Some(5.0),
+            ..Default::default()
+        };
+
+        rigid_body.apply_impulse((30.0, 40.0));
+        physics.update_rigid_body_2d(1.0, &mut transform, &mut rigid_body);
+
+        assert!((rigid_body.velocity - Vector2::new(3.0, 4.0)).norm() < 1e-5);
+        assert!((transform.translation.0 - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn overlapping_rectangles_record_their_collision() {
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(DeltaTime(0.016));
+        ecs.insert_shared_resource(Physics::new((0.0, 0.0), 64.0));
+        let collidable = || Collidable {
+            shapes: vec![CollisionShape::from_rectangle(0.0, 0.0, 10.0, 10.0)],
+            bit: 1,
+            mask: 1,
+        };
+        let left = ecs.insert((Transform2D::default(), collidable()));
+        let right = ecs.insert((
+            Transform2D {
+                translation: (8.0, 0.0),
+                ..Default::default()
+            },
+            collidable(),
+        ));
+
+        physics_update_system(&mut ecs);
+
+        let physics = ecs.shared_resource::<Physics>().unwrap();
+        let collision = physics
+            .last_collisions()
+            .iter()
+            .find(|collision| collision.entity == left)
+            .unwrap();
+        assert_eq!(collision.other, right);
+        assert_eq!(collision.normal, (-1.0, 0.0));
+        assert!((collision.penetration - 2.0).abs() < 1e-4);
+        assert_eq!(collision.contact_point, (9.0, 5.0));
+        assert_eq!(physics.last_collisions().len(), 2);
+    }
+
+    #[test]
+    fn collision_shape_outlines_have_one_segment_per_edge() {
+        let mut ecs = Ecs::new();
+        ecs.insert((
+            Transform2D::default(),
+            Collidable {
+                shapes: vec![CollisionShape::from_rectangle(0.0, 0.0, 10.0, 10.0)],
+                ..Default::default()
+            },
+        ));
+        ecs.insert((
+            Transform2D {
+                translation: (100.0, 50.0),
+                ..Default::default()
+            },
+            Collidable {
+                shapes: vec![
+                    CollisionShape::from_rectangle(0.0, 0.0, 10.0, 10.0),
+                    CollisionShape::from_centered_rectangle(0.0, 0.0, 4.0, 4.0),
+                ],
+                ..Default::default()
+            },
+        ));
+
+        let outlines = collision_shape_outlines(&ecs);
+
+        assert_eq!(outlines.len(), 12);
+        assert_eq!(outlines[3], ((0.0, 10.0), (0.0, 0.0)));
+        assert_eq!(outlines[4], ((100.0, 50.0), (110.0, 50.0)));
+        assert_eq!(outlines[11], ((98.0, 52.0), (98.0, 48.0)));
+    }
+
+    /// Returns the vertical position of two boxes stacked on a floor after one step
+    fn settled_stack(solver_iterations: u32) -> (f32, f32) {
+        let (ecs, lower_box, upper_box) = stepped_stack(solver_iterations, 83.0);
+
+        let y = |id| {
+            let (_, (transform,)) = ecs.query_one_by_id::<(R<Transform2D>,)>(id).unwrap();
+            transform.translation.1
+        };
+        (y(lower_box), y(upper_box))
+    }
+
+    /// Steps two boxes, the lower one sinking in a floor, and returns them
+    fn stepped_stack(solver_iterations: u32, upper_box_y: f32) -> (Ecs, EntityIndex, EntityIndex) {
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(DeltaTime(0.016));
+        let mut physics = Physics::new((0.0, 0.0), 64.0);
+        physics.set_solver_iterations(solver_iterations);
+        ecs.insert_shared_resource(physics);
+        let box_at = |y: f32| {
+            (
+                Transform2D {
+                    translation: (0.0, y),
+                    ..Default::default()
+                },
+                RigidBody2D::default(),
+                Collidable {
+                    shapes: vec![CollisionShape::from_rectangle(0.0, 0.0, 10.0, 10.0)],
+                    bit: 1,
+                    mask: 1,
+                },
+            )
+        };
+        let lower_box = ecs.insert(box_at(92.0));
+        let upper_box = ecs.insert(box_at(upper_box_y));
+        ecs.insert((
+            Transform2D {
+                translation: (-50.0, 100.0),
+                ..Default::default()
+            },
+            StaticBody2D,
+            Collidable {
+                shapes: vec![CollisionShape::from_rectangle(0.0, 0.0, 110.0, 10.0)],
+                bit: 1,
+                mask: 1,
+            },
+        ));
+
+        physics_update_system(&mut ecs);
+
+        (ecs, lower_box, upper_box)
+    }
+
+    #[test]
+    fn stacked_bodies_settle_with_enough_solver_iterations() {
+        let overlaps = |(lower_box, upper_box): (f32, f32)| {
+            (
+                lower_box + 10.0 - 100.0 > 1e-4,
+                upper_box + 10.0 - lower_box > 1e-4,
+            )
+        };
+
+        assert_ne!(overlaps(settled_stack(1)), (false, false));
+
+        let (lower_box, upper_box) = settled_stack(4);
+        assert_eq!(overlaps((lower_box, upper_box)), (false, false));
+        assert!((lower_box - 90.0).abs() < 1e-4);
+        assert!((upper_box - 80.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn collisions_of_every_solver_iteration_are_recorded_once() {
+        // The upper box only overlaps the lower one once it is pushed out of the floor
+        let (ecs, lower_box, upper_box) = stepped_stack(4, 81.0);
+
+        let physics = ecs.shared_resource::<Physics>().unwrap();
+        let pairs: Vec<(EntityIndex, EntityIndex)> = physics
+            .last_collisions()
+            .iter()
+            .map(|collision| (collision.entity, collision.other))
+            .collect();
+        assert_eq!(pairs.len(), 4);
+        assert!(pairs.contains(&(lower_box, upper_box)));
+        assert!(pairs.contains(&(upper_box, lower_box)));
+    }
+
+    #[test]
+    fn multi_shape_body_is_pushed_out_by_its_deepest_shape() {
+        let mut ecs = Ecs::new();
+        ecs.insert_shared_resource(DeltaTime(0.016));
+        ecs.insert_shared_resource(Physics::new((0.0, 0.0), 64.0));
+        let body = ecs.insert((
+            Transform2D::default(),
+            RigidBody2D::default(),
+            Collidable {
+                shapes: vec![
+                    CollisionShape::from_rectangle(3.0, 10.0, 10.0, 10.0),
+                    CollisionShape::from_rectangle(0.0, 0.0, 10.0, 10.0),
+                ],
+                bit: 1,
+                mask: 1,
+            },
+        ));
+        ecs.insert((
+            Transform2D::default(),
+            StaticBody2D,
+            Collidable {
+                shapes: vec![CollisionShape::from_rectangle(9.0, -100.0, 10.0, 200.0)],
+                bit: 1,
+                mask: 1,
+            },
+        ));
+
+        physics_update_system(&mut ecs);
+
+        let (_, (transform,)) = ecs.query_one_by_id::<(R<Transform2D>,)>(body).unwrap();
+        assert!((transform.translation.0 + 4.0).abs() < 1e-4);
+        assert!(transform.translation.1.abs() < 1e-4);
+    }
+}
@@ -19,25 +19,29 @@ pub fn are_colliding(
             let o = overlap(projected_first_shape, projected_second_shape);
             if o.abs() < shapes_overlap {
                 shapes_overlap = o.abs();
-
-                let axis = Vector2::new(
-                    if o < 0.0 { -axis.x } else { axis.x },
-                    if o < 0.0 { axis.y } else { -axis.y },
-                );
-                smallest_axis = Some(axis);
+                smallest_axis = Some(*axis);
             }
         }
     }
 
+    let smallest_axis = smallest_axis.unwrap();
+    let centers_offset = first_shape.polygon.center() - second_shape.polygon.center();
+    let smallest_axis = if centers_offset.dot(&smallest_axis) < 0.0 {
+        -smallest_axis
+    } else {
+        smallest_axis
+    };
+
     Some(CollisionData {
         overlap: shapes_overlap,
-        smallest_axis: smallest_axis.unwrap(),
+        smallest_axis,
     })
 }
 
 #[derive(Clone, Debug)]
 pub struct CollisionData {
     pub overlap: f32,
+    /// The unit axis of least overlap, pointing away from the second shape
     pub smallest_axis: Vector2,
 }
 
@@ -60,3 +64,38 @@ fn overlap(p1: (f32, f32), p2: (f32, f32)) -> f32 {
 fn projections_overlap(p1: (f32, f32), p2: (f32, f32)) -> bool {
     p1.0 < p2.1 && p2.0 <= p1.1
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuber_common::transform::Transform2D;
+
+    fn collide_with_square_at(x: f32, y: f32) -> CollisionData {
+        let square = CollisionShape::from_rectangle(0.0, 0.0, 10.0, 10.0);
+        let other = square.transform(&Transform2D {
+            translation: (x, y),
+            ..Default::default()
+        });
+
+        are_colliding(&square, &other).unwrap()
+    }
+
+    fn assert_push_out(collision_data: &CollisionData, axis: (f32, f32), overlap: f32) {
+        assert!((collision_data.smallest_axis - Vector2::new(axis.0, axis.1)).norm() < 1e-4);
+        assert!((collision_data.overlap - overlap).abs() < 1e-4);
+    }
+
+    #[test]
+    fn push_out_points_away_from_the_other_shape() {
+        assert_push_out(&collide_with_square_at(8.0, 0.0), (-1.0, 0.0), 2.0);
+        assert_push_out(&collide_with_square_at(-8.0, 0.0), (1.0, 0.0), 2.0);
+        assert_push_out(&collide_with_square_at(0.0, 8.0), (0.0, -1.0), 2.0);
+        assert_push_out(&collide_with_square_at(0.0, -8.0), (0.0, 1.0), 2.0);
+    }
+
+    #[test]
+    fn diagonal_push_out_uses_the_shallowest_axis() {
+        assert_push_out(&collide_with_square_at(7.0, 8.0), (0.0, -1.0), 2.0);
+        assert_push_out(&collide_with_square_at(-8.0, -7.0), (1.0, 0.0), 2.0);
+    }
+}
@@ -0,0 +1,125 @@
+use nalgebra::Point2;
+use std::collections::{BTreeSet, HashMap};
+use tuber_ecs::EntityIndex;
+
+type Cell = (i32, i32);
+
+/// Buckets bounding boxes into uniform cells to find the entities that may collide
+pub struct SpatialHashGrid {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<EntityIndex>>,
+}
+
+impl SpatialHashGrid {
+    /// Creates an empty grid
+    ///
+    /// Panics if the cell size isn't positive and finite.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0 && cell_size.is_finite(),
+            "The cell size must be positive and finite, got {}",
+            cell_size
+        );
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Adds an entity to every cell covered by its bounding box
+    pub fn insert(&mut self, entity: EntityIndex, bounds: (Point2<f32>, Point2<f32>)) {
+        let (minimum, maximum) = (self.cell(bounds.0), self.cell(bounds.1));
+        for x in minimum.0..=maximum.0 {
+            for y in minimum.1..=maximum.1 {
+                let entities = self.cells.entry((x, y)).or_default();
+                if !entities.contains(&entity) {
+                    entities.push(entity);
+                }
+            }
+        }
+    }
+
+    /// Returns the pairs of entities sharing a cell or lying in adjacent cells, smallest index first
+    pub fn candidate_pairs(&self) -> BTreeSet<(EntityIndex, EntityIndex)> {
+        let mut candidate_pairs = BTreeSet::new();
+        for (&(x, y), entities) in &self.cells {
+            for neighbour_x in x - 1..=x + 1 {
+                for neighbour_y in y - 1..=y + 1 {
+                    let neighbours = match self.cells.get(&(neighbour_x, neighbour_y)) {
+                        Some(neighbours) => neighbours,
+                        None => continue,
+                    };
+
+                    for &entity in entities {
+                        for &neighbour in neighbours {
+                            if entity < neighbour {
+                                candidate_pairs.insert((entity, neighbour));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        candidate_pairs
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    fn cell(&self, point: Point2<f32>) -> Cell {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(x: f32, y: f32, size: f32) -> (Point2<f32>, Point2<f32>) {
+        (Point2::new(x, y), Point2::new(x + size, y + size))
+    }
+
+    #[test]
+    #[should_panic(expected = "The cell size must be positive and finite, got 0")]
+    fn zero_cell_size_is_rejected() {
+        SpatialHashGrid::new(0.0);
+    }
+
+    #[test]
+    fn entities_in_the_same_or_adjacent_cells_are_candidates() {
+        let mut grid = SpatialHashGrid::new(32.0);
+        grid.insert(0, bounds(0.0, 0.0, 10.0));
+        grid.insert(1, bounds(16.0, 16.0, 10.0));
+        grid.insert(2, bounds(40.0, 40.0, 10.0));
+        grid.insert(3, bounds(500.0, 500.0, 10.0));
+
+        let candidate_pairs = grid.candidate_pairs();
+
+        assert!(candidate_pairs.contains(&(0, 1)));
+        assert!(candidate_pairs.contains(&(0, 2)));
+        assert!(candidate_pairs.contains(&(1, 2)));
+        assert!(candidate_pairs
+            .iter()
+            .all(|&(first, second)| first != 3 && second != 3));
+    }
+
+    #[test]
+    fn large_entities_cover_several_cells() {
+        let mut grid = SpatialHashGrid::new(32.0);
+        grid.insert(0, bounds(0.0, 0.0, 200.0));
+        grid.insert(1, bounds(180.0, 180.0, 10.0));
+        grid.insert(2, bounds(-100.0, 300.0, 10.0));
+
+        let candidate_pairs = grid.candidate_pairs();
+
+        assert_eq!(
+            candidate_pairs.into_iter().collect::<Vec<_>>(),
+            vec![(0, 1)]
+        );
+    }
+}
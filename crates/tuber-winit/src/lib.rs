@@ -1,10 +1,18 @@
+mod window_geometry;
+mod window_settings;
+
+pub use window_geometry::{PersistedWindowGeometry, WindowGeometry};
+
+use crate::window_settings::apply_window_settings;
 use std::convert::{TryFrom, TryInto};
 use std::time::Instant;
 use tuber_core::input::keyboard::Key;
 use tuber_core::input::mouse::Button;
 use tuber_core::input::Input;
+use tuber_core::window::WindowSettings;
 use tuber_core::{Engine, Result as TuberResult, TuberRunner};
 use tuber_graphics::{render, Graphics, Window};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode};
 use winit::{
     event::{Event, WindowEvent},
@@ -15,25 +23,52 @@ use winit::{
 enum TuberWinitError {
     UnknownVirtualKeycode(VirtualKeyCode),
     UnknownKeyboardInput(KeyboardInput),
-    UnknownMouseButton(MouseButton),
 }
 
 pub struct WinitTuberRunner;
 impl TuberRunner for WinitTuberRunner {
     fn run(&mut self, mut engine: Engine, mut graphics: Graphics) -> TuberResult<()> {
-        const UPDATE_TARGET_FPS: u32 = 100;
         const RENDER_TARGET_FPS: u32 = 60;
-        const DELTA_TIME: f64 = 1.0 / UPDATE_TARGET_FPS as f64;
         const TIME_BETWEEN_FRAME: f64 = 1.0 / RENDER_TARGET_FPS as f64;
-        let mut current_time = Instant::now();
-        let mut accumulator = 0f64;
         let mut last_render_time = Instant::now();
+        let mut applied_window_settings: Option<WindowSettings> = None;
+
+        let geometry_path = engine
+            .ecs()
+            .shared_resource::<PersistedWindowGeometry>()
+            .map(|persisted_window_geometry| persisted_window_geometry.0.clone());
 
         let event_loop = EventLoop::new();
-        let window = WindowBuilder::new()
-            .with_title("tuber")
-            .build(&event_loop)
-            .unwrap();
+        let monitors: Vec<((i32, i32), (u32, u32))> = event_loop
+            .available_monitors()
+            .map(|monitor| {
+                let (position, size) = (monitor.position(), monitor.size());
+                ((position.x, position.y), (size.width, size.height))
+            })
+            .collect();
+        let restored_geometry = geometry_path
+            .as_deref()
+            .and_then(WindowGeometry::load)
+            .filter(|geometry| geometry.fits_in_any(&monitors));
+
+        let title = engine
+            .ecs()
+            .shared_resource::<WindowSettings>()
+            .unwrap()
+            .title()
+            .to_owned();
+        let mut window_builder = WindowBuilder::new().with_title(title);
+        if let Some(geometry) = restored_geometry {
+            window_builder =
+                window_builder.with_inner_size(PhysicalSize::new(geometry.size.0, geometry.size.1));
+        }
+        let window = window_builder.build(&event_loop).unwrap();
+        if let Some(geometry) = restored_geometry {
+            window.set_outer_position(PhysicalPosition::new(
+                geometry.position.0,
+                geometry.position.1,
+            ));
+        }
         graphics.initialize(
             Window(Box::new(
                 &window as &dyn raw_window_handle::HasRawWindowHandle,
@@ -49,7 +84,21 @@ impl TuberRunner for WinitTuberRunner {
                 Event::WindowEvent {
                     event: WindowEvent::CloseRequested,
                     window_id,
-                } if window_id == window.id() => *control_flow = ControlFlow::Exit,
+                } if window_id == window.id() => {
+                    if let (Some(geometry_path), Ok(position)) =
+                        (&geometry_path, window.outer_position())
+                    {
+                        let geometry = WindowGeometry {
+                            position: (position.x, position.y),
+                            size: (window.inner_size().width, window.inner_size().height),
+                        };
+                        if let Err(error) = geometry.save(geometry_path) {
+                            eprintln!("Couldn't save the window geometry: {}", error);
+                        }
+                    }
+
+                    *control_flow = ControlFlow::Exit;
+                }
                 Event::WindowEvent {
                     event: WindowEvent::KeyboardInput { input, .. },
                     window_id,
@@ -79,13 +128,12 @@ impl TuberRunner for WinitTuberRunner {
                     engine.on_window_resized(new_size.width, new_size.height);
                 }
                 Event::MainEventsCleared => {
-                    let new_time = Instant::now();
-                    let frame_time = new_time.duration_since(current_time).as_secs_f64();
-                    current_time = new_time;
-                    accumulator += frame_time;
-                    while accumulator >= DELTA_TIME {
-                        engine.step(DELTA_TIME);
-                        accumulator -= DELTA_TIME;
+                    if let Err(error) = apply_window_settings(
+                        &window,
+                        &engine.ecs().shared_resource::<WindowSettings>().unwrap(),
+                        &mut applied_window_settings,
+                    ) {
+                        eprintln!("Couldn't grab the cursor: {}", error);
                     }
 
                     if last_render_time.elapsed().as_secs_f64() >= TIME_BETWEEN_FRAME {
@@ -94,6 +142,10 @@ impl TuberRunner for WinitTuberRunner {
                 }
                 Event::RedrawRequested(_) => {
                     let current_render_time = Instant::now();
+                    let frame_time = current_render_time
+                        .duration_since(last_render_time)
+                        .as_secs_f64();
+                    engine.step(frame_time);
                     render(engine.ecs());
                     last_render_time = current_render_time;
                 }
@@ -137,7 +189,7 @@ impl TryFrom<MouseInputWrapper> for Input {
             MouseButton::Left => Button::Left,
             MouseButton::Right => Button::Right,
             MouseButton::Middle => Button::Middle,
-            button => return Err(TuberWinitError::UnknownMouseButton(button)),
+            MouseButton::Other(button) => Button::Other(button),
         };
 
         Ok(match input.1 {
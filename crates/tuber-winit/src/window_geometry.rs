@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// The position and size of a monitor in physical pixels
+type MonitorBounds = ((i32, i32), (u32, u32));
+
+/// Path of the file the runner stores the window geometry in on exit and restores it from on
+/// startup, the geometry isn't persisted without this resource
+pub struct PersistedWindowGeometry(pub PathBuf);
+
+/// The outer position and inner size of a window in physical pixels
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+}
+
+impl WindowGeometry {
+    /// Reads a geometry from a file, `None` if it is missing or malformed
+    pub fn load(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        serde_json::from_reader(BufReader::new(file)).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Returns whether the window lies entirely within one of the given monitors
+    pub fn fits_in_any(&self, monitors: &[MonitorBounds]) -> bool {
+        let (x, y) = (self.position.0 as i64, self.position.1 as i64);
+        let (width, height) = (self.size.0 as i64, self.size.1 as i64);
+        monitors.iter().any(
+            |&((monitor_x, monitor_y), (monitor_width, monitor_height))| {
+                let (monitor_x, monitor_y) = (monitor_x as i64, monitor_y as i64);
+                x >= monitor_x
+                    && y >= monitor_y
+                    && x + width <= monitor_x + monitor_width as i64
+                    && y + height <= monitor_y + monitor_height as i64
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometry_is_restored_from_its_file() {
+        let path = std::env::temp_dir().join("tuber_window_geometry_test.json");
+        let geometry = WindowGeometry {
+            position: (-120, 40),
+            size: (800, 600),
+        };
+
+        geometry.save(&path).unwrap();
+        let loaded_geometry = WindowGeometry::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded_geometry, Some(geometry));
+        assert_eq!(WindowGeometry::load(&path), None);
+    }
+
+    #[test]
+    fn off_screen_geometry_does_not_fit() {
+        let monitors = [((0, 0), (1920, 1080)), ((1920, 0), (1280, 1024))];
+        let geometry = |position, size| WindowGeometry { position, size };
+
+        assert!(geometry((100, 100), (800, 600)).fits_in_any(&monitors));
+        assert!(geometry((2000, 300), (800, 600)).fits_in_any(&monitors));
+        assert!(!geometry((1500, 100), (800, 600)).fits_in_any(&monitors));
+        assert!(!geometry((-400, 100), (800, 600)).fits_in_any(&monitors));
+        assert!(!geometry((100, 100), (800, 600)).fits_in_any(&[]));
+    }
+}
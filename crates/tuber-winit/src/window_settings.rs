@@ -0,0 +1,108 @@
+use std::error::Error;
+use tuber_core::window::WindowSettings;
+
+/// A window the `WindowSettings` are applied to
+pub(crate) trait SettingsTarget {
+    fn set_title(&self, title: &str);
+    fn set_cursor_visible(&self, visible: bool);
+    fn set_cursor_grabbed(&self, grabbed: bool) -> Result<(), Box<dyn Error>>;
+}
+
+impl SettingsTarget for winit::window::Window {
+    fn set_title(&self, title: &str) {
+        winit::window::Window::set_title(self, title);
+    }
+
+    fn set_cursor_visible(&self, visible: bool) {
+        winit::window::Window::set_cursor_visible(self, visible);
+    }
+
+    fn set_cursor_grabbed(&self, grabbed: bool) -> Result<(), Box<dyn Error>> {
+        Ok(self.set_cursor_grab(grabbed)?)
+    }
+}
+
+/// Applies the window settings to a window, unless they were the last ones applied
+///
+/// Returns the error raised when the cursor can't be grabbed, the settings are still recorded as
+/// applied so that the grab isn't retried every frame.
+pub(crate) fn apply_window_settings(
+    window: &impl SettingsTarget,
+    window_settings: &WindowSettings,
+    applied_window_settings: &mut Option<WindowSettings>,
+) -> Result<(), Box<dyn Error>> {
+    if applied_window_settings.as_ref() == Some(window_settings) {
+        return Ok(());
+    }
+
+    window.set_title(window_settings.title());
+    window.set_cursor_visible(window_settings.cursor_visible());
+    *applied_window_settings = Some(window_settings.clone());
+    window.set_cursor_grabbed(window_settings.cursor_grabbed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct StubWindow {
+        titles: RefCell<Vec<String>>,
+        cursor_grabs: RefCell<Vec<bool>>,
+        cursor_grab_unsupported: bool,
+    }
+
+    impl SettingsTarget for StubWindow {
+        fn set_title(&self, title: &str) {
+            self.titles.borrow_mut().push(title.to_owned());
+        }
+
+        fn set_cursor_visible(&self, _visible: bool) {}
+
+        fn set_cursor_grabbed(&self, grabbed: bool) -> Result<(), Box<dyn Error>> {
+            self.cursor_grabs.borrow_mut().push(grabbed);
+            if self.cursor_grab_unsupported {
+                return Err("cursor grab is not supported".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn changed_settings_are_applied_to_the_window_once() {
+        let window = StubWindow::default();
+        let mut window_settings = WindowSettings::new();
+        let mut applied_window_settings = None;
+
+        apply_window_settings(&window, &window_settings, &mut applied_window_settings).unwrap();
+        window_settings.set_title("Score: 10");
+        apply_window_settings(&window, &window_settings, &mut applied_window_settings).unwrap();
+        apply_window_settings(&window, &window_settings, &mut applied_window_settings).unwrap();
+
+        assert_eq!(*window.titles.borrow(), vec!["tuber", "Score: 10"]);
+        assert_eq!(*window.cursor_grabs.borrow(), vec![false, false]);
+        assert_eq!(applied_window_settings, Some(window_settings));
+    }
+
+    #[test]
+    fn cursor_grab_errors_are_returned_once() {
+        let window = StubWindow {
+            cursor_grab_unsupported: true,
+            ..Default::default()
+        };
+        let mut window_settings = WindowSettings::new();
+        window_settings.set_cursor_grabbed(true);
+        let mut applied_window_settings = None;
+
+        let error = apply_window_settings(&window, &window_settings, &mut applied_window_settings)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "cursor grab is not supported");
+        assert!(
+            apply_window_settings(&window, &window_settings, &mut applied_window_settings).is_ok()
+        );
+        assert_eq!(*window.cursor_grabs.borrow(), vec![true]);
+        assert_eq!(applied_window_settings, Some(window_settings));
+    }
+}
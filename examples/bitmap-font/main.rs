@@ -1,7 +1,7 @@
 use tuber::common::transform::Transform2D;
 use tuber::graphics::camera::{Active, OrthographicCamera};
 use tuber::graphics::ui::Text;
-use tuber::graphics::Graphics;
+use tuber::graphics::{Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
 use tuber::Input::{KeyDown, KeyUp};
@@ -21,6 +21,7 @@ fn main() -> tuber::Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -43,12 +44,12 @@ fn main() -> tuber::Result<()> {
 
     let mut runner = WinitTuberRunner;
     let mut graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
-    graphics.set_clear_color((1.0, 1.0, 1.0));
+    graphics.set_clear_color(Color::rgb(1.0, 1.0, 1.0));
     engine.add_system_bundle(Graphics::default_system_bundle());
 
     let mut bundle = SystemBundle::new();
     bundle.add_system(move_camera_system);
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
 
     runner.run(engine, graphics)
 }
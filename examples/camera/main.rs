@@ -1,6 +1,6 @@
 use tuber::graphics::camera::{Active, OrthographicCamera};
 use tuber::graphics::shape::RectangleShape;
-use tuber::graphics::Graphics;
+use tuber::graphics::{Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
 use tuber::Input::{KeyDown, KeyUp};
@@ -15,7 +15,9 @@ fn main() -> Result<()> {
         RectangleShape {
             width: 100.0,
             height: 100.0,
-            color: (1.0, 0.0, 0.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(1.0, 0.0, 0.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (400.0, 300.0),
@@ -31,6 +33,7 @@ fn main() -> Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -42,7 +45,7 @@ fn main() -> Result<()> {
     let mut graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
     let mut bundle = SystemBundle::new();
     bundle.add_system(move_camera_right_system);
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
     engine.add_system_bundle(Graphics::default_system_bundle());
     WinitTuberRunner.run(engine, graphics)
 }
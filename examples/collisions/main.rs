@@ -1,7 +1,7 @@
 use tuber::common::transform::Transform2D;
 use tuber::graphics::camera::{Active, OrthographicCamera};
 use tuber::graphics::shape::RectangleShape;
-use tuber::graphics::Graphics;
+use tuber::graphics::{Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::physics::{Collidable, CollisionShape, Physics, RigidBody2D, StaticBody2D};
 use tuber::Input::MouseButtonDown;
@@ -23,6 +23,7 @@ fn main() -> tuber::Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -35,7 +36,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: 100.0,
             height: 100.0,
-            color: (1.0, 0.0, 0.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(1.0, 0.0, 0.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (200.0, 200.0),
@@ -53,7 +56,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: 100.0,
             height: 100.0,
-            color: (1.0, 0.0, 0.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(1.0, 0.0, 0.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (200.0, 0.0),
@@ -69,10 +74,12 @@ fn main() -> tuber::Result<()> {
     let mut runner = WinitTuberRunner;
     let graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
 
-    let physics = Physics::new((0.0, 1.0));
+    let mut physics = Physics::new((0.0, 1.0), 128.0);
+    physics.set_debug_rendering(true);
     engine.ecs().insert_shared_resource(physics);
 
-    engine.add_system_bundle(Physics::default_system_bundle());
+    engine.add_fixed_system_bundle(Physics::default_system_bundle());
+    engine.add_system_bundle(Physics::debug_system_bundle());
     engine.add_system_bundle(Graphics::default_system_bundle());
     let mut bundle = SystemBundle::new();
     engine.add_system_bundle(bundle);
@@ -0,0 +1,60 @@
+use tuber::graphics::camera::{Active, OrthographicCamera};
+use tuber::graphics::low_level::VertexDescription;
+use tuber::graphics::mesh::Mesh2D;
+use tuber::graphics::Graphics;
+use tuber::graphics_wgpu::GraphicsWGPU;
+use tuber::*;
+use tuber_common::transform::Transform2D;
+
+fn main() -> Result<()> {
+    let mut engine = Engine::new();
+
+    engine.ecs().insert((
+        OrthographicCamera {
+            left: 0.0,
+            right: 800.0,
+            top: 0.0,
+            bottom: 600.0,
+            near: -100.0,
+            far: 100.0,
+            ..Default::default()
+        },
+        Transform2D {
+            translation: (0.0, 0.0),
+            ..Default::default()
+        },
+        Active,
+    ));
+
+    engine.ecs().insert((
+        Transform2D {
+            translation: (300.0, 200.0),
+            ..Default::default()
+        },
+        Mesh2D {
+            vertices: vec![
+                VertexDescription {
+                    position: (100.0, 0.0, 0.0),
+                    color: (1.0, 1.0, 1.0),
+                    texture_coordinates: (0.5, 0.0),
+                },
+                VertexDescription {
+                    position: (0.0, 200.0, 0.0),
+                    color: (1.0, 0.5, 0.5),
+                    texture_coordinates: (0.0, 1.0),
+                },
+                VertexDescription {
+                    position: (200.0, 200.0, 0.0),
+                    color: (0.5, 0.5, 1.0),
+                    texture_coordinates: (1.0, 1.0),
+                },
+            ],
+            texture: "examples/sprite/sprite.png".into(),
+        },
+    ));
+
+    let graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
+    engine.add_system_bundle(Graphics::default_system_bundle());
+
+    WinitTuberRunner.run(engine, graphics)
+}
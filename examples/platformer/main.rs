@@ -1,9 +1,9 @@
 use tuber::ecs::ecs::Ecs;
 use tuber::ecs::query::accessors::{R, W};
 use tuber::ecs::system::SystemBundle;
-use tuber::graphics::camera::{Active, OrthographicCamera};
+use tuber::graphics::camera::{Active, CameraFollow, OrthographicCamera};
 use tuber::graphics::shape::RectangleShape;
-use tuber::graphics::Graphics;
+use tuber::graphics::{Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
 use tuber::physics::Collidable;
@@ -14,6 +14,28 @@ use tuber_physics::{CollisionShape, Physics, RigidBody2D, StaticBody2D};
 fn main() -> tuber::Result<()> {
     let mut engine = Engine::new();
 
+    let player = engine.ecs().insert((
+        RectangleShape {
+            width: 50.0,
+            height: 100.0,
+            anchor: (0.0, 0.0),
+            color: Color::rgb(1.0, 0.0, 0.0),
+            corner_radius: 0.0,
+        },
+        Transform2D {
+            translation: (100.0, 100.0),
+            ..Default::default()
+        },
+        RigidBody2D {
+            max_velocity: Some((10.0, f32::INFINITY)),
+            ..Default::default()
+        },
+        Collidable {
+            shapes: vec![CollisionShape::from_rectangle(0.0, 0.0, 50.0, 100.0)],
+            ..Default::default()
+        },
+    ));
+
     engine.ecs().insert((
         OrthographicCamera {
             left: 0.0,
@@ -22,28 +44,17 @@ fn main() -> tuber::Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
             ..Default::default()
         },
         Active,
-    ));
-
-    engine.ecs().insert((
-        RectangleShape {
-            width: 50.0,
-            height: 100.0,
-            color: (1.0, 0.0, 0.0),
-        },
-        Transform2D {
-            translation: (100.0, 100.0),
-            ..Default::default()
-        },
-        RigidBody2D::default(),
-        Collidable {
-            shapes: vec![CollisionShape::from_rectangle(0.0, 0.0, 50.0, 100.0)],
-            ..Default::default()
+        CameraFollow {
+            target: player,
+            offset: (-400.0, -300.0),
+            lerp: 0.1,
         },
     ));
 
@@ -51,7 +62,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: 800.0,
             height: 50.0,
-            color: (0.0, 1.0, 0.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(0.0, 1.0, 0.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (0.0, 550.0),
@@ -67,7 +80,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: 100.0,
             height: 50.0,
-            color: (0.0, 1.0, 0.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(0.0, 1.0, 0.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (350.0, 499.0),
@@ -84,7 +99,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: 300.0,
             height: 50.0,
-            color: (0.0, 1.0, 0.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(0.0, 1.0, 0.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (200.0, 200.0),
@@ -98,45 +115,37 @@ fn main() -> tuber::Result<()> {
         },
     ));
 
+    let mut input_map = InputMap::new();
+    input_map.bind("left", Input::KeyDown(Key::Q));
+    input_map.bind("right", Input::KeyDown(Key::D));
+    input_map.bind("jump", Input::KeyDown(Key::Z));
+    input_map.bind("jump", Input::KeyDown(Key::Spacebar));
+    engine.ecs().insert_shared_resource(input_map);
+
     let mut runner = WinitTuberRunner;
     let graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
-    engine
-        .ecs()
-        .insert_shared_resource(Physics::new((0.0, 1.0)));
+    let mut physics = Physics::new((0.0, 1.0), 128.0);
+    physics.set_solver_iterations(4);
+    engine.ecs().insert_shared_resource(physics);
 
-    engine.add_system_bundle(Physics::default_system_bundle());
+    engine.add_fixed_system_bundle(Physics::default_system_bundle());
     engine.add_system_bundle(Graphics::default_system_bundle());
     let mut bundle = SystemBundle::new();
     bundle.add_system(jump_system);
     bundle.add_system(move_system);
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
 
     runner.run(engine, graphics)
 }
 
 fn move_system(ecs: &mut Ecs) {
     let input = ecs.shared_resource::<InputState>().unwrap();
-    let (_, (mut rigid_body, transform)) =
-        ecs.query_one::<(W<RigidBody2D>, R<Transform2D>)>().unwrap();
-    let (_, (mut camera_transform, _)) = ecs
-        .query_one::<(W<Transform2D>, R<OrthographicCamera>)>()
-        .unwrap();
-    camera_transform.translation = (
-        transform.translation.0 - 400.0,
-        transform.translation.1 - 300.0,
-    );
-    if input.is(Input::KeyDown(Key::Q)) {
+    let input_map = ecs.shared_resource::<InputMap>().unwrap();
+    let (_, (mut rigid_body, _)) = ecs.query_one::<(W<RigidBody2D>, R<Transform2D>)>().unwrap();
+    if input.action_active(&input_map, "left") {
         rigid_body.acceleration.x = -5.0;
-
-        if rigid_body.velocity.x <= -10.0 {
-            rigid_body.velocity.x = -10.0;
-        }
-    } else if input.is(Input::KeyDown(Key::D)) {
+    } else if input.action_active(&input_map, "right") {
         rigid_body.acceleration.x = 5.0;
-
-        if rigid_body.velocity.x >= 10.0 {
-            rigid_body.velocity.x = 10.0;
-        }
     } else {
         rigid_body.acceleration.x = 0.0;
         if rigid_body.velocity.x > 0.0 {
@@ -163,8 +172,9 @@ fn move_system(ecs: &mut Ecs) {
 
 fn jump_system(ecs: &mut Ecs) {
     let input = ecs.shared_resource::<InputState>().unwrap();
+    let input_map = ecs.shared_resource::<InputMap>().unwrap();
     let (_, (mut rigid_body,)) = ecs.query_one::<(W<RigidBody2D>,)>().unwrap();
-    if input.is(Input::KeyDown(Key::Z)) {
+    if input.action_active(&input_map, "jump") {
         if rigid_body.velocity.y.abs() == 0.0 {
             rigid_body.acceleration.y = -40.0;
         }
@@ -1,6 +1,7 @@
 use rand::{thread_rng, Rng};
 use std::collections::VecDeque;
 use tuber::graphics::camera::{Active, OrthographicCamera};
+use tuber::graphics::low_level::BlendMode;
 use tuber::graphics::{sprite::*, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
@@ -51,6 +52,7 @@ fn main() -> Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -63,6 +65,7 @@ fn main() -> Result<()> {
         .ecs()
         .insert_shared_resource(PivotList(VecDeque::new()));
     engine.ecs().insert_shared_resource(Score(0));
+    engine.set_window_title("Snake - Score: 0");
 
     spawn_snake(engine.ecs());
     spawn_apple(engine.ecs());
@@ -73,7 +76,7 @@ fn main() -> Result<()> {
     bundle.add_system(move_body_parts_system);
     bundle.add_system(eat_apple_system);
     bundle.add_system(check_collision_with_body_system);
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
     engine.add_system_bundle(Graphics::default_system_bundle());
     WinitTuberRunner.run(engine, graphics)
 }
@@ -139,7 +142,7 @@ fn move_head_system(ecs: &mut Ecs) {
         }
 
         *velocity = compute_new_segment_velocity(transform.angle);
-        *transform = compute_new_segment_position(*transform, &velocity);
+        transform.translate((velocity.x, velocity.y));
 
         transform.translation.0 < -BODY_PART_SIZE
             || transform.translation.0 > WINDOW_WIDTH as f32
@@ -161,7 +164,12 @@ fn game_over(ecs: &mut Ecs) {
 fn reset_score(ecs: &mut Ecs) {
     let mut score = ecs.shared_resource_mut::<Score>().unwrap();
     score.0 = 0;
-    println!("Score: {}", score.0);
+    show_score(ecs, score.0);
+}
+
+fn show_score(ecs: &Ecs, score: u32) {
+    let mut window_settings = ecs.shared_resource_mut::<WindowSettings>().unwrap();
+    window_settings.set_title(&format!("Snake - Score: {}", score));
 }
 
 fn respawn_snake(ecs: &mut Ecs) {
@@ -182,7 +190,10 @@ fn spawn_apple(ecs: &mut Ecs) {
         Sprite {
             width: 64.0,
             height: 64.0,
+            anchor: (0.0, 0.0),
             texture: "examples/snake/apple.png".into(),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
         Apple,
     ));
@@ -198,7 +209,10 @@ fn spawn_snake(ecs: &mut Ecs) {
         Sprite {
             width: BODY_PART_SIZE,
             height: BODY_PART_SIZE,
+            anchor: (0.0, 0.0),
             texture: "examples/snake/snake_tail.png".into(),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
         Velocity {
             x: 0.0,
@@ -218,7 +232,10 @@ fn spawn_snake(ecs: &mut Ecs) {
         Sprite {
             width: BODY_PART_SIZE,
             height: BODY_PART_SIZE,
+            anchor: (0.0, 0.0),
             texture: "examples/snake/snake_face.png".into(),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
         Velocity {
             x: 0.0,
@@ -254,7 +271,7 @@ fn move_body_parts_system(ecs: &mut Ecs) {
             }
         }
         *velocity = compute_new_segment_velocity(transform.angle);
-        *transform = compute_new_segment_position(*transform, &velocity);
+        transform.translate((velocity.x, velocity.y));
     }
 
     for id_pivot_to_delete in pivots_to_delete {
@@ -292,7 +309,7 @@ fn eat_apple_system(ecs: &mut Ecs) {
                 apple_transform.translation.1 = rng.gen_range(0.0..600.0 - 64.0);
                 score.0 += 1;
                 grow_snake = true;
-                println!("Score: {}", score.0)
+                show_score(ecs, score.0);
             }
         }
     }
@@ -317,7 +334,10 @@ fn eat_apple_system(ecs: &mut Ecs) {
                 Sprite {
                     width: 64.0,
                     height: 64.0,
+                    anchor: (0.0, 0.0),
                     texture: "examples/snake/snake_tail.png".into(),
+                    blend_mode: BlendMode::Alpha,
+                    address_mode: None,
                 },
                 tail_velocity,
                 SnakeBodyPart {
@@ -348,16 +368,6 @@ fn compute_new_segment_velocity(angle_degrees: f32) -> Velocity {
     }
 }
 
-fn compute_new_segment_position(transform: Transform2D, velocity: &Velocity) -> Transform2D {
-    Transform2D {
-        translation: (
-            transform.translation.0 + velocity.x,
-            transform.translation.1 + velocity.y,
-        ),
-        ..transform
-    }
-}
-
 fn rectangle_intersects(
     first_rectangle: (f32, f32, f32, f32),
     second_rectangle: (f32, f32, f32, f32),
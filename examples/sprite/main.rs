@@ -1,5 +1,5 @@
-use std::time::Instant;
 use tuber::graphics::camera::{Active, OrthographicCamera};
+use tuber::graphics::low_level::BlendMode;
 use tuber::graphics::texture::{TextureRegion, TextureSource};
 use tuber::graphics::{sprite::*, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
@@ -17,6 +17,7 @@ fn main() -> Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -25,28 +26,15 @@ fn main() -> Result<()> {
         Active,
     ));
 
-    engine.ecs().insert((
-        Transform2D {
-            translation: (375.0, 275.0),
-            ..Default::default()
-        },
-        Sprite {
-            width: 50.0,
-            height: 50.0,
-            texture: "examples/sprite/sprite.png".into(),
-        },
+    engine.ecs().insert(Sprite::bundle(
+        "examples/sprite/sprite.png",
+        (375.0, 275.0),
+        (50.0, 50.0),
     ));
-
-    engine.ecs().insert((
-        Transform2D {
-            translation: (500.0, 275.0),
-            ..Default::default()
-        },
-        Sprite {
-            width: 50.0,
-            height: 50.0,
-            texture: "examples/sprite/sprite2.png".into(),
-        },
+    engine.ecs().insert(Sprite::bundle(
+        "examples/sprite/sprite2.png",
+        (500.0, 275.0),
+        (50.0, 50.0),
     ));
 
     engine.ecs().insert((
@@ -57,7 +45,10 @@ fn main() -> Result<()> {
         Sprite {
             width: 50.0,
             height: 50.0,
+            anchor: (0.0, 0.0),
             texture: "fqgqgqgpng".into(),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
     ));
 
@@ -69,10 +60,13 @@ fn main() -> Result<()> {
         Sprite {
             width: 100.0,
             height: 100.0,
+            anchor: (0.0, 0.0),
             texture: TextureSource::TextureRegion(
                 "mkgskgsmlgk".into(),
                 TextureRegion::new(0.0, 0.0, 16.0, 16.0),
             ),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
     ));
 
@@ -84,10 +78,13 @@ fn main() -> Result<()> {
         Sprite {
             width: 100.0,
             height: 100.0,
+            anchor: (0.0, 0.0),
             texture: TextureSource::TextureAtlas(
                 "examples/sprite/texture-atlas.json".into(),
                 "tree".into(),
             ),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
     ));
 
@@ -99,10 +96,13 @@ fn main() -> Result<()> {
         Sprite {
             width: 50.0,
             height: 50.0,
+            anchor: (0.0, 0.0),
             texture: TextureSource::TextureAtlas(
                 "examples/sprite/texture-atlas.json".into(),
                 "house".into(),
             ),
+            blend_mode: BlendMode::Alpha,
+            address_mode: None,
         },
     ));
 
@@ -117,23 +117,14 @@ fn main() -> Result<()> {
             texture: TextureSource::WholeTexture("examples/sprite/animated_sprite.png".into()),
 
             animation_state: AnimationState {
-                keyframes: vec![
-                    TextureRegion::new(0.0, 0.0, 16.0, 16.0),
-                    TextureRegion::new(16.0, 0.0, 16.0, 16.0),
-                    TextureRegion::new(32.0, 0.0, 16.0, 16.0),
-                    TextureRegion::new(48.0, 0.0, 16.0, 16.0),
-                    TextureRegion::new(64.0, 0.0, 16.0, 16.0),
-                    TextureRegion::new(80.0, 0.0, 16.0, 16.0),
-                ],
-                current_keyframe: 0,
-                start_instant: Instant::now(),
-                frame_duration: 100,
                 flip_x: true,
+                ..AnimationState::from_grid(16.0, 16.0, 6, 1, 6).unwrap()
             },
         },
     ));
 
     let mut graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
+    graphics.preload_textures(&["examples/sprite/sprite.png", "examples/sprite/sprite2.png"]);
     engine.add_system_bundle(Graphics::default_system_bundle());
 
     WinitTuberRunner.run(engine, graphics)
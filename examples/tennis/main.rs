@@ -2,7 +2,7 @@ use tuber::ecs::ecs::Ecs;
 use tuber::ecs::query::accessors::{R, W};
 use tuber::ecs::system::SystemBundle;
 use tuber::graphics::camera::{Active, OrthographicCamera};
-use tuber::graphics::{shape::*, Graphics};
+use tuber::graphics::{shape::*, Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
 use tuber::*;
@@ -11,10 +11,11 @@ use tuber_common::transform::Transform2D;
 const BALL_COUNT: usize = 20;
 const PADDLE_WIDTH: f32 = 20.0;
 const PADDLE_HEIGHT: f32 = 100.0;
-const BALL_SIZE: f32 = 10.0;
+const BALL_RADIUS: f32 = 5.0;
+const BALL_DIAMETER: f32 = BALL_RADIUS * 2.0;
 const LEFT_PADDLE_INITIAL_POSITION: (f32, f32) = (50.0, 250.0);
 const RIGHT_PADDLE_INITIAL_POSITION: (f32, f32) = (730.0, 250.0);
-const BALL_INITIAL_POSITION: (f32, f32) = (395.0, 295.0);
+const BALL_INITIAL_POSITION: (f32, f32) = (400.0, 300.0);
 
 struct Ball;
 struct Paddle;
@@ -36,6 +37,7 @@ fn main() -> tuber::Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -48,7 +50,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: PADDLE_WIDTH,
             height: PADDLE_HEIGHT,
-            color: (1.0, 1.0, 1.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(1.0, 1.0, 1.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: LEFT_PADDLE_INITIAL_POSITION,
@@ -62,7 +66,9 @@ fn main() -> tuber::Result<()> {
         RectangleShape {
             width: PADDLE_WIDTH,
             height: PADDLE_HEIGHT,
-            color: (1.0, 1.0, 1.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(1.0, 1.0, 1.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: RIGHT_PADDLE_INITIAL_POSITION,
@@ -75,14 +81,14 @@ fn main() -> tuber::Result<()> {
     let mut rng = rand::thread_rng();
     for _ in 0..BALL_COUNT {
         let _ball = engine.ecs().insert((
-            RectangleShape {
-                width: BALL_SIZE,
-                height: BALL_SIZE,
-                color: (
+            CircleShape {
+                radius: BALL_RADIUS,
+                color: Color::rgb(
                     rng.gen_range(0.0..=1.0),
                     rng.gen_range(0.0..=1.0),
                     rng.gen_range(0.0..=1.0),
                 ),
+                thickness: None,
             },
             Velocity {
                 x: rng.gen_range(-10.0..=-5.0),
@@ -90,7 +96,6 @@ fn main() -> tuber::Result<()> {
             },
             Transform2D {
                 translation: BALL_INITIAL_POSITION,
-                rotation_center: (BALL_SIZE / 2.0, BALL_SIZE / 2.0),
                 ..Default::default()
             },
             Ball,
@@ -105,7 +110,7 @@ fn main() -> tuber::Result<()> {
     bundle.add_system(move_paddle_system);
     bundle.add_system(collision_system);
     engine.add_system_bundle(Graphics::default_system_bundle());
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
 
     runner.run(engine, graphics)
 }
@@ -122,17 +127,17 @@ fn move_paddle_system(ecs: &mut Ecs) {
 }
 
 fn move_ball_system(ecs: &mut Ecs) {
-    for (_id, (rectangle_shape, mut transform, mut velocity)) in
-        ecs.query::<(R<RectangleShape>, W<Transform2D>, W<Velocity>)>()
+    for (_id, (circle_shape, mut transform, mut velocity)) in
+        ecs.query::<(R<CircleShape>, W<Transform2D>, W<Velocity>)>()
     {
-        if (transform.translation.0 + rectangle_shape.width >= 800.0)
-            || (transform.translation.0 <= 0.0)
+        if (transform.translation.0 + circle_shape.radius >= 800.0)
+            || (transform.translation.0 - circle_shape.radius <= 0.0)
         {
             velocity.x = -velocity.x;
         }
 
-        if (transform.translation.1 + rectangle_shape.height >= 600.0)
-            || (transform.translation.1 <= 0.0)
+        if (transform.translation.1 + circle_shape.radius >= 600.0)
+            || (transform.translation.1 - circle_shape.radius <= 0.0)
         {
             velocity.y = -velocity.y;
         }
@@ -152,11 +157,14 @@ fn collision_system(ecs: &mut Ecs) {
             for (_ball_id, (mut ball_transform, mut velocity, _)) in
                 ecs.query::<(W<Transform2D>, W<Velocity>, R<Ball>)>()
             {
-                let ball_position = ball_transform.translation;
+                let ball_position = (
+                    ball_transform.translation.0 - BALL_RADIUS,
+                    ball_transform.translation.1 - BALL_RADIUS,
+                );
 
                 if !ball_is_close_to_paddle(
                     ball_position,
-                    BALL_SIZE,
+                    BALL_DIAMETER,
                     paddle_transform.translation,
                     PADDLE_WIDTH,
                     PADDLE_HEIGHT,
@@ -165,12 +173,12 @@ fn collision_system(ecs: &mut Ecs) {
                 }
 
                 if ball_position.0 < paddle_position.0 + paddle_shape.width
-                    && ball_position.0 + BALL_SIZE > paddle_position.0
+                    && ball_position.0 + BALL_DIAMETER > paddle_position.0
                     && ball_position.1 > paddle_position.1
-                    && ball_position.1 + BALL_SIZE < paddle_position.1 + paddle_shape.height
+                    && ball_position.1 + BALL_DIAMETER < paddle_position.1 + paddle_shape.height
                 {
                     ball_transform.translation.0 += if velocity.x >= 0.0 {
-                        -(ball_position.0 + BALL_SIZE - paddle_position.0)
+                        -(ball_position.0 + BALL_DIAMETER - paddle_position.0)
                     } else {
                         paddle_position.0 + paddle_shape.width - ball_position.0
                     };
@@ -178,12 +186,12 @@ fn collision_system(ecs: &mut Ecs) {
                 }
 
                 if ball_position.1 < paddle_position.1 + paddle_shape.height
-                    && ball_position.1 + BALL_SIZE > paddle_position.1
+                    && ball_position.1 + BALL_DIAMETER > paddle_position.1
                     && ball_position.0 > paddle_position.0
-                    && ball_position.0 + BALL_SIZE < paddle_position.0 + paddle_shape.width
+                    && ball_position.0 + BALL_DIAMETER < paddle_position.0 + paddle_shape.width
                 {
                     ball_transform.translation.1 += if velocity.y >= 0.0 {
-                        -(ball_position.1 + BALL_SIZE - paddle_position.1)
+                        -(ball_position.1 + BALL_DIAMETER - paddle_position.1)
                     } else {
                         paddle_position.1 + paddle_shape.height - ball_position.1
                     };
@@ -1,10 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tuber::ecs::ecs::Ecs;
-use tuber::ecs::query::accessors::{R, W};
+use tuber::ecs::query::accessors::W;
 use tuber::ecs::system::SystemBundle;
 use tuber::graphics::camera::{Active, OrthographicCamera};
-use tuber::graphics::tilemap::TilemapRender;
-use tuber::graphics::Graphics;
+use tuber::graphics::tilemap::{TilemapProjection, TilemapRender};
+use tuber::graphics::{Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
 use tuber::Input::{KeyDown, KeyUp};
@@ -25,6 +25,7 @@ fn main() -> tuber::Result<()> {
             bottom: 150.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -66,7 +67,15 @@ fn main() -> tuber::Result<()> {
 
                 return None;
             }),
+            layer: 0,
             dirty: true,
+            dirty_tiles: HashSet::new(),
+            animations: HashMap::new(),
+            animation_time: 0.0,
+            texel_inset: true,
+            tint: Color::WHITE,
+            tile_tint_function: None,
+            projection: TilemapProjection::Orthogonal,
         },
     ));
 
@@ -76,19 +85,20 @@ fn main() -> tuber::Result<()> {
 
     let mut runner = WinitTuberRunner;
     let mut graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
+    graphics.set_pixel_snapping(true);
 
     let mut bundle = SystemBundle::new();
     bundle.add_system(move_camera_system);
     engine.add_system_bundle(Graphics::default_system_bundle());
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
 
     runner.run(engine, graphics)
 }
 
 fn move_camera_system(ecs: &mut Ecs) {
     let input_state = ecs.shared_resource::<InputState>().unwrap();
-    let (_, (_, mut transform)) = ecs
-        .query_one::<(R<OrthographicCamera>, W<Transform2D>)>()
+    let (_, (mut camera, mut transform)) = ecs
+        .query_one::<(W<OrthographicCamera>, W<Transform2D>)>()
         .unwrap();
 
     if input_state.is(KeyDown(Key::Z)) && input_state.is(KeyUp(Key::S)) {
@@ -104,10 +114,8 @@ fn move_camera_system(ecs: &mut Ecs) {
     }
 
     if input_state.is(KeyDown(Key::A)) && input_state.is(KeyUp(Key::E)) {
-        transform.scale.0 += 0.01;
-        transform.scale.1 += 0.01;
+        camera.zoom += 0.01;
     } else if input_state.is(KeyDown(Key::E)) && input_state.is(KeyUp(Key::A)) {
-        transform.scale.0 -= 0.01;
-        transform.scale.1 -= 0.01;
+        camera.zoom = (camera.zoom - 0.01).max(0.01);
     }
 }
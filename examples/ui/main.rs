@@ -1,7 +1,7 @@
 use tuber::graphics::camera::{Active, OrthographicCamera};
 use tuber::graphics::shape::RectangleShape;
 use tuber::graphics::ui::{Frame, NoViewTransform, Text};
-use tuber::graphics::Graphics;
+use tuber::graphics::{Color, Graphics};
 use tuber::graphics_wgpu::GraphicsWGPU;
 use tuber::keyboard::Key;
 use tuber::Input::{KeyDown, KeyUp};
@@ -20,6 +20,7 @@ fn main() -> Result<()> {
             bottom: 600.0,
             near: -100.0,
             far: 100.0,
+            ..Default::default()
         },
         Transform2D {
             translation: (0.0, 0.0),
@@ -32,7 +33,9 @@ fn main() -> Result<()> {
         RectangleShape {
             width: 100.0,
             height: 100.0,
-            color: (0.0, 0.0, 1.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(0.0, 0.0, 1.0),
+            corner_radius: 0.0,
         },
         Transform2D {
             translation: (100.0, 100.0),
@@ -44,7 +47,9 @@ fn main() -> Result<()> {
         RectangleShape {
             width: 100.0,
             height: 100.0,
-            color: (0.0, 1.0, 1.0),
+            anchor: (0.0, 0.0),
+            color: Color::rgb(0.0, 1.0, 1.0),
+            corner_radius: 16.0,
         },
         Transform2D {
             translation: (200.0, 200.0),
@@ -65,7 +70,7 @@ fn main() -> Result<()> {
         Frame {
             width: 200.0,
             height: 50.0,
-            color: (1.0, 0.0, 0.0),
+            color: Color::rgb(1.0, 0.0, 0.0),
         },
         Transform2D {
             translation: (75.0, 0.0),
@@ -75,12 +80,12 @@ fn main() -> Result<()> {
     ));
 
     let mut graphics = Graphics::new(Box::new(GraphicsWGPU::new()));
-    graphics.set_clear_color((1.0, 1.0, 1.0));
+    graphics.set_clear_color(Color::rgb(1.0, 1.0, 1.0));
     let mut bundle = SystemBundle::new();
     bundle.add_system(move_camera_right_system);
     bundle.add_system(lose_health_system);
     engine.add_system_bundle(Graphics::default_system_bundle());
-    engine.add_system_bundle(bundle);
+    engine.add_fixed_system_bundle(bundle);
     WinitTuberRunner.run(engine, graphics)
 }
 
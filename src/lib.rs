@@ -1,6 +1,9 @@
 pub use tuber_common as common;
-pub use tuber_core::{ecs, input::*, DeltaTime, Engine, Error, Result, TuberRunner};
+pub use tuber_core::{
+    ecs, input::*, window::WindowSettings, DeltaTime, Engine, Error, Paused, Result, TuberRunner,
+    FIXED_DELTA_TIME,
+};
 pub use tuber_graphics as graphics;
 pub use tuber_graphics_wgpu as graphics_wgpu;
 pub use tuber_physics as physics;
-pub use tuber_winit::WinitTuberRunner;
+pub use tuber_winit::{PersistedWindowGeometry, WinitTuberRunner};